@@ -1,16 +1,31 @@
 use crate::game;
 use crate::game::{CurrentScreen, GameState};
+use crate::keybindings::KeyAction;
+use crate::loading_screen::LoadingScreen;
+use crate::new_game_menu::{NewGameAction, NewGameMenu};
 use crate::pause_menu::{PauseMenu, PauseMenuAction};
-use crate::ui::text::TextRenderer;
-use crate::upgrade_menu::{UpgradeMenu, UpgradeMenuAction};
+use crate::ui::batch;
+use crate::ui::button::ClickButtons;
+use crate::ui::debug_overlay::DebugOverlay;
+use crate::ui::offscreen::OffscreenTarget;
+use crate::ui::pipeline::{supported_sample_count, MultisampleTarget, PipelineConfig};
+use crate::ui::ring::{Ring, RingRenderer};
+use crate::ui::scaling::ScalingConfig;
+use crate::ui::text::{game_ui_ids, SharedTextRenderer, TextRenderer};
+use crate::upgrade_menu::{UpgradeMenu, UpgradeMenuAction, UPGRADE_ICON_TEXTURES};
 use egui_wgpu::wgpu;
 use egui_wgpu::wgpu::SurfaceError;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use winit::application::ApplicationHandler;
 use winit::dpi::PhysicalSize;
 use winit::event::{ElementState, WindowEvent};
-use winit::event_loop::ActiveEventLoop;
-use winit::window::{Window, WindowId};
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::keyboard::PhysicalKey;
+use winit::window::{CursorGrabMode, CursorIcon, Window, WindowId};
 
 pub struct AppState {
     pub device: wgpu::Device,
@@ -19,10 +34,34 @@ pub struct AppState {
     pub surface: wgpu::Surface<'static>,
     pub pause_menu: PauseMenu,
     pub upgrade_menu: UpgradeMenu,
-    pub text_renderer: TextRenderer,
+    pub loading_screen: LoadingScreen,
+    pub new_game_menu: NewGameMenu,
+    /// Draws the game UI and, each under its own namespace, the menus' text
+    pub text_renderer: SharedTextRenderer,
+    pub debug_overlay: DebugOverlay,
+    /// Countdown arc drawn next to the timer text
+    pub timer_ring: RingRenderer,
     pub game_state: GameState,
+    /// Clear color for screens without a backdrop of their own
+    pub background_color: [f32; 4],
+    /// Per-screen clear colors, e.g. for the main menu. Menus that set their
+    /// own `background_color` take precedence.
+    pub screen_backgrounds: HashMap<CurrentScreen, [f32; 4]>,
+    /// Mouse buttons that restart the game from the game over screen
+    pub game_over_click: ClickButtons,
+    /// What every UI pipeline was built for; fixed after startup
+    pipeline_config: PipelineConfig,
+    /// Present modes the surface supports, see `set_present_mode`
+    present_modes: Vec<wgpu::PresentMode>,
+    /// Drawn into and resolved to the frame's view when MSAA is on
+    msaa_target: Option<MultisampleTarget>,
+    /// Textures still to load on the loading screen, one per frame, as `(id, path)`
+    pending_textures: VecDeque<(&'static str, &'static str)>,
 }
 
+/// Muted blue behind the game
+pub const DEFAULT_BACKGROUND_COLOR: [f32; 4] = [0.18, 0.24, 0.32, 1.0];
+
 impl AppState {
     async fn new(
         instance: &wgpu::Instance,
@@ -30,6 +69,7 @@ impl AppState {
         window: &Window,
         width: u32,
         height: u32,
+        msaa_samples: u32,
     ) -> Self {
         let power_pref = wgpu::PowerPreference::default();
         let adapter = instance
@@ -41,7 +81,9 @@ impl AppState {
             .await
             .expect("Failed to find an appropriate adapter");
 
-        let features = wgpu::Features::empty();
+        // Needed for MSAA sample counts other than 4
+        let features =
+            adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -76,12 +118,75 @@ impl AppState {
 
         surface.configure(&device, &surface_config);
 
-        let pause_menu = PauseMenu::new(&device, &queue, surface_config.format, window);
-        let upgrade_menu = UpgradeMenu::new(&device, &queue, surface_config.format, window);
-        let mut text_renderer = TextRenderer::new(&device, &queue, surface_config.format, window);
-        let mut game_state = GameState::new();
-        game_state.game_ui.start_timer(None);
-        game::initialize_game_ui(&mut text_renderer, &game_state.game_ui, window);
+        let msaa_samples = msaa_samples.max(1);
+        let sample_count =
+            supported_sample_count(&adapter, &device, surface_config.format, msaa_samples);
+        if sample_count != msaa_samples {
+            println!(
+                "{}x MSAA is not supported, using {}x instead",
+                msaa_samples, sample_count
+            );
+        }
+        let pipeline_config = PipelineConfig::default().with_sample_count(sample_count);
+        let msaa_target = (sample_count > 1).then(|| {
+            MultisampleTarget::new(&device, width, height, surface_config.format, sample_count)
+        });
+
+        let format = surface_config.format;
+        let size = window.inner_size();
+        // One renderer, atlas and prepare for all text on screen
+        let text_renderer: SharedTextRenderer = Rc::new(RefCell::new(
+            TextRenderer::new_with_config(&device, &queue, format, size, &pipeline_config),
+        ));
+        let pause_menu = PauseMenu::with_text_renderer(
+            &device,
+            &queue,
+            format,
+            size,
+            &pipeline_config,
+            &text_renderer,
+        );
+        let upgrade_menu = UpgradeMenu::with_text_renderer(
+            &device,
+            &queue,
+            format,
+            size,
+            &pipeline_config,
+            &text_renderer,
+        );
+        let loading_screen = LoadingScreen::with_text_renderer(
+            &device,
+            &queue,
+            format,
+            size,
+            &pipeline_config,
+            &text_renderer,
+        );
+        let new_game_menu = NewGameMenu::with_text_renderer(
+            &device,
+            &queue,
+            format,
+            size,
+            &pipeline_config,
+            &text_renderer,
+        );
+        let mut debug_overlay =
+            DebugOverlay::new_with_config(&device, format, width, height, &pipeline_config);
+        debug_overlay.add_vertical_guide(0.5);
+        debug_overlay.add_horizontal_guide(0.5);
+        let mut timer_ring = RingRenderer::new_with_config(&device, format, &pipeline_config);
+        timer_ring.resize(width as f32, height as f32);
+        // The timer starts once loading is done
+        let game_state = GameState::new();
+        game::initialize_game_ui(
+            &mut text_renderer.borrow_mut(),
+            &game_state.game_ui,
+            window.inner_size(),
+        );
+        // Created hidden, shown when the timer runs out
+        text_renderer
+            .borrow_mut()
+            .create_game_over_display(width, height);
         Self {
             device,
             queue,
@@ -89,127 +194,216 @@ impl AppState {
             surface_config,
             pause_menu,
             upgrade_menu,
+            loading_screen,
+            new_game_menu,
             text_renderer,
+            debug_overlay,
+            timer_ring,
             game_state,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            screen_backgrounds: HashMap::new(),
+            game_over_click: ClickButtons::Any,
+            pipeline_config,
+            present_modes: swapchain_capabilities.present_modes,
+            msaa_target,
+            pending_textures: UPGRADE_ICON_TEXTURES.into_iter().collect(),
         }
     }
 
-    fn resize_surface(&mut self, width: u32, height: u32, window: &Window) {
-        self.surface_config.width = width;
-        self.surface_config.height = height;
-        self.surface.configure(&self.device, &self.surface_config);
-        let resolution = glyphon::Resolution { width, height };
-        self.pause_menu.resize(&self.queue, resolution);
-        self.upgrade_menu.resize(&self.queue, resolution);
-        self.text_renderer.resize(&self.queue, resolution);
-        // Re-initialize game UI text positions with the actual window
-        game::initialize_game_ui(&mut self.text_renderer, &self.game_state.game_ui, window);
-    }
-}
-
-pub struct App {
-    instance: wgpu::Instance,
-    state: Option<AppState>,
-    window: Option<Arc<Window>>,
-}
+    /// Load the next pending texture and advance the loading bar; once
+    /// everything is loaded, go on to the new game screen
+    fn advance_loading(&mut self) {
+        let total = UPGRADE_ICON_TEXTURES.len();
+        if let Some((texture_id, path)) = self.pending_textures.pop_front() {
+            if let Err(e) = self.upgrade_menu.button_manager.icon_renderer.load_texture(
+                &self.device,
+                &self.queue,
+                path,
+                texture_id,
+            ) {
+                println!(
+                    "Failed to load texture '{}' from {}: {}",
+                    texture_id, path, e
+                );
+            }
+        }
+        let loaded = total - self.pending_textures.len();
+        self.loading_screen
+            .set_progress(loaded as f32 / total.max(1) as f32);
 
-impl App {
-    pub fn new() -> Self {
-        let instance = egui_wgpu::wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
-        Self {
-            instance,
-            state: None,
-            window: None,
+        if self.pending_textures.is_empty() {
+            self.game_state.current_screen = CurrentScreen::NewGame;
         }
     }
 
-    async fn set_window(&mut self, window: Window) {
-        let window = Arc::new(window);
-        let initial_width = 1360;
-        let initial_height = 768;
-
-        let _ = window.request_inner_size(PhysicalSize::new(initial_width, initial_height));
+    /// Start a run with the seed and difficulty picked on the new game screen
+    fn start_new_game(&mut self) {
+        let config = self.new_game_menu.config();
+        println!(
+            "Starting {} game with seed '{}'",
+            config.difficulty.label(),
+            config.seed
+        );
+        self.game_state
+            .game_ui
+            .start_timer(Some(config.difficulty.timer_config()));
+        self.game_state.new_game = config;
+        self.game_state.current_screen = CurrentScreen::Game;
+    }
 
-        let surface = self
-            .instance
-            .create_surface(window.clone())
-            .expect("Failed to create surface!");
+    /// Attachment for one of the frame's passes: `view` itself, or the
+    /// multisampled texture resolving into it when MSAA is on
+    fn color_attachment<'a>(
+        &'a self,
+        view: &'a wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        match &self.msaa_target {
+            Some(target) => target.attachment(view, load),
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            },
+        }
+    }
 
-        let state = AppState::new(
-            &self.instance,
-            surface,
-            &window,
-            initial_width,
-            initial_width,
-        )
-        .await;
+    /// Stop the run and show the game over text
+    fn enter_game_over(&mut self) {
+        self.game_state.current_screen = CurrentScreen::GameOver;
+        self.game_state.game_ui.stop_timer();
+        self.text_renderer.borrow_mut().show_game_over_display();
+    }
 
-        self.window.get_or_insert(window);
-        self.state.get_or_insert(state);
+    /// Leave the game over screen with a fresh timer
+    fn restart_after_game_over(&mut self) {
+        self.text_renderer.borrow_mut().hide_game_over_display();
+        self.game_state
+            .game_ui
+            .start_timer(Some(self.game_state.new_game.difficulty.timer_config()));
+        self.game_state.current_screen = CurrentScreen::Game;
     }
 
-    fn handle_resized(&mut self, width: u32, height: u32) {
-        if width > 0 && height > 0 {
-            if let Some(window) = self.window.as_ref() {
-                self.state
-                    .as_mut()
-                    .unwrap()
-                    .resize_surface(width, height, window);
-            }
-        }
+    /// Clear color for the current screen: the open menu's backdrop, then the
+    /// screen's entry in `screen_backgrounds`, then `background_color`
+    fn current_background(&self) -> [f32; 4] {
+        let screen = self.game_state.current_screen;
+        let menu_background = match screen {
+            CurrentScreen::Pause => self.pause_menu.background_color,
+            CurrentScreen::Upgrade => self.upgrade_menu.background_color,
+            CurrentScreen::NewGame => self.new_game_menu.background_color,
+            _ => None,
+        };
+        menu_background
+            .or_else(|| self.screen_backgrounds.get(&screen).copied())
+            .unwrap_or(self.background_color)
     }
 
-    fn handle_redraw(&mut self) {
-        // Handle minimizing window
-        if let Some(window) = self.window.as_ref() {
-            if let Some(min) = window.is_minimized() {
-                if min {
-                    return;
-                }
-            }
+    /// Reconfigure the surface to present with `present_mode`. Fails, keeping
+    /// the current mode, if the surface doesn't support it; the `Auto` modes
+    /// are always accepted since wgpu falls back to a supported mode for them.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> Result<(), String> {
+        let automatic = matches!(
+            present_mode,
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+        );
+        if !automatic && !self.present_modes.contains(&present_mode) {
+            return Err(format!("Present mode '{:?}' not supported", present_mode));
         }
+        self.surface_config.present_mode = present_mode;
+        self.surface.configure(&self.device, &self.surface_config);
+        Ok(())
+    }
 
-        let state = self.state.as_mut().unwrap();
-
-        let surface_texture = state.surface.get_current_texture();
-
-        match surface_texture {
-            Err(SurfaceError::Outdated) => {
-                return;
-            }
-            Err(_) => {
-                surface_texture.expect("Failed to acquire next swap chain texture");
-                return;
+    /// Switch between vsync and presenting as soon as a frame is ready, to
+    /// measure real frame times. Returns the new mode.
+    pub fn toggle_vsync(&mut self) -> Result<wgpu::PresentMode, String> {
+        let present_mode = match self.surface_config.present_mode {
+            wgpu::PresentMode::Immediate | wgpu::PresentMode::Mailbox => {
+                wgpu::PresentMode::AutoVsync
             }
-            Ok(_) => {}
+            // `AutoNoVsync` quietly falls back to vsync, so pick a real mode
+            _ => [wgpu::PresentMode::Immediate, wgpu::PresentMode::Mailbox]
+                .into_iter()
+                .find(|mode| self.present_modes.contains(mode))
+                .ok_or_else(|| "No present mode without vsync is supported".to_string())?,
         };
+        self.set_present_mode(present_mode)?;
+        Ok(present_mode)
+    }
 
-        let surface_texture = surface_texture.unwrap();
+    fn resize_surface(&mut self, width: u32, height: u32, window: &Window) {
+        self.surface_config.width = width;
+        self.surface_config.height = height;
+        self.surface.configure(&self.device, &self.surface_config);
+        if self.msaa_target.is_some() {
+            self.msaa_target = Some(MultisampleTarget::new(
+                &self.device,
+                width,
+                height,
+                self.surface_config.format,
+                self.pipeline_config.sample_count,
+            ));
+        }
+        let resolution = glyphon::Resolution { width, height };
+        self.pause_menu.resize(&self.queue, resolution);
+        self.upgrade_menu.resize(&self.queue, resolution);
+        self.loading_screen.resize(&self.queue, resolution);
+        self.new_game_menu.resize(&self.queue, resolution);
+        self.text_renderer
+            .borrow_mut()
+            .resize(&self.queue, resolution);
+        self.debug_overlay.resize(width, height);
+        self.timer_ring.resize(width as f32, height as f32);
+        // Re-initialize game UI text positions with the actual window
+        game::initialize_game_ui(
+            &mut self.text_renderer.borrow_mut(),
+            &self.game_state.game_ui,
+            window.inner_size(),
+        );
+        // Auto-size every buffer that registered a reflow handler
+        self.text_renderer.borrow_mut().reflow(width, height);
+        self.sync_cursor_space(window);
+    }
 
-        let surface_view = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    /// Let the menus map cursor positions from the window into surface
+    /// pixels, in case the window's inner size and the surface differ
+    fn sync_cursor_space(&mut self, window: &Window) {
+        let size = window.inner_size();
+        self.pause_menu.button_manager.set_cursor_space(size);
+        self.pause_menu
+            .confirm_dialog
+            .button_manager
+            .set_cursor_space(size);
+        self.upgrade_menu.button_manager.set_cursor_space(size);
+        self.loading_screen.button_manager.set_cursor_space(size);
+        self.new_game_menu.button_manager.set_cursor_space(size);
+    }
 
-        let mut encoder = state
+    /// Draw one frame of the game UI and menus into `view`, which is the
+    /// surface texture normally or an `OffscreenTarget` for screenshots
+    fn render_frame(&mut self, view: &wgpu::TextureView) {
+        batch::begin_frame();
+        let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-        // Clear the screen with a muted blue background
+        // The screen's background is the first layer
+        let [r, g, b, a] = self.current_background();
         {
             let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.18, // muted blue
-                            g: 0.24,
-                            b: 0.32,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[Some(self.color_attachment(
+                    view,
+                    wgpu::LoadOp::Clear(wgpu::Color {
+                        r: r as f64,
+                        g: g as f64,
+                        b: b as f64,
+                        a: a as f64,
+                    }),
+                ))],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
                 label: Some("clear screen render pass"),
@@ -217,74 +411,59 @@ impl App {
             });
         }
 
-        // --- Draw vertical dashed green line at center ---
-        if state.pause_menu.is_debug_panel_visible() {
-            let w = state.surface_config.width as f32;
-            let h = state.surface_config.height as f32;
-            let center_x = w / 2.0;
-            let dash_height: f32 = 16.0;
-            let dash_gap: f32 = 12.0;
-            let dash_width = 3.0;
-            let color = [0.1, 1.0, 0.1, 0.85]; // bright green, mostly opaque
-            let mut dashes = Vec::new();
-            let mut y = 0.0;
-            while y < h {
-                let dash_h = dash_height.min(h - y);
-                dashes.push(crate::ui::rectangle::Rectangle::new(
-                    center_x - dash_width / 2.0,
-                    y,
-                    dash_width,
-                    dash_h,
-                    color,
-                ));
-                y += dash_height + dash_gap;
-            }
-            // Use the pause_menu's rectangle_renderer for simplicity (always present)
-            let renderer = &mut state.pause_menu.button_manager.rectangle_renderer;
-            for dash in dashes {
-                renderer.add_rectangle(dash);
-            }
-            // Render the dashes before anything else
+        // --- Debug guides, shown together with the debug panel ---
+        self.debug_overlay
+            .set_visible(self.pause_menu.is_debug_panel_visible());
+        if self.debug_overlay.is_visible() {
+            // Render the guides before anything else
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[Some(self.color_attachment(view, wgpu::LoadOp::Load))],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
-                label: Some("center line render pass"),
+                label: Some("debug overlay render pass"),
                 occlusion_query_set: None,
             });
-            renderer.render(&state.device, &mut render_pass);
+            self.debug_overlay
+                .render(&self.device, &self.queue, &mut render_pass);
         }
-        // --- End vertical dashed line ---
+        // --- End debug guides ---
+
+        // Frame timing feeds the debug panel and all animations
+        self.game_state.update_performance_metrics();
+        // Cap the step so the first frame after an idle wait doesn't skip the animation
+        let delta_time = self.game_state.delta_time.min(1.0 / 30.0);
 
         // --- Game UI: update and render timer/score/level ---
         // Update timer/score/level based on current_screen
-        game::update_game_ui(
-            &mut state.text_renderer,
-            &mut state.game_state.game_ui,
-            &state.game_state.current_screen,
+        let timer_expired = game::update_game_ui(
+            &mut self.text_renderer.borrow_mut(),
+            &mut self.game_state.game_ui,
+            &self.game_state.current_screen,
+            delta_time,
         );
+        if timer_expired && self.game_state.current_screen == CurrentScreen::Game {
+            self.enter_game_over();
+        }
 
-        // --- Debug Info Panel ---
-        if state.pause_menu.is_debug_panel_visible() {
-            // Update performance metrics
-            state.game_state.update_performance_metrics();
+        self.pause_menu.update(delta_time);
+        self.upgrade_menu.update(delta_time);
+        self.new_game_menu.update(delta_time);
 
-            let window_size = &state.surface_config;
-            let debug_text = format!(
+        // --- Debug Info Panel ---
+        if self.pause_menu.is_debug_panel_visible() {
+            let window_size = &self.surface_config;
+            let mut debug_text = format!(
                 "Window: {}x{} | FPS: {} | Avg Frame: {:.2}ms",
                 window_size.width,
                 window_size.height,
-                state.game_state.current_fps,
-                state.game_state.avg_frame_time * 1000.0
+                self.game_state.current_fps,
+                self.game_state.avg_frame_time * 1000.0
             );
-            use crate::ui::text::{TextPosition, TextStyle};
+            if let Some(transition) = self.pause_menu.last_transition_text() {
+                debug_text.push_str("\nLast input: ");
+                debug_text.push_str(&transition);
+            }
+            use crate::ui::text::{TextDirection, TextPosition, TextStyle, TextWrap};
             use glyphon::Color;
             let style = TextStyle {
                 font_family: "HankenGrotesk".to_string(),
@@ -293,191 +472,393 @@ impl App {
                 color: Color::rgb(220, 40, 40),
                 weight: glyphon::Weight::BOLD,
                 style: glyphon::Style::Normal,
+                wrap: TextWrap::Word,
+                direction: TextDirection::Auto,
+                letter_spacing: 0.0,
             };
             let pos = TextPosition {
                 x: window_size.width as f32 - 420.0,
                 y: 20.0,
                 max_width: Some(400.0),
-                max_height: Some(40.0),
+                max_height: Some(80.0), // Room for the input line
             };
-            state.text_renderer.create_text_buffer(
-                "debug_info",
+            self.text_renderer.borrow_mut().create_text_buffer(
+                game_ui_ids::DEBUG_INFO,
                 &debug_text,
                 Some(style),
                 Some(pos),
             );
+            // Keep debug info above game labels it may overlap
+            let _ = self
+                .text_renderer
+                .borrow_mut()
+                .set_z_index(game_ui_ids::DEBUG_INFO, 100);
         } else {
-            // Hide debug info by making it transparent if it exists
-            if let Some(buf) = state.text_renderer.text_buffers.get_mut("debug_info") {
-                buf.visible = false;
+            // Hide debug info if it exists; set_visible only dirties the renderer on change
+            if self
+                .text_renderer
+                .borrow()
+                .is_visible(game_ui_ids::DEBUG_INFO)
+            {
+                let _ = self
+                    .text_renderer
+                    .borrow_mut()
+                    .set_visible(game_ui_ids::DEBUG_INFO, false);
             }
         }
-        // Prepare and render text BEFORE pause menu overlay
+        // Countdown ring to the left of the timer text, in the timer's color
+        self.timer_ring.clear_rings();
+        if let Some((x, y, _w, h)) = self
+            .text_renderer
+            .borrow()
+            .get_buffer_bounds(game_ui_ids::MAIN_TIMER)
+        {
+            let radius = h * 0.4;
+            let color = self.game_state.game_ui.get_timer_color();
+            self.timer_ring.add_ring(
+                Ring::new(x - radius - 12.0, y + h / 2.0, radius, radius * 0.3)
+                    .with_fraction(self.game_state.game_ui.get_timer_fraction())
+                    .with_color([
+                        color.r() as f32 / 255.0,
+                        color.g() as f32 / 255.0,
+                        color.b() as f32 / 255.0,
+                        color.a() as f32 / 255.0,
+                    ]),
+            );
+        }
+
+        // Only the menu of the current screen is visible
+        if self.game_state.current_screen == CurrentScreen::Pause {
+            self.pause_menu.show(self.game_state.test_mode);
+        } else {
+            self.pause_menu.hide();
+        }
+        if self.game_state.current_screen == CurrentScreen::Upgrade {
+            self.upgrade_menu.show();
+        } else {
+            self.upgrade_menu.hide();
+        }
+
+        if self.game_state.current_screen == CurrentScreen::Loading {
+            self.loading_screen.show();
+        } else {
+            self.loading_screen.hide();
+        }
+        if self.game_state.current_screen == CurrentScreen::NewGame {
+            self.new_game_menu.show();
+        } else {
+            self.new_game_menu.hide();
+        }
+
+        // One prepare for the game UI and every menu's text, then the game UI
+        // text is drawn BEFORE the menu overlay
         if let Err(e) =
-            state
-                .text_renderer
-                .prepare(&state.device, &state.queue, &state.surface_config)
+            self.text_renderer
+                .borrow_mut()
+                .prepare(&self.device, &self.queue, &self.surface_config)
         {
             println!("Failed to prepare text renderer: {}", e);
         }
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[Some(self.color_attachment(view, wgpu::LoadOp::Load))],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
                 label: Some("text render pass"),
                 occlusion_query_set: None,
             });
-            if let Err(e) = state.text_renderer.render(&mut render_pass) {
+            self.timer_ring
+                .render(&self.device, &self.queue, &mut render_pass);
+            if let Err(e) = self.text_renderer.borrow().render(&mut render_pass) {
                 println!("Failed to render text: {}", e);
             }
         }
         // --- End Game UI ---
 
-        // Show pause menu if current_screen == Pause
-        if state.game_state.current_screen == CurrentScreen::Pause {
-            state.pause_menu.show(state.game_state.test_mode);
-            // Prepare pause menu for rendering
-            if let Err(e) =
-                state
-                    .pause_menu
-                    .prepare(&state.device, &state.queue, &state.surface_config)
-            {
-                println!("Failed to prepare pause menu: {}", e);
-            }
-
-            // Create a render pass for the pause menu
+        // Menus draw into a pass set up here; hidden ones draw nothing
+        if self.pause_menu.is_visible()
+            || self.upgrade_menu.is_visible()
+            || self.loading_screen.is_visible()
+            || self.new_game_menu.is_visible()
+        {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[Some(self.color_attachment(view, wgpu::LoadOp::Load))],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
-                label: Some("pause menu render pass"),
+                label: Some("menu render pass"),
                 occlusion_query_set: None,
             });
+            if let Err(e) = self.pause_menu.render_to(
+                &self.device,
+                &self.queue,
+                &self.surface_config,
+                &mut render_pass,
+            ) {
+                println!("Failed to render pause menu: {}", e);
+            }
+            if let Err(e) = self.upgrade_menu.render_to(
+                &self.device,
+                &self.queue,
+                &self.surface_config,
+                &mut render_pass,
+            ) {
+                println!("Failed to render upgrade menu: {}", e);
+            }
+            if let Err(e) = self.loading_screen.render_to(
+                &self.device,
+                &self.queue,
+                &self.surface_config,
+                &mut render_pass,
+            ) {
+                println!("Failed to render loading screen: {}", e);
+            }
+            if let Err(e) = self.new_game_menu.render_to(
+                &self.device,
+                &self.queue,
+                &self.surface_config,
+                &mut render_pass,
+            ) {
+                println!("Failed to render new game menu: {}", e);
+            }
+        }
 
-            // --- Add semi-transparent grey overlay ---
-            let overlay_color = [0.08, 0.09, 0.11, 0.88]; // darker, neutral semi-transparent grey
-            let (w, h) = (
-                state.surface_config.width as f32,
-                state.surface_config.height as f32,
-            );
-            state
-                .pause_menu
-                .button_manager
-                .rectangle_renderer
-                .add_rectangle(crate::ui::rectangle::Rectangle::new(
-                    0.0,
-                    0.0,
-                    w,
-                    h,
-                    overlay_color,
-                ));
-            state
-                .pause_menu
-                .button_manager
-                .rectangle_renderer
-                .render(&state.device, &mut render_pass);
-            // --- End overlay ---
+        self.queue.submit(Some(encoder.finish()));
+    }
 
-            // Render the pause menu
-            if let Err(e) = state.pause_menu.render(&state.device, &mut render_pass) {
-                println!("Failed to render pause menu: {}", e);
+    /// Render one frame offscreen at the surface size and read it back as an image
+    pub fn render_to_image(&mut self) -> Result<image::RgbaImage, String> {
+        let target = OffscreenTarget::new(
+            &self.device,
+            self.surface_config.width,
+            self.surface_config.height,
+            self.surface_config.format,
+        );
+        self.render_frame(target.view());
+        target.read_to_image(&self.device, &self.queue)
+    }
+
+    /// Apply new font size bounds to the menus and game UI
+    pub fn set_scaling(&mut self, scaling: ScalingConfig) {
+        self.text_renderer.borrow_mut().scaling = scaling.clone();
+        self.pause_menu.set_scaling(scaling);
+        game::initialize_game_ui(
+            &mut self.text_renderer.borrow_mut(),
+            &self.game_state.game_ui,
+            PhysicalSize::new(self.surface_config.width, self.surface_config.height),
+        );
+    }
+
+    /// Whether the next frame must be drawn even without input: loading, a ticking
+    /// timer, a rolling score, the live FPS readout in the debug panel, a menu
+    /// animating in, or a blinking caret
+    fn needs_continuous_redraw(&self) -> bool {
+        self.game_state.current_screen == CurrentScreen::Loading
+            || self.game_state.game_ui.is_timer_ticking()
+            || self.game_state.game_ui.is_score_animating()
+            || self.pause_menu.is_debug_panel_visible()
+            || self.pause_menu.is_animating()
+            || self.upgrade_menu.is_animating()
+            || self.new_game_menu.is_animating()
+    }
+}
+
+/// Startup options for the app
+#[derive(Debug, Clone, Default)]
+pub struct AppConfig {
+    /// Maximum frames per second; 0 leaves the frame rate uncapped (display refresh)
+    pub target_fps: u32,
+    /// Font size bounds for text that scales with the window
+    pub scaling: ScalingConfig,
+    /// MSAA samples per pixel: 2, 4 or 8 smooth rounded corners and text
+    /// edges, 0 or 1 turns it off. Unsupported counts fall back to the
+    /// highest the GPU supports below them.
+    pub msaa_samples: u32,
+}
+
+pub struct App {
+    instance: wgpu::Instance,
+    state: Option<AppState>,
+    window: Option<Arc<Window>>,
+    config: AppConfig,
+    /// When the next frame is due while capped to `target_fps`. The event loop
+    /// waits until then instead of blocking inside a redraw.
+    next_frame: Option<Instant>,
+    /// Cursor icon last set on the window
+    cursor_icon: CursorIcon,
+    /// Keys currently held down, to tell presses from repeats
+    held_keys: HashSet<PhysicalKey>,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::with_config(AppConfig::default())
+    }
+
+    pub fn with_config(config: AppConfig) -> Self {
+        let instance = egui_wgpu::wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        Self {
+            instance,
+            state: None,
+            window: None,
+            config,
+            next_frame: None,
+            cursor_icon: CursorIcon::Default,
+            held_keys: HashSet::new(),
+        }
+    }
+
+    /// Time between frames when capped by `target_fps`, `None` when uncapped
+    fn frame_interval(&self) -> Option<Duration> {
+        (self.config.target_fps > 0)
+            .then(|| Duration::from_secs_f64(1.0 / self.config.target_fps as f64))
+    }
+
+    async fn set_window(&mut self, window: Window) {
+        let window = Arc::new(window);
+        let initial_width = 1360;
+        let initial_height = 768;
+
+        let _ = window.request_inner_size(PhysicalSize::new(initial_width, initial_height));
+
+        let surface = self
+            .instance
+            .create_surface(window.clone())
+            .expect("Failed to create surface!");
+
+        let mut state = AppState::new(
+            &self.instance,
+            surface,
+            &window,
+            initial_width,
+            initial_width,
+            self.config.msaa_samples,
+        )
+        .await;
+        state.set_scaling(self.config.scaling.clone());
+
+        self.window.get_or_insert(window);
+        self.state.get_or_insert(state);
+    }
+
+    fn handle_resized(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            if let Some(window) = self.window.as_ref() {
+                self.state
+                    .as_mut()
+                    .unwrap()
+                    .resize_surface(width, height, window);
             }
-        } else {
-            state.pause_menu.hide();
-            // Explicitly clear rectangles if menu is not visible
-            state
-                .pause_menu
-                .button_manager
-                .rectangle_renderer
-                .clear_rectangles();
-        }
-
-        // Show upgrade menu if current_screen == Upgrade
-        if state.game_state.current_screen == CurrentScreen::Upgrade {
-            state.upgrade_menu.show();
-            // Prepare upgrade menu for rendering
-            if let Err(e) =
-                state
-                    .upgrade_menu
-                    .prepare(&state.device, &state.queue, &state.surface_config)
-            {
-                println!("Failed to prepare upgrade menu: {}", e);
+        }
+    }
+
+    /// Captures and hides the cursor during gameplay and releases it on every
+    /// other screen. Only touches the window when the desired mode changes.
+    fn update_cursor_capture(&mut self) {
+        if let (Some(window), Some(state)) = (self.window.as_ref(), self.state.as_mut()) {
+            let capture = state.game_state.current_screen == CurrentScreen::Game;
+            if capture == state.game_state.capture_mouse {
+                return;
             }
+            state.game_state.capture_mouse = capture;
 
-            // Create a render pass for the upgrade menu
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                label: Some("upgrade menu render pass"),
-                occlusion_query_set: None,
-            });
+            let result = if capture {
+                // Locked isn't supported everywhere (e.g. X11), fall back to Confined
+                window
+                    .set_cursor_grab(CursorGrabMode::Locked)
+                    .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+            } else {
+                window.set_cursor_grab(CursorGrabMode::None)
+            };
+            if let Err(e) = result {
+                println!("Failed to update cursor grab: {}", e);
+            }
+            window.set_cursor_visible(!capture);
+        }
+    }
 
-            // --- Add semi-transparent grey overlay ---
-            let overlay_color = [0.08, 0.09, 0.11, 0.88]; // darker, neutral semi-transparent grey
-            let (w, h) = (
-                state.surface_config.width as f32,
-                state.surface_config.height as f32,
-            );
-            state
-                .upgrade_menu
-                .button_manager
-                .rectangle_renderer
-                .add_rectangle(crate::ui::rectangle::Rectangle::new(
-                    0.0,
-                    0.0,
-                    w,
-                    h,
-                    overlay_color,
-                ));
-            state
-                .upgrade_menu
-                .button_manager
-                .rectangle_renderer
-                .render(&state.device, &mut render_pass);
-            // --- End overlay ---
-
-            // Render the upgrade menu
-            if let Err(e) = state.upgrade_menu.render(&state.device, &mut render_pass) {
-                println!("Failed to render upgrade menu: {}", e);
+    /// Show a pointer over clickable buttons of the open menu. Only touches the
+    /// window when the icon changes.
+    fn update_cursor_icon(&mut self) {
+        if let (Some(window), Some(state)) = (self.window.as_ref(), self.state.as_ref()) {
+            let cursor_icon = state
+                .pause_menu
+                .desired_cursor()
+                .or_else(|| state.upgrade_menu.desired_cursor())
+                .or_else(|| state.new_game_menu.desired_cursor())
+                .unwrap_or_default();
+            if cursor_icon != self.cursor_icon {
+                window.set_cursor(cursor_icon);
+                self.cursor_icon = cursor_icon;
             }
-        } else {
-            state.upgrade_menu.hide();
-            // Explicitly clear rectangles if menu is not visible
-            state
-                .upgrade_menu
-                .button_manager
-                .rectangle_renderer
-                .clear_rectangles();
         }
+    }
 
-        state.queue.submit(Some(encoder.finish()));
-        surface_texture.present();
-        // Request another redraw to keep the timer updating
+    fn handle_redraw(&mut self, event_loop: &ActiveEventLoop) {
+        // Handle minimizing window
         if let Some(window) = self.window.as_ref() {
-            window.request_redraw();
+            if let Some(min) = window.is_minimized() {
+                if min {
+                    // Stop pacing until a restore redraws the window again
+                    self.next_frame = None;
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                    return;
+                }
+            }
+        }
+
+        // Too early for a capped frame: the one already scheduled draws instead
+        let frame_start = Instant::now();
+        if self
+            .next_frame
+            .is_some_and(|next_frame| frame_start < next_frame)
+        {
+            return;
+        }
+        self.next_frame = None;
+        event_loop.set_control_flow(ControlFlow::Wait);
+
+        let frame_interval = self.frame_interval();
+        let state = self.state.as_mut().unwrap();
+
+        let surface_texture = state.surface.get_current_texture();
+
+        match surface_texture {
+            Err(SurfaceError::Outdated) => {
+                return;
+            }
+            Err(_) => {
+                surface_texture.expect("Failed to acquire next swap chain texture");
+                return;
+            }
+            Ok(_) => {}
+        };
+
+        let surface_texture = surface_texture.unwrap();
+
+        let surface_view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        state.render_frame(&surface_view);
+        surface_texture.present();
+        // Load one asset per frame so the loading screen can show progress
+        if state.game_state.current_screen == CurrentScreen::Loading {
+            state.advance_loading();
+        }
+        // Only keep redrawing while something animates, otherwise idle until input
+        if state.needs_continuous_redraw() {
+            match frame_interval {
+                Some(interval) => {
+                    let next_frame = frame_start + interval;
+                    self.next_frame = Some(next_frame);
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                }
+                None => {
+                    if let Some(window) = self.window.as_ref() {
+                        window.request_redraw();
+                    }
+                }
+            }
         }
     }
 }
@@ -490,13 +871,28 @@ impl ApplicationHandler for App {
         pollster::block_on(self.set_window(window));
     }
 
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        // Draw the capped frame once its deadline has passed
+        if self
+            .next_frame
+            .is_some_and(|next_frame| Instant::now() >= next_frame)
+        {
+            if let Some(window) = self.window.as_ref() {
+                window.request_redraw();
+            }
+        }
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
         let state = self.state.as_mut().unwrap();
 
+        // Set when a menu used the event, so the game below must ignore it
+        let mut consumed = false;
+
         // Handle pause menu input first if in Pause screen and menu is visible
         if state.game_state.current_screen == CurrentScreen::Pause && state.pause_menu.is_visible()
         {
-            state.pause_menu.handle_input(&event);
+            consumed |= state.pause_menu.handle_input(&event);
             // Check for pause menu actions
             match state.pause_menu.get_last_action() {
                 PauseMenuAction::Resume => {
@@ -523,65 +919,112 @@ impl ApplicationHandler for App {
         if state.game_state.current_screen == CurrentScreen::Upgrade
             && state.upgrade_menu.is_visible()
         {
-            state.upgrade_menu.handle_input(&event);
+            consumed |= state.upgrade_menu.handle_input(&event);
             // Check for upgrade menu actions
             match state.upgrade_menu.get_last_action() {
-                UpgradeMenuAction::SelectUpgrade1 => {
-                    // TODO: Implement upgrade 1 selection
-                    println!("Upgrade 1 selected!");
-                }
-                UpgradeMenuAction::SelectUpgrade2 => {
-                    // TODO: Implement upgrade 2 selection
-                    println!("Upgrade 2 selected!");
-                }
-                UpgradeMenuAction::SelectUpgrade3 => {
-                    // TODO: Implement upgrade 3 selection
-                    println!("Upgrade 3 selected!");
+                UpgradeMenuAction::SelectUpgrade(index) => {
+                    // TODO: Apply the selected upgrade
+                    if let Some(upgrade) = state.upgrade_menu.upgrade(index) {
+                        println!("{} selected!", upgrade.name);
+                    }
                 }
                 UpgradeMenuAction::None => {}
             }
         }
 
+        if state.game_state.current_screen == CurrentScreen::NewGame
+            && state.new_game_menu.is_visible()
+        {
+            consumed |= state.new_game_menu.handle_input(&event);
+            match state.new_game_menu.get_last_action() {
+                NewGameAction::Start => state.start_new_game(),
+                NewGameAction::SelectDifficulty(_) | NewGameAction::None => {}
+            }
+        }
+
+        // Clicking anywhere on the game over screen starts a new run
+        if state.game_state.current_screen == CurrentScreen::GameOver && !consumed {
+            if let WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } = &event
+            {
+                if state.game_over_click.accepts(*button) {
+                    state.restart_after_game_over();
+                }
+            }
+        }
+
         // Handle keyboard events for menu navigation
         if let WindowEvent::KeyboardInput { event, .. } = &event {
-            if event.state == ElementState::Pressed {
-                if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape) =
-                    event.physical_key
-                {
-                    if state.game_state.current_screen == CurrentScreen::Pause {
-                        state.game_state.current_screen = CurrentScreen::Game;
-                        state.game_state.game_ui.resume_timer();
-                    } else {
-                        state.game_state.current_screen = CurrentScreen::Pause;
-                        state.game_state.game_ui.pause_timer();
-                    }
-                    if let Some(window) = self.window.as_ref() {
-                        window.request_redraw();
-                    }
+            // Only act on the key-down edge. winit repeats held keys, and not every
+            // platform flags each repeat, so keys already down are ignored too.
+            let key_down = match event.state {
+                ElementState::Pressed => !event.repeat && self.held_keys.insert(event.physical_key),
+                ElementState::Released => {
+                    self.held_keys.remove(&event.physical_key);
+                    false
                 }
-
-                // Add key to switch to upgrade menu (U key)
-                if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::KeyU) =
-                    event.physical_key
-                {
-                    state.game_state.current_screen = CurrentScreen::Upgrade;
-                    if let Some(window) = self.window.as_ref() {
-                        window.request_redraw();
+            };
+            // On the new game screen keys are typed into the seed box instead
+            let typing = state.game_state.current_screen == CurrentScreen::NewGame;
+            if key_down && !typing && !consumed {
+                match state.game_state.keybindings.action_for(event.physical_key) {
+                    Some(KeyAction::TogglePause) => {
+                        if state.game_state.current_screen == CurrentScreen::Pause {
+                            state.game_state.current_screen = CurrentScreen::Game;
+                            state.game_state.game_ui.resume_timer();
+                        } else {
+                            state.game_state.current_screen = CurrentScreen::Pause;
+                            state.game_state.game_ui.pause_timer();
+                        }
+                        if let Some(window) = self.window.as_ref() {
+                            window.request_redraw();
+                        }
+                    }
+                    Some(KeyAction::OpenUpgradeMenu) => {
+                        state.game_state.current_screen = CurrentScreen::Upgrade;
+                        if let Some(window) = self.window.as_ref() {
+                            window.request_redraw();
+                        }
                     }
+                    Some(KeyAction::ToggleVsync) => match state.toggle_vsync() {
+                        Ok(present_mode) => println!("Present mode: {:?}", present_mode),
+                        Err(e) => println!("Failed to toggle vsync: {}", e),
+                    },
+                    None => {}
                 }
             }
         }
 
+        // Keep cursor grab/visibility in sync with whichever screen is now active
+        self.update_cursor_capture();
+        self.update_cursor_icon();
+
         match event {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
             WindowEvent::RedrawRequested => {
-                self.handle_redraw();
-                self.window.as_ref().unwrap().request_redraw();
+                self.handle_redraw(event_loop);
             }
             WindowEvent::Resized(new_size) => {
                 self.handle_resized(new_size.width, new_size.height);
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            // Input can change hover/pressed visuals, so draw at least one more frame
+            WindowEvent::CursorMoved { .. }
+            | WindowEvent::CursorLeft { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::KeyboardInput { .. }
+            | WindowEvent::Focused(_) => {
+                // Releases that happen while unfocused are never delivered
+                if let WindowEvent::Focused(false) = event {
+                    self.held_keys.clear();
+                }
+                self.window.as_ref().unwrap().request_redraw();
             }
             _ => (),
         }
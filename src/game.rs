@@ -1,11 +1,18 @@
-use crate::ui::text::{TextPosition, TextRenderer, TextStyle};
+use crate::keybindings::Keybindings;
+use crate::ui::animation::Easing;
+use crate::ui::button::utils::lerp_hsl;
+use crate::ui::text::{
+    game_ui_ids, TextDirection, TextPosition, TextRenderer, TextStyle, TextWrap,
+};
 use glyphon::Color;
+use std::cell::Cell;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
-use winit::window::Window;
+use winit::dpi::PhysicalSize;
 
 // Add the full definition of GameState and CurrentScreen
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CurrentScreen {
     Loading,
     Game,
@@ -41,6 +48,10 @@ pub struct GameState {
     pub current_screen: CurrentScreen,
     /// Whether test mode is enabled.
     pub test_mode: bool,
+    /// Keys bound to menu actions, remappable at runtime.
+    pub keybindings: Keybindings,
+    /// Seed and difficulty chosen on the new game screen for the current run.
+    pub new_game: NewGameConfig,
     // pub enemy: Enemy,
     // pub audio_manager: GameAudioManager,
     /// Performance monitoring
@@ -69,8 +80,10 @@ impl GameState {
             capture_mouse: false,
             exit_reached: false,
             game_ui: GameUIManager::new(),
-            current_screen: CurrentScreen::Upgrade,
+            current_screen: CurrentScreen::Loading,
             test_mode: false,
+            keybindings: Keybindings::default(),
+            new_game: NewGameConfig::default(),
             // enemy: Enemy::new([-0.5, 30.0, 0.0], 150.0),
             // audio_manager,
             frame_times: Vec::new(),
@@ -135,6 +148,80 @@ impl GameState {
     }
 }
 
+/// How hard a run is, chosen on the new game screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Timer for a run at this difficulty: less time the harder it is
+    pub fn timer_config(self) -> TimerConfig {
+        let duration = match self {
+            Difficulty::Easy => Duration::from_secs(90),
+            Difficulty::Normal => Duration::from_secs(60),
+            Difficulty::Hard => Duration::from_secs(40),
+        };
+        TimerConfig {
+            duration,
+            ..TimerConfig::default()
+        }
+    }
+}
+
+/// Settings for a new run, picked on `CurrentScreen::NewGame`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewGameConfig {
+    /// Seed as typed by the player; empty for a random one
+    pub seed: String,
+    pub difficulty: Difficulty,
+}
+
+/// How the remaining time is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TimerFormat {
+    /// "05.25": seconds with hundredths
+    #[default]
+    SecondsCentis,
+    /// "01:30": minutes and whole seconds
+    MinutesSeconds,
+    /// "1:02:30": hours, minutes and whole seconds
+    HoursMinutesSeconds,
+}
+
+impl TimerFormat {
+    /// Format a remaining duration. Whole-second formats round up, so the
+    /// display only reaches zero when the time has actually run out.
+    pub fn format(self, remaining: Duration) -> String {
+        let total_seconds = remaining.as_secs_f64().ceil() as u64;
+        match self {
+            TimerFormat::SecondsCentis => format!("{:05.2}", remaining.as_secs_f64()),
+            TimerFormat::MinutesSeconds => {
+                format!("{:02}:{:02}", total_seconds / 60, total_seconds % 60)
+            }
+            TimerFormat::HoursMinutesSeconds => format!(
+                "{}:{:02}:{:02}",
+                total_seconds / 3600,
+                (total_seconds / 60) % 60,
+                total_seconds % 60
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimerConfig {
     pub duration: Duration,
@@ -143,6 +230,15 @@ pub struct TimerConfig {
     pub normal_color: Color,
     pub warning_color: Color,
     pub critical_color: Color,
+    pub format: TimerFormat,
+    /// Fade the timer text in and out once it is below `critical_threshold`
+    pub critical_pulse: bool,
+    /// Pulses per second while critical
+    pub pulse_frequency: f32,
+    /// Blend the color from normal through warning to critical while the
+    /// remaining time goes from `warning_threshold` to `critical_threshold`,
+    /// instead of switching at each threshold
+    pub smooth_color: bool,
 }
 
 impl Default for TimerConfig {
@@ -154,10 +250,60 @@ impl Default for TimerConfig {
             normal_color: Color::rgb(100, 255, 100),
             warning_color: Color::rgb(255, 255, 100),
             critical_color: Color::rgb(255, 100, 100),
+            format: TimerFormat::default(),
+            critical_pulse: true,
+            pulse_frequency: 2.0,
+            smooth_color: false,
         }
     }
 }
 
+/// Source of the current time for `GameTimer`
+pub trait Clock: std::fmt::Debug {
+    fn now(&self) -> Instant;
+}
+
+/// Real time, the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when advanced, for deterministic timing.
+/// Clones share the same time, so keep one to advance the timer's copy.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
 #[derive(Debug)]
 pub struct GameTimer {
     pub start_time: Instant,
@@ -166,22 +312,29 @@ pub struct GameTimer {
     pub is_expired: bool,
     pub paused_at: Option<Instant>,
     pub elapsed_paused: Duration,
+    clock: Box<dyn Clock>,
 }
 
 impl GameTimer {
     pub fn new(config: TimerConfig) -> Self {
+        Self::with_clock(config, SystemClock)
+    }
+
+    /// A timer reading time from `clock` instead of the system clock
+    pub fn with_clock(config: TimerConfig, clock: impl Clock + 'static) -> Self {
         Self {
-            start_time: Instant::now(),
+            start_time: clock.now(),
             config,
             is_running: false,
             is_expired: false,
             paused_at: None,
             elapsed_paused: Duration::ZERO,
+            clock: Box::new(clock),
         }
     }
 
     pub fn start(&mut self) {
-        self.start_time = Instant::now();
+        self.start_time = self.clock.now();
         self.is_running = true;
         self.is_expired = false;
         self.paused_at = None;
@@ -190,13 +343,15 @@ impl GameTimer {
 
     pub fn pause(&mut self) {
         if self.is_running && self.paused_at.is_none() {
-            self.paused_at = Some(Instant::now());
+            self.paused_at = Some(self.clock.now());
         }
     }
 
     pub fn resume(&mut self) {
         if let Some(paused_at) = self.paused_at.take() {
-            self.elapsed_paused += paused_at.elapsed();
+            self.elapsed_paused = self
+                .elapsed_paused
+                .saturating_add(self.clock.now().saturating_duration_since(paused_at));
         }
     }
 
@@ -205,7 +360,7 @@ impl GameTimer {
     }
 
     pub fn reset(&mut self) {
-        self.start_time = Instant::now();
+        self.start_time = self.clock.now();
         self.is_expired = false;
         self.paused_at = None;
         self.elapsed_paused = Duration::ZERO;
@@ -215,15 +370,22 @@ impl GameTimer {
         if !self.is_running || self.is_expired {
             return Duration::ZERO;
         }
-        let elapsed = if let Some(paused_at) = self.paused_at {
-            paused_at.duration_since(self.start_time) - self.elapsed_paused
-        } else {
-            Instant::now().duration_since(self.start_time) - self.elapsed_paused
-        };
-        self.config
-            .duration
-            .checked_sub(elapsed)
-            .unwrap_or(Duration::ZERO)
+        // Frozen at the pause instant while paused. Saturate so pausing right
+        // after start (or a clock that jumps) can't underflow.
+        let now = self.paused_at.unwrap_or_else(|| self.clock.now());
+        let elapsed = now
+            .saturating_duration_since(self.start_time)
+            .saturating_sub(self.elapsed_paused);
+        self.config.duration.saturating_sub(elapsed)
+    }
+
+    /// Remaining time as a fraction of the full duration, 1.0 when just started
+    pub fn remaining_fraction(&self) -> f32 {
+        if self.config.duration.is_zero() {
+            return 0.0;
+        }
+        (self.get_remaining_time().as_secs_f32() / self.config.duration.as_secs_f32())
+            .clamp(0.0, 1.0)
     }
 
     pub fn is_expired(&self) -> bool {
@@ -242,6 +404,25 @@ impl GameTimer {
 
     pub fn get_current_color(&self) -> Color {
         let remaining = self.get_remaining_time();
+        let (warning, critical) = (
+            self.config.warning_threshold,
+            self.config.critical_threshold,
+        );
+        if self.config.smooth_color && remaining > critical && remaining < warning {
+            // 0.0 at the warning threshold, 1.0 at the critical one; the
+            // warning color is reached halfway
+            let t = (warning - remaining).as_secs_f32() / (warning - critical).as_secs_f32();
+            return if t < 0.5 {
+                lerp_hsl(self.config.normal_color, self.config.warning_color, t * 2.0)
+            } else {
+                lerp_hsl(
+                    self.config.warning_color,
+                    self.config.critical_color,
+                    t * 2.0 - 1.0,
+                )
+            };
+        }
+
         if remaining <= self.config.critical_threshold {
             self.config.critical_color
         } else if remaining <= self.config.warning_threshold {
@@ -252,9 +433,19 @@ impl GameTimer {
     }
 
     pub fn format_time(&self) -> String {
+        self.config.format.format(self.get_remaining_time())
+    }
+
+    /// Opacity for the timer text: 1.0 normally, pulsing between 0.35 and 1.0
+    /// while critical. The phase follows the remaining time, so the pulse runs
+    /// at the same speed at any frame rate and holds still while paused.
+    pub fn pulse_opacity(&self) -> f32 {
         let remaining = self.get_remaining_time();
-        let seconds = remaining.as_secs_f64();
-        format!("{:05.2}", seconds)
+        if !self.config.critical_pulse || remaining > self.config.critical_threshold {
+            return 1.0;
+        }
+        let phase = remaining.as_secs_f32() * self.config.pulse_frequency * std::f32::consts::TAU;
+        0.675 + 0.325 * phase.cos()
     }
 }
 
@@ -262,6 +453,19 @@ pub struct GameUIManager {
     pub timer: Option<GameTimer>,
     pub level: i32,
     pub score: u32,
+    /// Show "PAUSED" under the timer while it is paused
+    pub show_pause_indicator: bool,
+    /// Seconds the displayed score takes to roll up to a new value; 0 snaps
+    pub score_tween_duration: f32,
+    /// Score currently shown, moving toward `score`
+    displayed_score: f32,
+    /// Displayed score when the current tween started
+    score_tween_from: f32,
+    score_tween_elapsed: f32,
+    /// Level and displayed score last written to the HUD text, so unchanged
+    /// values aren't reshaped every frame
+    shown_level: Option<i32>,
+    shown_score: Option<u32>,
 }
 
 impl Default for GameUIManager {
@@ -276,6 +480,13 @@ impl GameUIManager {
             timer: None,
             level: 1,
             score: 0,
+            show_pause_indicator: true,
+            score_tween_duration: 0.5,
+            displayed_score: 0.0,
+            score_tween_from: 0.0,
+            score_tween_elapsed: 0.0,
+            shown_level: None,
+            shown_score: None,
         }
     }
 
@@ -311,18 +522,43 @@ impl GameUIManager {
         self.timer.as_ref().map(|t| t.is_expired()).unwrap_or(false)
     }
 
+    /// Whether the timer is paused, as opposed to stopped or running
+    pub fn is_timer_paused(&self) -> bool {
+        self.timer
+            .as_ref()
+            .map(|t| t.is_running && t.paused_at.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Whether the timer display changes over time (running, not paused, not expired)
+    pub fn is_timer_ticking(&self) -> bool {
+        self.timer
+            .as_ref()
+            .map(|t| t.is_running && t.paused_at.is_none() && !t.is_expired)
+            .unwrap_or(false)
+    }
+
     pub fn get_timer_text(&self) -> String {
         self.timer
             .as_ref()
             .map_or("00.00".to_string(), |t| t.format_time())
     }
 
+    /// Remaining fraction of the timer for countdown indicators, 0.0 without a timer
+    pub fn get_timer_fraction(&self) -> f32 {
+        self.timer.as_ref().map_or(0.0, |t| t.remaining_fraction())
+    }
+
     pub fn get_timer_color(&self) -> Color {
         self.timer
             .as_ref()
             .map_or(Color::rgb(255, 255, 255), |t| t.get_current_color())
     }
 
+    pub fn get_timer_opacity(&self) -> f32 {
+        self.timer.as_ref().map_or(1.0, |t| t.pulse_opacity())
+    }
+
     pub fn set_level(&mut self, level: i32) {
         self.level = level;
     }
@@ -335,16 +571,64 @@ impl GameUIManager {
         format!("Level: {}", self.level)
     }
 
+    /// Set the score; the displayed score rolls toward it over `score_tween_duration`.
+    /// Setting the current score again changes nothing.
     pub fn set_score(&mut self, score: u32) {
+        if score == self.score {
+            return;
+        }
         self.score = score;
+        self.score_tween_from = self.displayed_score;
+        self.score_tween_elapsed = 0.0;
     }
 
     pub fn get_score(&self) -> u32 {
         self.score
     }
 
+    /// The animated score as currently shown
+    pub fn get_displayed_score(&self) -> u32 {
+        self.displayed_score.round() as u32
+    }
+
+    /// Level text to write to the HUD, `None` while it already shows this level
+    fn level_text_update(&self) -> Option<String> {
+        (self.shown_level != Some(self.level)).then(|| self.get_level_text())
+    }
+
+    /// Score text to write to the HUD, `None` while it already shows the
+    /// displayed score
+    fn score_text_update(&self) -> Option<String> {
+        (self.shown_score != Some(self.get_displayed_score())).then(|| self.get_score_text())
+    }
+
+    /// Whether the displayed score is still rolling toward the real one
+    pub fn is_score_animating(&self) -> bool {
+        self.displayed_score != self.score as f32
+    }
+
+    /// Advance the score roll-up by `delta_time` seconds
+    pub fn update_score_animation(&mut self, delta_time: f32) {
+        if !self.is_score_animating() {
+            return;
+        }
+        self.score_tween_elapsed += delta_time.max(0.0);
+        let t = if self.score_tween_duration > 0.0 {
+            self.score_tween_elapsed / self.score_tween_duration
+        } else {
+            1.0
+        };
+        let target = self.score as f32;
+        self.displayed_score = if t >= 1.0 {
+            target
+        } else {
+            let eased = Easing::EaseOutCubic.apply(t);
+            self.score_tween_from + (target - self.score_tween_from) * eased
+        };
+    }
+
     pub fn get_score_text(&self) -> String {
-        format!("Score: {}", self.score)
+        format!("Score: {}", self.get_displayed_score())
     }
 
     pub fn pause_timer(&mut self) {
@@ -364,9 +648,8 @@ impl GameUIManager {
 pub fn initialize_game_ui(
     text_renderer: &mut TextRenderer,
     game_ui: &GameUIManager,
-    window: &Window,
+    size: PhysicalSize<u32>,
 ) {
-    let size = window.inner_size();
     let width = size.width;
     let height = size.height;
 
@@ -385,6 +668,12 @@ pub fn initialize_game_ui(
         } else {
             (18.0, 22.0, 120.0, 25.0)
         };
+    // Keep the breakpoint sizes within the configured bounds
+    let scaling = &text_renderer.scaling;
+    let timer_font_size = scaling.timer.clamp_size(timer_font_size);
+    let timer_line_height = scaling.timer.clamp_line_height(timer_line_height);
+    let label_font_size = scaling.hud_label.clamp_size(label_font_size);
+    let label_line_height = scaling.hud_label.clamp_line_height(label_line_height);
 
     // Timer display (centered at top)
     let timer_style = TextStyle {
@@ -394,6 +683,9 @@ pub fn initialize_game_ui(
         color: Color::rgb(100, 255, 100),
         weight: glyphon::Weight::BOLD,
         style: glyphon::Style::Normal,
+        wrap: TextWrap::Word,
+        direction: TextDirection::Auto,
+        letter_spacing: 0.0,
     };
     let timer_position = TextPosition {
         x: (width as f32 / 2.0) - (timer_max_width / 2.75),
@@ -402,10 +694,39 @@ pub fn initialize_game_ui(
         max_height: Some(timer_max_height),
     };
     text_renderer.create_text_buffer(
-        "main_timer",
+        game_ui_ids::MAIN_TIMER,
         &game_ui.get_timer_text(),
         Some(timer_style),
-        Some(timer_position),
+        Some(timer_position.clone()),
+    );
+
+    // Paused indicator (under the timer, shown by update_game_ui while paused)
+    let paused_style = TextStyle {
+        font_family: "HankenGrotesk".to_string(),
+        font_size: label_font_size,
+        line_height: label_line_height,
+        color: Color::rgb(255, 255, 255),
+        weight: glyphon::Weight::BOLD,
+        style: glyphon::Style::Normal,
+        wrap: TextWrap::Word,
+        direction: TextDirection::Auto,
+        letter_spacing: 0.0,
+    };
+    let paused_position = TextPosition {
+        x: timer_position.x,
+        y: timer_position.y + timer_max_height,
+        max_width: Some(label_max_width),
+        max_height: Some(label_max_height),
+    };
+    text_renderer.create_text_buffer(
+        game_ui_ids::PAUSED_INDICATOR,
+        "PAUSED",
+        Some(paused_style),
+        Some(paused_position),
+    );
+    let _ = text_renderer.set_visible(
+        game_ui_ids::PAUSED_INDICATOR,
+        game_ui.show_pause_indicator && game_ui.is_timer_paused(),
     );
 
     // Level display (top left, above score)
@@ -416,6 +737,9 @@ pub fn initialize_game_ui(
         color: Color::rgb(255, 255, 150),
         weight: glyphon::Weight::NORMAL,
         style: glyphon::Style::Normal,
+        wrap: TextWrap::Word,
+        direction: TextDirection::Auto,
+        letter_spacing: 0.0,
     };
     let level_position = TextPosition {
         x: 20.0,
@@ -424,7 +748,7 @@ pub fn initialize_game_ui(
         max_height: Some(label_max_height),
     };
     text_renderer.create_text_buffer(
-        "level",
+        game_ui_ids::LEVEL,
         &game_ui.get_level_text(),
         Some(level_style),
         Some(level_position),
@@ -438,6 +762,9 @@ pub fn initialize_game_ui(
         color: Color::rgb(150, 255, 255),
         weight: glyphon::Weight::NORMAL,
         style: glyphon::Style::Normal,
+        wrap: TextWrap::Word,
+        direction: TextDirection::Auto,
+        letter_spacing: 0.0,
     };
     let score_position = TextPosition {
         x: 20.0,
@@ -446,7 +773,7 @@ pub fn initialize_game_ui(
         max_height: Some(label_max_height),
     };
     text_renderer.create_text_buffer(
-        "score",
+        game_ui_ids::SCORE,
         &game_ui.get_score_text(),
         Some(score_style),
         Some(score_position),
@@ -474,23 +801,203 @@ pub fn update_game_ui(
     text_renderer: &mut TextRenderer,
     game_ui: &mut GameUIManager,
     _current_screen: &CurrentScreen,
+    delta_time: f32,
 ) -> bool {
     // Only update the timer, do not pause/resume here
     let timer_expired = game_ui.update_timer();
 
     // Update timer display
     let timer_text = game_ui.get_timer_text();
-    let _ = update_text_content(text_renderer, "main_timer", &timer_text);
+    let _ = update_text_content(text_renderer, game_ui_ids::MAIN_TIMER, &timer_text);
     // Update timer color by updating style
-    if let Some(buffer) = text_renderer.text_buffers.get_mut("main_timer") {
+    if let Some(buffer) = text_renderer.text_buffers.get_mut(game_ui_ids::MAIN_TIMER) {
         let mut style = buffer.style.clone();
         style.color = game_ui.get_timer_color();
-        let _ = text_renderer.update_style("main_timer", style);
+        let _ = text_renderer.update_style(game_ui_ids::MAIN_TIMER, style);
     }
+    let _ = text_renderer.set_opacity(game_ui_ids::MAIN_TIMER, game_ui.get_timer_opacity());
 
-    // Update level and score displays
-    let _ = update_text_content(text_renderer, "level", &game_ui.get_level_text());
-    let _ = update_text_content(text_renderer, "score", &game_ui.get_score_text());
+    // Tell a paused timer apart from a stopped one
+    let _ = text_renderer.set_visible(
+        game_ui_ids::PAUSED_INDICATOR,
+        game_ui.show_pause_indicator && game_ui.is_timer_paused(),
+    );
+
+    // Level and score only change now and then, so only reshape them when they do
+    game_ui.update_score_animation(delta_time);
+    if let Some(text) = game_ui.level_text_update() {
+        if update_text_content(text_renderer, game_ui_ids::LEVEL, &text).is_ok() {
+            game_ui.shown_level = Some(game_ui.level);
+        }
+    }
+    if let Some(text) = game_ui.score_text_update() {
+        if update_text_content(text_renderer, game_ui_ids::SCORE, &text).is_ok() {
+            game_ui.shown_score = Some(game_ui.get_displayed_score());
+        }
+    }
 
     timer_expired
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A started 60s timer and the clock driving it
+    fn timer() -> (GameTimer, ManualClock) {
+        let clock = ManualClock::new();
+        let mut timer = GameTimer::with_clock(TimerConfig::default(), clock.clone());
+        timer.start();
+        (timer, clock)
+    }
+
+    #[test]
+    fn timer_counts_down_with_the_clock() {
+        let (timer, clock) = timer();
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(40));
+    }
+
+    #[test]
+    fn paused_time_is_not_counted() {
+        let (mut timer, clock) = timer();
+        clock.advance(Duration::from_secs(10));
+        timer.pause();
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(50));
+        assert!(!timer.update());
+
+        timer.resume();
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(50));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(45));
+    }
+
+    #[test]
+    fn update_reports_expiry_once() {
+        let (mut timer, clock) = timer();
+        clock.advance(Duration::from_secs(59));
+        assert!(!timer.update());
+        assert!(!timer.is_expired());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(timer.update());
+        assert!(timer.is_expired());
+        assert!(!timer.update());
+        assert_eq!(timer.get_remaining_time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn color_follows_the_thresholds() {
+        let (timer, clock) = timer();
+        let config = TimerConfig::default();
+        assert_eq!(timer.get_current_color(), config.normal_color);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(timer.get_current_color(), config.warning_color);
+        clock.advance(Duration::from_secs(15));
+        assert_eq!(timer.get_current_color(), config.critical_color);
+    }
+
+    #[test]
+    fn timer_formats_at_several_durations() {
+        let secs = Duration::from_secs_f64;
+        let cases = [
+            (secs(90.0), "90.00", "01:30", "0:01:30"),
+            (secs(5.25), "05.25", "00:06", "0:00:06"),
+            (secs(0.0), "00.00", "00:00", "0:00:00"),
+            (secs(3750.0), "3750.00", "62:30", "1:02:30"),
+        ];
+        for (remaining, centis, minutes, hours) in cases {
+            assert_eq!(TimerFormat::SecondsCentis.format(remaining), centis);
+            assert_eq!(TimerFormat::MinutesSeconds.format(remaining), minutes);
+            assert_eq!(TimerFormat::HoursMinutesSeconds.format(remaining), hours);
+        }
+    }
+
+    #[test]
+    fn whole_second_formats_round_up() {
+        let just_started = Duration::from_millis(59_001);
+        assert_eq!(TimerFormat::MinutesSeconds.format(just_started), "01:00");
+        assert_eq!(
+            TimerFormat::MinutesSeconds.format(Duration::from_millis(1)),
+            "00:01"
+        );
+    }
+
+    #[test]
+    fn format_time_uses_the_configured_format() {
+        let clock = ManualClock::new();
+        let config = TimerConfig {
+            duration: Duration::from_secs(90),
+            format: TimerFormat::MinutesSeconds,
+            ..TimerConfig::default()
+        };
+        let mut timer = GameTimer::with_clock(config, clock);
+        timer.start();
+        assert_eq!(timer.format_time(), "01:30");
+    }
+
+    #[test]
+    fn pausing_right_after_start_does_not_underflow() {
+        let (mut timer, clock) = timer();
+        timer.pause();
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+        timer.resume();
+        timer.pause();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn paused_time_longer_than_elapsed_saturates() {
+        let (mut timer, clock) = timer();
+        timer.elapsed_paused = Duration::from_secs(100);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+        timer.pause();
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn start_time_after_now_saturates() {
+        let (mut timer, clock) = timer();
+        timer.start_time = clock.now() + Duration::from_secs(10);
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+        timer.pause();
+        timer.resume();
+        assert_eq!(timer.get_remaining_time(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn setting_the_same_score_needs_no_text_update() {
+        let mut game_ui = GameUIManager::new();
+        game_ui.score_tween_duration = 0.0;
+        assert!(game_ui.score_text_update().is_some());
+        game_ui.shown_score = Some(game_ui.get_displayed_score());
+
+        game_ui.set_score(0);
+        game_ui.update_score_animation(0.1);
+        assert!(game_ui.score_text_update().is_none());
+
+        game_ui.set_score(50);
+        game_ui.update_score_animation(0.1);
+        assert!(game_ui.score_text_update().unwrap().contains("50"));
+        game_ui.shown_score = Some(game_ui.get_displayed_score());
+
+        game_ui.set_score(50);
+        game_ui.update_score_animation(0.1);
+        assert!(game_ui.score_text_update().is_none());
+    }
+
+    #[test]
+    fn unchanged_level_needs_no_text_update() {
+        let mut game_ui = GameUIManager::new();
+        assert!(game_ui.level_text_update().is_some());
+        game_ui.shown_level = Some(game_ui.level);
+        assert!(game_ui.level_text_update().is_none());
+
+        game_ui.level += 1;
+        assert!(game_ui.level_text_update().is_some());
+    }
+}
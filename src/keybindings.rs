@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+/// Actions that can be triggered from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    /// Open the pause menu, or resume when it is open
+    TogglePause,
+    /// Switch to the upgrade menu
+    OpenUpgradeMenu,
+    /// Turn vsync off or back on, for profiling frame times
+    ToggleVsync,
+}
+
+/// Maps each action to the physical key that triggers it
+#[derive(Debug, Clone)]
+pub struct Keybindings {
+    bindings: HashMap<KeyAction, KeyCode>,
+}
+
+impl Default for Keybindings {
+    /// Escape pauses, U opens the upgrade menu, F2 toggles vsync
+    fn default() -> Self {
+        let mut bindings = HashMap::new();
+        bindings.insert(KeyAction::TogglePause, KeyCode::Escape);
+        bindings.insert(KeyAction::OpenUpgradeMenu, KeyCode::KeyU);
+        bindings.insert(KeyAction::ToggleVsync, KeyCode::F2);
+        Self { bindings }
+    }
+}
+
+impl Keybindings {
+    /// Bind `action` to `key`. Any other action bound to `key` is unbound so a
+    /// key never triggers two actions. Returns the action that lost the key.
+    pub fn rebind(&mut self, action: KeyAction, key: KeyCode) -> Option<KeyAction> {
+        let displaced = self
+            .bindings
+            .iter()
+            .find(|(other, bound)| **other != action && **bound == key)
+            .map(|(other, _)| *other);
+        if let Some(other) = displaced {
+            self.bindings.remove(&other);
+        }
+        self.bindings.insert(action, key);
+        displaced
+    }
+
+    /// Remove the binding for `action`
+    pub fn unbind(&mut self, action: KeyAction) {
+        self.bindings.remove(&action);
+    }
+
+    /// The key currently bound to `action`
+    pub fn key_for(&self, action: KeyAction) -> Option<KeyCode> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// The action bound to a physical key, if any
+    pub fn action_for(&self, key: PhysicalKey) -> Option<KeyAction> {
+        let PhysicalKey::Code(code) = key else {
+            return None;
+        };
+        self.bindings
+            .iter()
+            .find(|(_, bound)| **bound == code)
+            .map(|(action, _)| *action)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::ui::button::utils::dpi_scale;
+use crate::ui::prelude::*;
+use crate::ui::progress_bar::ProgressBar;
+use crate::ui::text::SharedTextRenderer;
+use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
+use glyphon::{Color, Resolution};
+use winit::dpi::PhysicalSize;
+
+const LOADING_TEXT_ID: &str = "loading_text";
+const PROGRESS_BAR_ID: &str = "loading";
+
+/// Shown on `CurrentScreen::Loading` while startup assets load: a centered
+/// "Loading…" text above a progress bar
+pub struct LoadingScreen {
+    pub button_manager: ButtonManager,
+    visible: bool,
+    progress: f32,
+}
+
+impl LoadingScreen {
+    /// Build without a window, e.g. for offscreen rendering
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+        ))
+    }
+
+    /// Like `new_with_config`, drawing text through `text_renderer` under
+    /// the "loading" namespace
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::with_text_renderer(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+            text_renderer,
+            "loading",
+        ))
+    }
+
+    fn with_button_manager(button_manager: ButtonManager) -> Self {
+        let mut loading_screen = Self {
+            button_manager,
+            visible: false,
+            progress: 0.0,
+        };
+        loading_screen.create_layout();
+        loading_screen.set_visible(false);
+        loading_screen
+    }
+
+    /// Center the text and bar in the window, keeping the current progress
+    fn create_layout(&mut self) {
+        let window_width = self.button_manager.window_size.width as f32;
+        let window_height = self.button_manager.window_size.height as f32;
+        let scale = dpi_scale(window_height);
+
+        let text_style = TextStyle {
+            font_family: "HankenGrotesk".to_string(),
+            font_size: 48.0 * scale,
+            line_height: 58.0 * scale,
+            color: Color::rgb(255, 255, 255),
+            weight: glyphon::Weight::MEDIUM,
+            ..TextStyle::default()
+        };
+        let (_min_x, text_width, text_height) = self
+            .button_manager
+            .text_renderer
+            .borrow()
+            .measure_text("Loading…", &text_style);
+
+        let bar_width = (window_width * 0.4).max(200.0);
+        let bar_height = 16.0 * scale;
+        let bar_y = window_height / 2.0 + 12.0 * scale;
+
+        let text_id = self.button_manager.text_id(LOADING_TEXT_ID);
+        self.button_manager
+            .text_renderer
+            .borrow_mut()
+            .create_text_buffer(
+                &text_id,
+                "Loading…",
+                Some(text_style),
+                Some(TextPosition {
+                    x: (window_width - text_width) / 2.0,
+                    y: bar_y - text_height - 24.0 * scale,
+                    max_width: Some(text_width + 1.0),
+                    max_height: Some(text_height),
+                }),
+            );
+        let bar_position = ButtonPosition::new(
+            (window_width - bar_width) / 2.0,
+            bar_y,
+            bar_width,
+            bar_height,
+        )
+        .with_anchor(ButtonAnchor::TopLeft);
+        // On resize, move the existing bar so its label moves with it
+        if self
+            .button_manager
+            .set_progress_bar_position(PROGRESS_BAR_ID, bar_position.clone())
+            .is_ok()
+        {
+            if let Some(bar) = self.button_manager.get_progress_bar_mut(PROGRESS_BAR_ID) {
+                bar.corner_radius = bar_height / 2.0;
+            }
+        } else {
+            self.button_manager.add_progress_bar(
+                ProgressBar::new(PROGRESS_BAR_ID, bar_position)
+                    .with_corner_radius(bar_height / 2.0)
+                    .with_value(self.progress),
+            );
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        let text_id = self.button_manager.text_id(LOADING_TEXT_ID);
+        let result = self
+            .button_manager
+            .text_renderer
+            .borrow_mut()
+            .set_visible(&text_id, visible);
+        if let Err(e) = result.and_then(|_| {
+            self.button_manager
+                .set_progress_bar_visible(PROGRESS_BAR_ID, visible)
+        }) {
+            println!("Failed to update loading screen: {}", e);
+        }
+    }
+
+    pub fn show(&mut self) {
+        if !self.visible {
+            self.visible = true;
+            self.set_visible(true);
+        }
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            self.visible = false;
+            self.set_visible(false);
+            self.button_manager.rectangle_renderer.clear_rectangles();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Fill the bar to `progress`, clamped to 0.0..=1.0
+    pub fn set_progress(&mut self, progress: f32) {
+        if let Err(e) = self.button_manager.set_progress(PROGRESS_BAR_ID, progress) {
+            println!("Failed to update loading screen: {}", e);
+        }
+        if let Some(bar) = self.button_manager.get_progress_bar_mut(PROGRESS_BAR_ID) {
+            self.progress = bar.value;
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    pub fn resize(&mut self, queue: &Queue, resolution: Resolution) {
+        self.button_manager.resize(queue, resolution);
+        self.button_manager.window_size = PhysicalSize {
+            width: resolution.width,
+            height: resolution.height,
+        };
+        self.create_layout();
+        self.set_visible(self.visible);
+    }
+
+    /// Draw the screen into a render pass the host owns; see
+    /// `ButtonManager::render_to`. Draws nothing while hidden.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        self.button_manager
+            .render_to(device, queue, surface_config, render_pass, None)
+    }
+}
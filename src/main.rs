@@ -1,4 +1,7 @@
 mod app;
+mod keybindings;
+mod loading_screen;
+mod new_game_menu;
 mod pause_menu;
 mod ui;
 mod upgrade_menu;
@@ -15,7 +18,8 @@ fn main() {
 async fn run() {
     let event_loop = EventLoop::new().unwrap();
 
-    event_loop.set_control_flow(ControlFlow::Poll);
+    // Wait for events; the app requests redraws itself while anything animates
+    event_loop.set_control_flow(ControlFlow::Wait);
 
     let mut app = app::App::new();
 
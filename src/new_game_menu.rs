@@ -0,0 +1,425 @@
+use crate::game::{Difficulty, NewGameConfig};
+use crate::ui::button::{create_primary_button_style, utils::dpi_scale, DEFAULT_OVERLAY_COLOR};
+use crate::ui::prelude::*;
+use crate::ui::text::SharedTextRenderer;
+use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
+use glyphon::{Color, Resolution};
+use std::cell::Cell;
+use std::rc::Rc;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::window::CursorIcon;
+
+const TITLE_TEXT_ID: &str = "title";
+const SEED_LABEL_ID: &str = "seed_label";
+const DIFFICULTY_LABEL_ID: &str = "difficulty_label";
+const SEED_INPUT_ID: &str = "seed";
+const START_BUTTON_ID: &str = "start";
+/// Longest seed the box accepts, in characters
+const MAX_SEED_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NewGameAction {
+    SelectDifficulty(Difficulty),
+    /// Start a run with `NewGameMenu::config`
+    Start,
+    None,
+}
+
+/// Shown on `CurrentScreen::NewGame`: a seed box, one toggle per difficulty
+/// and a Start button. Enter in the seed box starts too.
+pub struct NewGameMenu {
+    pub button_manager: ButtonManager<NewGameAction>,
+    pub visible: bool,
+    pub last_action: NewGameAction,
+    /// Color of the full-window rectangle drawn behind the menu
+    pub overlay_color: [f32; 4],
+    /// Clear color while the menu is open; `None` keeps the game background
+    pub background_color: Option<[f32; 4]>,
+    difficulty: Difficulty,
+    /// Set by the seed box when Enter is pressed in it
+    seed_submitted: Rc<Cell<bool>>,
+}
+
+impl NewGameMenu {
+    /// Build without a window, e.g. for offscreen rendering
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+        ))
+    }
+
+    /// Like `new_with_config`, drawing text through `text_renderer` under
+    /// the "new_game" namespace
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::with_text_renderer(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+            text_renderer,
+            "new_game",
+        ))
+    }
+
+    fn with_button_manager(button_manager: ButtonManager<NewGameAction>) -> Self {
+        let mut new_game_menu = Self {
+            button_manager,
+            visible: false,
+            last_action: NewGameAction::None,
+            overlay_color: DEFAULT_OVERLAY_COLOR,
+            background_color: None,
+            difficulty: Difficulty::default(),
+            seed_submitted: Rc::new(Cell::new(false)),
+        };
+        new_game_menu.create_layout();
+        new_game_menu.set_visible(false);
+        new_game_menu
+    }
+
+    fn text_style(font_size: f32, line_height: f32) -> TextStyle {
+        TextStyle {
+            font_family: "HankenGrotesk".to_string(),
+            font_size,
+            line_height,
+            color: Color::rgb(255, 255, 255),
+            weight: glyphon::Weight::MEDIUM,
+            ..TextStyle::default()
+        }
+    }
+
+    /// Slate toggles, green while selected
+    fn difficulty_style(text_style: &TextStyle) -> ButtonStyle {
+        ButtonStyle::builder()
+            .text_style(text_style.clone())
+            .padding(16.0, 10.0)
+            .colors(
+                Color::rgb(51, 65, 85),  // slate-700
+                Color::rgb(71, 85, 105), // slate-600
+                Color::rgb(30, 41, 59),  // slate-800
+            )
+            .selected_color(Color::rgb(30, 110, 30)) // primary green
+            .build()
+    }
+
+    /// Add a label text buffer at `(x, y)`, replacing any with the same id
+    fn add_label(&mut self, id: &str, text: &str, style: TextStyle, x: f32, y: f32) -> f32 {
+        let text_id = self.button_manager.text_id(id);
+        let mut text_renderer = self.button_manager.text_renderer.borrow_mut();
+        let (_min_x, width, height) = text_renderer.measure_text(text, &style);
+        text_renderer.create_text_buffer(
+            &text_id,
+            text,
+            Some(style),
+            Some(TextPosition {
+                x,
+                y,
+                max_width: Some(width + 1.0),
+                max_height: Some(height),
+            }),
+        );
+        height
+    }
+
+    /// Lay out a centered column for the window size, adding the widgets the
+    /// first time and moving them afterwards
+    fn create_layout(&mut self) {
+        let window_width = self.button_manager.window_size.width as f32;
+        let window_height = self.button_manager.window_size.height as f32;
+        let scale = dpi_scale(window_height);
+
+        let toggle_width = 160.0 * scale;
+        let row_height = 56.0 * scale;
+        let gap = 12.0 * scale;
+        let column_width = toggle_width * 3.0 + gap * 2.0;
+        let column_x = (window_width - column_width) / 2.0;
+
+        let title_style = Self::text_style(56.0 * scale, 68.0 * scale);
+        let (_min_x, title_width, _height) = self
+            .button_manager
+            .text_renderer
+            .borrow()
+            .measure_text("New Game", &title_style);
+        let mut y = window_height * 0.18;
+        y += self.add_label(
+            TITLE_TEXT_ID,
+            "New Game",
+            title_style,
+            (window_width - title_width) / 2.0,
+            y,
+        );
+
+        // Seed box under its label
+        let label_style = Self::text_style(24.0 * scale, 30.0 * scale);
+        y += 32.0 * scale;
+        y += self.add_label(SEED_LABEL_ID, "Seed", label_style.clone(), column_x, y);
+        y += 8.0 * scale;
+        let seed_position = ButtonPosition::new(column_x, y, column_width, row_height)
+            .with_anchor(ButtonAnchor::TopLeft);
+        let input_style = Self::text_style(28.0 * scale, 34.0 * scale);
+        // Added again so the text takes the new size; what was typed is kept
+        let seed = self
+            .button_manager
+            .text_input_value(SEED_INPUT_ID)
+            .unwrap_or_default()
+            .to_string();
+        let seed_submitted = Rc::clone(&self.seed_submitted);
+        self.button_manager.add_text_input(
+            TextInput::new(SEED_INPUT_ID, seed_position)
+                .with_value(&seed)
+                .with_style(input_style)
+                .with_max_length(MAX_SEED_LENGTH)
+                .with_on_submit(move |_| seed_submitted.set(true)),
+        );
+        y += row_height;
+
+        // Difficulty toggles in a row under their label
+        y += 24.0 * scale;
+        y += self.add_label(DIFFICULTY_LABEL_ID, "Difficulty", label_style, column_x, y);
+        y += 8.0 * scale;
+        let toggle_text_style = Self::text_style(28.0 * scale, 34.0 * scale);
+        for (i, difficulty) in Difficulty::ALL.into_iter().enumerate() {
+            let position =
+                ButtonPosition::new(column_x + i as f32 * (toggle_width + gap), y, 0.0, 0.0)
+                    .with_anchor(ButtonAnchor::TopLeft);
+            let spacing = ButtonSpacing::Fixed(toggle_width, row_height);
+            let id = Self::difficulty_id(difficulty);
+            if let Some(button) = self.button_manager.get_button_mut(&id) {
+                button.position = position;
+                button.style.spacing = spacing;
+            } else {
+                self.button_manager.add_button(
+                    Button::new(&id, difficulty.label())
+                        .with_id_action(NewGameAction::SelectDifficulty(difficulty))
+                        .with_style(ButtonStyle {
+                            spacing,
+                            ..Self::difficulty_style(&toggle_text_style)
+                        })
+                        .with_text_align(TextAlign::Center)
+                        .with_selected(difficulty == self.difficulty)
+                        .with_tooltip(&format!(
+                            "{} seconds on the clock",
+                            difficulty.timer_config().duration.as_secs()
+                        ))
+                        .with_position(position),
+                );
+            }
+        }
+        y += row_height;
+
+        // Start button centered below
+        let start_width = 240.0 * scale;
+        let start_height = 64.0 * scale;
+        let start_position = ButtonPosition::new(
+            (window_width - start_width) / 2.0,
+            y + 40.0 * scale,
+            0.0,
+            0.0,
+        )
+        .with_anchor(ButtonAnchor::TopLeft);
+        let start_spacing = ButtonSpacing::Fixed(start_width, start_height);
+        if let Some(button) = self.button_manager.get_button_mut(START_BUTTON_ID) {
+            button.position = start_position;
+            button.style.spacing = start_spacing;
+        } else {
+            let mut start_style = create_primary_button_style();
+            start_style.text_style = Self::text_style(32.0 * scale, 40.0 * scale);
+            start_style.spacing = start_spacing;
+            self.button_manager.add_button(
+                Button::new(START_BUTTON_ID, "Start")
+                    .with_id_action(NewGameAction::Start)
+                    .with_style(start_style)
+                    .with_text_align(TextAlign::Center)
+                    .with_position(start_position),
+            );
+        }
+
+        self.button_manager.update_button_positions();
+    }
+
+    fn difficulty_id(difficulty: Difficulty) -> String {
+        format!("difficulty_{}", difficulty.label().to_lowercase())
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.button_manager.set_all_visible(visible);
+        let mut result = self
+            .button_manager
+            .set_text_input_visible(SEED_INPUT_ID, visible);
+        for label_id in [TITLE_TEXT_ID, SEED_LABEL_ID, DIFFICULTY_LABEL_ID] {
+            let text_id = self.button_manager.text_id(label_id);
+            result = result.and_then(|_| {
+                self.button_manager
+                    .text_renderer
+                    .borrow_mut()
+                    .set_visible(&text_id, visible)
+            });
+        }
+        if let Err(e) = result {
+            println!("Failed to update new game menu: {}", e);
+        }
+    }
+
+    pub fn show(&mut self) {
+        // show is called every frame while the menu is up
+        if !self.visible {
+            self.visible = true;
+            self.last_action = NewGameAction::None;
+            self.set_visible(true);
+            self.button_manager.update_button_states();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            self.visible = false;
+            self.last_action = NewGameAction::None;
+            self.set_visible(false);
+            // Nothing of the menu may be drawn by a host that renders it anyway
+            self.button_manager.rectangle_renderer.clear_rectangles();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The seed and difficulty currently chosen
+    pub fn config(&self) -> NewGameConfig {
+        NewGameConfig {
+            seed: self
+                .button_manager
+                .text_input_value(SEED_INPUT_ID)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            difficulty: self.difficulty,
+        }
+    }
+
+    /// Select `difficulty`, deselecting the other toggles
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        for other in Difficulty::ALL {
+            if let Err(e) = self
+                .button_manager
+                .set_selected(&Self::difficulty_id(other), other == difficulty)
+            {
+                println!("Failed to select difficulty: {}", e);
+            }
+        }
+    }
+
+    /// Cursor for the current mouse position while the menu is open
+    pub fn desired_cursor(&self) -> Option<CursorIcon> {
+        if !self.visible {
+            return None;
+        }
+        self.button_manager.desired_cursor()
+    }
+
+    /// Advance held repeat buttons and the caret blink by `delta_time` seconds
+    pub fn update(&mut self, delta_time: f32) {
+        for action in self.button_manager.update(delta_time) {
+            self.apply_action(action);
+        }
+    }
+
+    /// Whether the caret is blinking, so frames must keep coming
+    pub fn is_animating(&self) -> bool {
+        self.button_manager.is_animating()
+    }
+
+    /// Returns whether the event was consumed, e.g. typed into the seed box
+    pub fn handle_input(&mut self, event: &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        for action in self.button_manager.handle_input(event) {
+            self.apply_action(action);
+        }
+        if self.seed_submitted.take() {
+            self.last_action = NewGameAction::Start;
+        }
+        self.button_manager.input_consumed()
+    }
+
+    fn apply_action(&mut self, action: NewGameAction) {
+        if let NewGameAction::SelectDifficulty(difficulty) = action {
+            self.set_difficulty(difficulty);
+        }
+        self.last_action = action;
+    }
+
+    pub fn get_last_action(&mut self) -> NewGameAction {
+        let action = self.last_action.clone();
+        self.last_action = NewGameAction::None;
+        action
+    }
+
+    pub fn resize(&mut self, queue: &Queue, resolution: Resolution) {
+        self.button_manager.resize(queue, resolution);
+        self.button_manager.window_size = PhysicalSize {
+            width: resolution.width,
+            height: resolution.height,
+        };
+        self.create_layout();
+        self.set_visible(self.visible);
+    }
+
+    /// Draw the menu, dimming what's behind it, into a render pass the host
+    /// owns; see `ButtonManager::render_to`. Draws nothing while hidden.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        self.button_manager.render_to(
+            device,
+            queue,
+            surface_config,
+            render_pass,
+            Some(self.overlay_color),
+        )
+    }
+}
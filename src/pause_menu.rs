@@ -1,20 +1,36 @@
+use crate::ui::animation::{EntranceAnimation, EntranceDirection};
 use crate::ui::button::{
     create_danger_button_style,
     create_goldenrod_button_style,
     create_lobby_button_style, // new styles
     create_primary_button_style,
     create_warning_button_style,
+    utils::dpi_scale,
     Button,
     ButtonAnchor,
     ButtonManager,
     ButtonPosition,
+    ButtonSpacing,
+    Padding,
     TextAlign,
+    DEFAULT_OVERLAY_COLOR,
 };
+use crate::ui::confirm::{ConfirmAction, ConfirmDialog};
+use crate::ui::menu::MenuBuilder;
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::scaling::ScalingConfig;
+use crate::ui::text::SharedTextRenderer;
 use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
 use glyphon::Resolution;
+use std::sync::mpsc::Sender;
 use winit::dpi::PhysicalSize;
 use winit::event::WindowEvent;
-use winit::window::Window;
+use winit::window::{CursorIcon, Window};
+
+/// Label of the debug toggle; newlines are hard breaks, see `TextWrap`
+const DEBUG_LABEL: &str = " Show\nDebug\n  Info";
+/// Button state transitions kept while the debug panel is open
+const DEBUG_TRANSITION_LOG_SIZE: usize = 16;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PauseMenuAction {
@@ -27,38 +43,136 @@ pub enum PauseMenuAction {
 }
 
 pub struct PauseMenu {
-    pub button_manager: ButtonManager,
+    pub button_manager: ButtonManager<PauseMenuAction>,
     pub visible: bool,
     pub last_action: PauseMenuAction,
     pub show_debug_panel: bool, // Track debug panel visibility
+    /// Color of the full-window rectangle drawn behind the menu
+    pub overlay_color: [f32; 4],
+    /// Clear color while the menu is open; `None` keeps the game background
+    pub background_color: Option<[f32; 4]>,
+    /// Font size bounds for the button labels; change with `set_scaling`
+    pub scaling: ScalingConfig,
+    /// Played by `show`; `None` makes the buttons appear at once
+    pub entrance_animation: Option<EntranceAnimation>,
+    /// Receives every action as soon as it happens, see `set_action_sender`
+    action_sender: Option<Sender<PauseMenuAction>>,
+    /// Layout of the main buttons, applied again on resize
+    menu: MenuBuilder<PauseMenuAction>,
+    /// Asks before the quit buttons take effect
+    pub confirm_dialog: ConfirmDialog,
+    /// Action waiting for the player's answer in `confirm_dialog`
+    pending_confirm: Option<PauseMenuAction>,
 }
 
 impl PauseMenu {
-    pub fn new(
+    /// Laid out for the window's current inner size
+    pub fn from_window(
         device: &Device,
         queue: &Queue,
         surface_format: wgpu::TextureFormat,
         window: &Window,
     ) -> Self {
-        let mut button_manager = ButtonManager::new(device, queue, surface_format, window);
+        Self::new(device, queue, surface_format, window.inner_size())
+    }
+
+    /// Build without a window, e.g. for offscreen rendering
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        Self::with_button_manager(
+            ButtonManager::new_with_config(device, queue, surface_format, window_size, config),
+            ConfirmDialog::new_with_config(device, queue, surface_format, window_size, config),
+        )
+    }
 
-        // Create pause menu buttons
-        Self::create_menu_buttons(&mut button_manager, window.inner_size());
+    /// Like `new_with_config`, drawing text through `text_renderer` under
+    /// the "pause" namespace
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+    ) -> Self {
+        Self::with_button_manager(
+            ButtonManager::with_text_renderer(
+                device,
+                queue,
+                surface_format,
+                window_size,
+                config,
+                text_renderer,
+                "pause",
+            ),
+            ConfirmDialog::with_text_renderer(
+                device,
+                queue,
+                surface_format,
+                window_size,
+                config,
+                text_renderer,
+                "pause_confirm",
+            ),
+        )
+    }
+
+    fn with_button_manager(
+        mut button_manager: ButtonManager<PauseMenuAction>,
+        confirm_dialog: ConfirmDialog,
+    ) -> Self {
+        let scaling = ScalingConfig::default();
+        let text_style =
+            Self::scaled_text_style(button_manager.window_size.height as f32, &scaling);
+        let menu = Self::menu_layout();
+        menu.apply(&mut button_manager, &text_style);
+        Self::add_debug_button(&mut button_manager, &text_style);
 
         Self {
             button_manager,
+            menu,
             visible: false,
             last_action: PauseMenuAction::None,
             show_debug_panel: false,
+            overlay_color: DEFAULT_OVERLAY_COLOR,
+            background_color: None,
+            scaling,
+            entrance_animation: Some(EntranceAnimation::new(0.05, EntranceDirection::FromBottom)),
+            action_sender: None,
+            confirm_dialog,
+            pending_confirm: None,
         }
     }
 
-    fn scaled_text_style(window_height: f32) -> crate::ui::text::TextStyle {
+    fn scaled_text_style(
+        window_height: f32,
+        scaling: &ScalingConfig,
+    ) -> crate::ui::text::TextStyle {
         // Virtual DPI scaling based on reference height
-        let reference_height = 1080.0;
-        let scale = (window_height / reference_height).clamp(0.7, 2.0);
-        let font_size = (32.0 * scale).clamp(16.0, 48.0); // 32px at 1080p, min 16, max 48
-        let line_height = (40.0 * scale).clamp(24.0, 60.0); // 40px at 1080p, min 24, max 60
+        let scale = dpi_scale(window_height);
+        let font_size = scaling.menu.clamp_size(32.0 * scale); // 32px at 1080p
+        let line_height = scaling.menu.clamp_line_height(40.0 * scale); // 40px at 1080p
         crate::ui::text::TextStyle {
             font_family: "HankenGrotesk".to_string(),
             font_size,
@@ -68,124 +182,100 @@ impl PauseMenu {
                 .color,
             weight: glyphon::Weight::MEDIUM,
             style: glyphon::Style::Normal,
+            wrap: crate::ui::text::TextWrap::Word,
+            direction: crate::ui::text::TextDirection::Auto,
+            letter_spacing: 0.0,
         }
     }
 
-    fn create_menu_buttons(button_manager: &mut ButtonManager, window_size: PhysicalSize<u32>) {
-        let reference_height = 1080.0;
-        let scale = (window_size.height as f32 / reference_height).clamp(0.7, 2.0);
-        // Button sizing with DPI scaling
-        let button_width = (window_size.width as f32 * 0.38 * scale).clamp(180.0, 600.0);
-        let button_height = (window_size.height as f32 * 0.09 * scale).clamp(32.0, 140.0);
-        let button_spacing = (window_size.height as f32 * 0.015 * scale).clamp(2.0, 24.0);
-        let total_height = button_height * 5.0 + button_spacing * 4.0;
-        let center_x = window_size.width as f32 / 2.0;
-        let start_y = (window_size.height as f32 - total_height) / 2.0;
-        let text_style = Self::scaled_text_style(window_size.height as f32);
-
-        // Helper for y position
-        let y =
-            |i: usize| start_y + button_height / 2.0 + i as f32 * (button_height + button_spacing);
-
-        // Resume button
-        let mut resume_style = create_primary_button_style();
-        resume_style.text_style = text_style.clone();
-        let resume_button = Button::new("resume", "Resume Game")
-            .with_style(resume_style)
-            .with_text_align(TextAlign::Center)
-            .with_position(
-                ButtonPosition::new(center_x, y(0), button_width, button_height)
-                    .with_anchor(ButtonAnchor::Center),
-            );
-
-        // Settings button is now 'Restart Run' with goldenrod style
-        let mut settings_style = create_goldenrod_button_style();
-        settings_style.text_style = text_style.clone();
-        let settings_button = Button::new("settings", "Restart Run")
-            .with_style(settings_style)
-            .with_text_align(TextAlign::Center)
-            .with_position(
-                ButtonPosition::new(center_x, y(1), button_width, button_height)
-                    .with_anchor(ButtonAnchor::Center),
-            );
-
-        // Toggle Test Mode button with goldenrod style
-        let mut test_mode_style = create_goldenrod_button_style();
-        test_mode_style.text_style = text_style.clone();
-        let test_mode_button = Button::new("toggle_test_mode", "Toggle Test Mode")
-            .with_style(test_mode_style)
-            .with_text_align(TextAlign::Center)
-            .with_position(
-                ButtonPosition::new(center_x, y(2), button_width, button_height)
-                    .with_anchor(ButtonAnchor::Center),
-            );
-
-        // Restart button is now 'Quit to Lobby' with less saturated red style
-        let mut restart_style = create_lobby_button_style();
-        restart_style.text_style = text_style.clone();
-        let restart_button = Button::new("restart", "Quit to Lobby")
-            .with_style(restart_style)
-            .with_text_align(TextAlign::Center)
-            .with_position(
-                ButtonPosition::new(center_x, y(3), button_width, button_height)
-                    .with_anchor(ButtonAnchor::Center),
-            );
-
-        // Quit to Menu button - Center aligned for comparison, now darker red
-        let mut quit_style = create_danger_button_style();
-        quit_style.text_style = text_style.clone();
-        let quit_menu_button = Button::new("quit_menu", "Quit App")
-            .with_style(quit_style)
-            .with_text_align(TextAlign::Center)
-            .with_position(
-                ButtonPosition::new(center_x, y(4), button_width, button_height)
-                    .with_anchor(ButtonAnchor::Center),
-            );
+    /// The main buttons, top to bottom
+    fn menu_layout() -> MenuBuilder<PauseMenuAction> {
+        MenuBuilder::new()
+            .item(
+                "resume",
+                "Resume Game",
+                create_primary_button_style(),
+                PauseMenuAction::Resume,
+            )
+            // Settings button is now 'Restart Run' with goldenrod style
+            .item(
+                "settings",
+                "Restart Run",
+                create_goldenrod_button_style(),
+                PauseMenuAction::Settings,
+            )
+            .item(
+                "toggle_test_mode",
+                "Toggle Test Mode",
+                create_goldenrod_button_style(),
+                PauseMenuAction::ToggleTestMode,
+            )
+            // Restart button is now 'Quit to Lobby' with less saturated red style
+            .item(
+                "restart",
+                "Quit to Lobby",
+                create_lobby_button_style(),
+                PauseMenuAction::Restart,
+            )
+            .item(
+                "quit_menu",
+                "Quit App",
+                create_danger_button_style(),
+                PauseMenuAction::QuitToMenu,
+            )
+    }
+
+    fn add_debug_button(
+        button_manager: &mut ButtonManager<PauseMenuAction>,
+        text_style: &crate::ui::text::TextStyle,
+    ) {
+        let window_size = button_manager.window_size;
 
         // Add debug button in bottom left
         let mut debug_style = create_warning_button_style();
         debug_style.text_style.font_size = text_style.font_size * 0.5;
         debug_style.text_style.line_height = text_style.line_height * 0.5;
-        debug_style.padding = (2.0 * scale, 6.0 * scale); // minimal horizontal, some vertical padding
-        debug_style.spacing = crate::ui::button::ButtonSpacing::Wrap;
-        // Measure the text width for three lines
+        // Scales with the button instead of being multiplied by the DPI scale by hand
+        debug_style.padding = Padding::Relative(0.02, 0.1);
+        debug_style.spacing = ButtonSpacing::Wrap;
+        // Measure all three lines of the label
         let (_min_x, text_width, text_height) = button_manager
             .text_renderer
-            .measure_text(" Show\nDebug\n  Info", &debug_style.text_style);
-        let debug_button_side = text_width.max(text_height) + 2.0 * debug_style.padding.1;
-        let debug_button = Button::new("debug", " Show\nDebug\n  Info")
+            .borrow()
+            .measure_text(DEBUG_LABEL, &debug_style.text_style);
+        // Same size add_button gives a Wrap button, so the bottom margin holds
+        let debug_button_width = debug_style.padding.fit_width(text_width);
+        let debug_button_height = debug_style.padding.fit_height(text_height);
+        let debug_button = Button::new("debug", DEBUG_LABEL)
             .with_style(debug_style)
             .with_text_align(TextAlign::Center)
             .with_position(ButtonPosition {
                 x: 60.0,
-                y: window_size.height as f32 - debug_button_side - 16.0, // 16px from bottom
-                width: debug_button_side,
-                height: debug_button_side,
+                y: window_size.height as f32 - debug_button_height - 16.0, // 16px from bottom
+                width: debug_button_width,
+                height: debug_button_height,
                 anchor: ButtonAnchor::TopLeft,
             });
 
-        // Add buttons to manager
-        button_manager.add_button(resume_button);
-        button_manager.add_button(settings_button);
-        button_manager.add_button(test_mode_button);
-        button_manager.add_button(restart_button);
-        button_manager.add_button(quit_menu_button);
         button_manager.add_button(debug_button);
-
-        // Update button positions to ensure text is properly centered
         button_manager.update_button_positions();
     }
 
     pub fn show(&mut self, is_test_mode: bool) {
+        // show is called every frame while the menu is up; only animate on opening
+        let opening = !self.visible;
         self.visible = true;
         self.last_action = PauseMenuAction::None;
 
         // Show all buttons
-        for button in self.button_manager.buttons.values_mut() {
-            button.set_visible(true);
-        }
+        self.button_manager.set_all_visible(true);
         // Ensure button text is made visible and styled immediately
         self.button_manager.update_button_states();
+        if opening {
+            if let Some(entrance) = &self.entrance_animation {
+                self.button_manager.start_entrance(entrance.clone());
+            }
+        }
         // Update the test mode button text
         self.update_test_mode_button_text(is_test_mode);
     }
@@ -193,46 +283,99 @@ impl PauseMenu {
     pub fn hide(&mut self) {
         self.visible = false;
         self.last_action = PauseMenuAction::None;
+        self.button_manager.stop_entrance();
+        self.confirm_dialog.hide();
+        self.pending_confirm = None;
 
         // Hide all buttons
-        for button in self.button_manager.buttons.values_mut() {
-            button.set_visible(false);
-        }
+        self.button_manager.set_all_visible(false);
+        // Nothing of the menu may be drawn by a host that renders it anyway
+        self.button_manager.rectangle_renderer.clear_rectangles();
     }
 
     pub fn is_visible(&self) -> bool {
         self.visible
     }
 
-    pub fn handle_input(&mut self, event: &WindowEvent) {
+    /// Cursor for the current mouse position while the menu is open
+    pub fn desired_cursor(&self) -> Option<CursorIcon> {
         if !self.visible {
-            return;
+            return None;
         }
-
-        self.button_manager.handle_input(event);
-
-        // Check for button clicks
-        if self.button_manager.is_button_clicked("resume") {
-            self.last_action = PauseMenuAction::Resume;
+        if self.confirm_dialog.is_visible() {
+            return self.confirm_dialog.desired_cursor();
         }
+        self.button_manager.desired_cursor()
+    }
 
-        if self.button_manager.is_button_clicked("settings") {
-            self.last_action = PauseMenuAction::Settings;
+    /// Advance the entrance animation and held repeat buttons by `delta_time` seconds
+    pub fn update(&mut self, delta_time: f32) {
+        for action in self.button_manager.update(delta_time) {
+            self.emit_action(action);
         }
+    }
+
+    /// Whether buttons are still animating in, so frames must keep coming
+    pub fn is_animating(&self) -> bool {
+        self.button_manager.is_animating()
+    }
 
-        if self.button_manager.is_button_clicked("restart") {
-            self.last_action = PauseMenuAction::Restart;
+    /// Feed `event` to the menu. Returns whether the menu consumed it, in
+    /// which case the game must not handle it too; see
+    /// `ButtonManager::input_consumed`.
+    pub fn handle_input(&mut self, event: &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
         }
 
-        if self.button_manager.is_button_clicked("quit_menu") {
-            self.last_action = PauseMenuAction::QuitToMenu;
+        // The dialog is modal: it gets everything until it is answered
+        if self.confirm_dialog.is_visible() {
+            if let Some(answer) = self.confirm_dialog.handle_input(event) {
+                let pending = self.pending_confirm.take();
+                if let (ConfirmAction::Confirmed, Some(action)) = (answer, pending) {
+                    self.emit_action(action);
+                }
+            }
+            return true;
         }
-        if self.button_manager.is_button_clicked("toggle_test_mode") {
-            self.last_action = PauseMenuAction::ToggleTestMode;
+
+        for action in self.button_manager.handle_input(event) {
+            self.request_action(action);
         }
         if self.button_manager.is_button_clicked("debug") {
             self.show_debug_panel = !self.show_debug_panel;
+            // Record state changes only while the panel can show them
+            self.button_manager
+                .set_transition_log(self.show_debug_panel.then_some(DEBUG_TRANSITION_LOG_SIZE));
+        }
+        self.button_manager.input_consumed()
+    }
+
+    /// Send every future action to `sender` the moment it happens, so none are
+    /// lost between `get_last_action` polls. Polling keeps working alongside it.
+    pub fn set_action_sender(&mut self, sender: Sender<PauseMenuAction>) {
+        self.action_sender = Some(sender);
+    }
+
+    /// Emit `action`, asking first for the ones that leave the run
+    fn request_action(&mut self, action: PauseMenuAction) {
+        let message = match action {
+            PauseMenuAction::QuitToMenu => "Quit the game? This run will be lost.",
+            PauseMenuAction::Restart => "Quit to the lobby? This run will be lost.",
+            _ => return self.emit_action(action),
+        };
+        self.pending_confirm = Some(action);
+        self.confirm_dialog.show_confirm(message);
+    }
+
+    fn emit_action(&mut self, action: PauseMenuAction) {
+        if let Some(sender) = &self.action_sender {
+            // The receiver went away, stop sending
+            if sender.send(action.clone()).is_err() {
+                self.action_sender = None;
+            }
         }
+        self.last_action = action;
     }
 
     pub fn get_last_action(&mut self) -> PauseMenuAction {
@@ -251,96 +394,46 @@ impl PauseMenu {
         // Recreate buttons with new positions regardless of visibility
         // This ensures proper centering when the menu becomes visible
         self.recreate_buttons_for_new_size();
+        self.confirm_dialog.resize(queue, resolution);
+    }
+
+    /// Replace the font bounds and lay the buttons out again
+    pub fn set_scaling(&mut self, scaling: ScalingConfig) {
+        self.scaling = scaling;
+        self.recreate_buttons_for_new_size();
     }
 
     fn recreate_buttons_for_new_size(&mut self) {
         let window_size = self.button_manager.window_size;
-        let reference_height = 1080.0;
-        let scale = (window_size.height as f32 / reference_height).clamp(0.7, 2.0);
-        let button_width = (window_size.width as f32 * 0.38 * scale).clamp(180.0, 600.0);
-        let button_height = (window_size.height as f32 * 0.09 * scale).clamp(32.0, 140.0);
-        let button_spacing = (window_size.height as f32 * 0.015 * scale).clamp(2.0, 24.0);
-        let total_height = button_height * 5.0 + button_spacing * 4.0;
-        let center_x = window_size.width as f32 / 2.0;
-        let start_y = (window_size.height as f32 - total_height) / 2.0;
-        let text_style = Self::scaled_text_style(window_size.height as f32);
-        let y =
-            |i: usize| start_y + button_height / 2.0 + i as f32 * (button_height + button_spacing);
-
-        // Update button positions and text style
-        if let Some(resume_button) = self.button_manager.get_button_mut("resume") {
-            resume_button.position.x = center_x;
-            resume_button.position.y = y(0);
-            resume_button.position.width = button_width;
-            resume_button.position.height = button_height;
-            resume_button.position.anchor = ButtonAnchor::Center;
-            resume_button.style.text_style = text_style.clone();
-        }
-
-        if let Some(settings_button) = self.button_manager.get_button_mut("settings") {
-            settings_button.text = "Restart Run".to_string();
-            settings_button.style = create_goldenrod_button_style();
-            settings_button.style.text_style = text_style.clone();
-            settings_button.position.x = center_x;
-            settings_button.position.y = y(1);
-            settings_button.position.width = button_width;
-            settings_button.position.height = button_height;
-            settings_button.position.anchor = ButtonAnchor::Center;
-        }
-
-        if let Some(test_mode_button) = self.button_manager.get_button_mut("toggle_test_mode") {
-            test_mode_button.text = "Toggle Test Mode".to_string();
-            test_mode_button.style = create_goldenrod_button_style();
-            test_mode_button.style.text_style = text_style.clone();
-            test_mode_button.position.x = center_x;
-            test_mode_button.position.y = y(2);
-            test_mode_button.position.width = button_width;
-            test_mode_button.position.height = button_height;
-            test_mode_button.position.anchor = ButtonAnchor::Center;
-        }
-
-        if let Some(restart_button) = self.button_manager.get_button_mut("restart") {
-            restart_button.text = "Quit to Lobby".to_string();
-            restart_button.style = create_lobby_button_style();
-            restart_button.style.text_style = text_style.clone();
-            restart_button.position.x = center_x;
-            restart_button.position.y = y(3);
-            restart_button.position.width = button_width;
-            restart_button.position.height = button_height;
-            restart_button.position.anchor = ButtonAnchor::Center;
-        }
-
-        if let Some(quit_menu_button) = self.button_manager.get_button_mut("quit_menu") {
-            quit_menu_button.style = create_danger_button_style();
-            quit_menu_button.style.text_style = text_style.clone();
-            quit_menu_button.position.x = center_x;
-            quit_menu_button.position.y = y(4);
-            quit_menu_button.position.width = button_width;
-            quit_menu_button.position.height = button_height;
-            quit_menu_button.position.anchor = ButtonAnchor::Center;
-        }
+        let text_style = Self::scaled_text_style(window_size.height as f32, &self.scaling);
+        self.menu.apply(&mut self.button_manager, &text_style);
 
         // Update debug button position for new window size
         let (style, padding) =
             if let Some(debug_button) = self.button_manager.get_button_mut("debug") {
-                debug_button.style.spacing = crate::ui::button::ButtonSpacing::Wrap;
+                debug_button.style.spacing = ButtonSpacing::Wrap;
                 (
                     debug_button.style.text_style.clone(),
                     debug_button.style.padding,
                 )
             } else {
-                (create_warning_button_style().text_style, (2.0, 6.0))
+                (
+                    create_warning_button_style().text_style,
+                    Padding::Relative(0.02, 0.1),
+                )
             };
         let (_min_x, text_width, text_height) = self
             .button_manager
             .text_renderer
-            .measure_text("Show\nDebug\nInfo", &style);
-        let side = text_width.max(text_height) + 2.0 * padding.1;
+            .borrow()
+            .measure_text(DEBUG_LABEL, &style);
+        let width = padding.fit_width(text_width);
+        let height = padding.fit_height(text_height);
         if let Some(debug_button) = self.button_manager.get_button_mut("debug") {
             debug_button.position.x = 60.0;
-            debug_button.position.y = window_size.height as f32 - side - 16.0;
-            debug_button.position.width = side;
-            debug_button.position.height = side;
+            debug_button.position.y = window_size.height as f32 - height - 16.0;
+            debug_button.position.width = width;
+            debug_button.position.height = height;
             debug_button.position.anchor = ButtonAnchor::TopLeft;
         }
 
@@ -348,34 +441,171 @@ impl PauseMenu {
         self.button_manager.update_button_positions();
     }
 
+    /// Draw the menu, dimming what's behind it, into a render pass the host
+    /// owns; see `ButtonManager::render_to`. Draws nothing while hidden.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        self.button_manager.render_to(
+            device,
+            queue,
+            surface_config,
+            render_pass,
+            Some(self.overlay_color),
+        )?;
+        self.confirm_dialog
+            .render_to(device, queue, surface_config, render_pass)
+    }
+
     pub fn prepare(
         &mut self,
         device: &Device,
         queue: &Queue,
         surface_config: &SurfaceConfiguration,
     ) -> Result<(), glyphon::PrepareError> {
-        self.button_manager.prepare(device, queue, surface_config)
+        self.button_manager.prepare(device, queue, surface_config)?;
+        self.confirm_dialog
+            .button_manager
+            .prepare(device, queue, surface_config)
     }
 
     pub fn render(
         &mut self,
         device: &Device,
+        queue: &Queue,
         render_pass: &mut RenderPass,
     ) -> Result<(), glyphon::RenderError> {
-        self.button_manager.render(device, render_pass)
+        // Dim the scene behind the menu first
+        self.button_manager
+            .render_overlay(device, queue, render_pass, self.overlay_color);
+        self.button_manager.render(device, queue, render_pass)?;
+        if self.confirm_dialog.is_visible() {
+            let dialog = &mut self.confirm_dialog;
+            dialog
+                .button_manager
+                .render_overlay(device, queue, render_pass, dialog.overlay_color);
+            dialog.button_manager.render(device, queue, render_pass)?;
+        }
+        Ok(())
     }
 
     pub fn is_debug_panel_visible(&self) -> bool {
         self.show_debug_panel
     }
 
+    /// The latest button state change as one line for the debug panel
+    pub fn last_transition_text(&self) -> Option<String> {
+        let transition = self.button_manager.last_transition()?;
+        Some(format!(
+            "{}: {:?} -> {:?} ({:?})",
+            transition.button_id, transition.from, transition.to, transition.cause
+        ))
+    }
+
     pub fn update_test_mode_button_text(&mut self, is_test_mode: bool) {
-        if let Some(button) = self.button_manager.get_button_mut("toggle_test_mode") {
-            if is_test_mode {
-                button.text = "Exit Test Mode".to_string();
-            } else {
-                button.text = "Enter Test Mode".to_string();
-            }
+        let text = if is_test_mode {
+            "Exit Test Mode"
+        } else {
+            "Enter Test Mode"
+        };
+        if let Err(e) = self.button_manager.set_text("toggle_test_mode", text) {
+            println!("Failed to update test mode button: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::confirm::{CANCEL_BUTTON_ID, CONFIRM_BUTTON_ID};
+    use crate::ui::test_support::{self, FORMAT, WINDOW_SIZE};
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, ElementState, MouseButton};
+
+    /// Move the cursor to the middle of `bounds` and click there
+    fn click_at(menu: &mut PauseMenu, bounds: (f32, f32, f32, f32)) {
+        let (x, y, width, height) = bounds;
+        menu.handle_input(&WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new((x + width / 2.0) as f64, (y + height / 2.0) as f64),
+        });
+        for state in [ElementState::Pressed, ElementState::Released] {
+            menu.handle_input(&WindowEvent::MouseInput {
+                device_id: DeviceId::dummy(),
+                state,
+                button: MouseButton::Left,
+            });
+        }
+    }
+
+    fn click_menu(menu: &mut PauseMenu, id: &str) {
+        let bounds = menu.button_manager.buttons[id].visual_bounds();
+        click_at(menu, bounds);
+    }
+
+    fn click_dialog(menu: &mut PauseMenu, id: &str) {
+        let bounds = menu.confirm_dialog.button_manager.buttons[id].visual_bounds();
+        click_at(menu, bounds);
+    }
+
+    #[test]
+    fn text_style_scales_by_dpi_scale() {
+        let scaling = ScalingConfig::default();
+        for height in [720.0, 1080.0, 1440.0] {
+            let style = PauseMenu::scaled_text_style(height, &scaling);
+            assert_eq!(style.font_size, 32.0 * dpi_scale(height));
+            assert_eq!(style.line_height, 40.0 * dpi_scale(height));
+        }
+    }
+
+    #[test]
+    fn quitting_waits_for_the_confirm_dialog() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut menu = PauseMenu::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        menu.entrance_animation = None;
+        menu.show(false);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        menu.set_action_sender(sender);
+
+        for (id, action, message) in [
+            (
+                "quit_menu",
+                PauseMenuAction::QuitToMenu,
+                "Quit the game? This run will be lost.",
+            ),
+            (
+                "restart",
+                PauseMenuAction::Restart,
+                "Quit to the lobby? This run will be lost.",
+            ),
+        ] {
+            click_menu(&mut menu, id);
+            assert!(menu.confirm_dialog.is_visible());
+            assert_eq!(menu.confirm_dialog.message(), message);
+            assert_eq!(menu.pending_confirm, Some(action.clone()));
+            assert_eq!(menu.get_last_action(), PauseMenuAction::None);
+
+            // Cancelling drops the pending action without emitting anything
+            click_dialog(&mut menu, CANCEL_BUTTON_ID);
+            assert!(!menu.confirm_dialog.is_visible());
+            assert_eq!(menu.pending_confirm, None);
+            assert_eq!(menu.get_last_action(), PauseMenuAction::None);
+            assert!(receiver.try_recv().is_err());
+
+            click_menu(&mut menu, id);
+            click_dialog(&mut menu, CONFIRM_BUTTON_ID);
+            assert_eq!(menu.pending_confirm, None);
+            assert_eq!(menu.get_last_action(), action);
+            assert_eq!(receiver.try_recv(), Ok(action));
         }
     }
 }
@@ -6,11 +6,21 @@ var s_icon: sampler;
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) uv: vec2<f32>,
+    @location(2) tint: vec4<f32>,
+    @location(3) size: vec2<f32>,
+    // x: mask kind (0 = circle, 1 = rounded rect), y: corner radius in pixels
+    @location(4) mask: vec2<f32>,
+    // Position inside the quad, 0..1 from the top-left, for the masks
+    @location(5) local: vec2<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
+    @location(1) tint: vec4<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) mask: vec2<f32>,
+    @location(4) local: vec2<f32>,
 }
 
 @vertex
@@ -18,18 +28,32 @@ fn vs_main(vertex: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.position = vec4<f32>(vertex.position, 0.0, 1.0);
     out.uv = vertex.uv;
+    out.tint = vertex.tint;
+    out.size = vertex.size;
+    out.mask = vertex.mask;
+    out.local = vertex.local;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let tex_color = textureSample(t_icon, s_icon, in.uv);
-    
+    let tex_color = textureSample(t_icon, s_icon, in.uv) * in.tint;
+
+    if (in.mask.x > 0.5) {
+        // Rounded rectangle: signed distance in pixels from the rounded edge
+        let radius = in.mask.y;
+        let half_size = in.size * 0.5;
+        let p = abs((in.local - vec2<f32>(0.5, 0.5)) * in.size) - (half_size - vec2<f32>(radius, radius));
+        let dist = length(max(p, vec2<f32>(0.0, 0.0))) + min(max(p.x, p.y), 0.0) - radius;
+        let rect_alpha = 1.0 - smoothstep(-0.5, 0.5, dist);
+        return vec4<f32>(tex_color.rgb, tex_color.a * rect_alpha);
+    }
+
     // Only apply antialiasing if the texture has some alpha (not fully transparent)
     if (tex_color.a > 0.0) {
         // Calculate distance from center (assuming circular icon)
         let center = vec2<f32>(0.5, 0.5);
-        let distance = length(in.uv - center);
+        let distance = length(in.local - center);
         
         // Create smooth antialiased edge only where the texture is already visible
         let edge_width = 0.01; // Smaller edge width for subtle smoothing
@@ -43,4 +67,4 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     
     // Return original texture if it's fully transparent
     return tex_color;
-} 
\ No newline at end of file
+}
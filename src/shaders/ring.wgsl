@@ -0,0 +1,51 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) local: vec2<f32>,
+    // outer radius, thickness, start angle, sweep angle
+    @location(3) params: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+    @location(2) params: vec4<f32>,
+}
+
+const TAU: f32 = 6.28318530718;
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(vertex.position, 0.0, 1.0);
+    out.color = vertex.color;
+    out.local = vertex.local;
+    out.params = vertex.params;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = in.params.x;
+    let thickness = in.params.y;
+    let start = in.params.z;
+    let sweep = in.params.w;
+
+    // Signed distance to the ring band, in pixels
+    let dist = length(in.local);
+    let band = abs(dist - (radius - thickness * 0.5)) - thickness * 0.5;
+    var alpha = 1.0 - smoothstep(-1.0, 1.0, band);
+
+    if (sweep < TAU) {
+        // Angle measured clockwise from 12 o'clock (local y points down)
+        let angle = atan2(in.local.x, -in.local.y);
+        let relative = fract((angle - start) / TAU) * TAU;
+        // Anti-alias the end of the arc over about one pixel of arc length
+        alpha *= clamp((sweep - relative) * dist + 0.5, 0.0, 1.0);
+    }
+
+    var output_color = in.color;
+    output_color.a *= alpha;
+    return output_color;
+}
@@ -0,0 +1,112 @@
+/// Curves mapping linear progress (0.0..=1.0) to eased progress
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing {
+    Linear,
+    /// Fast start, gentle landing
+    #[default]
+    EaseOutCubic,
+    EaseInOutCubic,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Side of its final position a button slides in from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EntranceDirection {
+    /// Starts above its slot and moves down
+    FromTop,
+    /// Starts below its slot and moves up
+    #[default]
+    FromBottom,
+}
+
+/// Slide-and-fade for a menu opening. Each button starts `stagger` seconds
+/// after the one before it in `button_order`.
+#[derive(Debug, Clone)]
+pub struct EntranceAnimation {
+    /// Seconds each button takes to arrive
+    pub duration: f32,
+    /// Delay in seconds between consecutive buttons
+    pub stagger: f32,
+    /// How far each button travels, in pixels
+    pub distance: f32,
+    pub direction: EntranceDirection,
+    pub easing: Easing,
+    elapsed: f32,
+}
+
+impl EntranceAnimation {
+    pub fn new(stagger: f32, direction: EntranceDirection) -> Self {
+        Self {
+            duration: 0.25,
+            stagger: stagger.max(0.0),
+            distance: 24.0,
+            direction,
+            easing: Easing::default(),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration.max(0.0);
+        self
+    }
+
+    pub fn with_distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn advance(&mut self, delta_time: f32) {
+        self.elapsed += delta_time.max(0.0);
+    }
+
+    /// Jump to the end, with every button in its final position
+    pub fn finish(&mut self) {
+        self.elapsed = f32::INFINITY;
+    }
+
+    /// Eased progress of the button at `index`, 0.0 before it starts
+    pub fn progress(&self, index: usize) -> f32 {
+        let local = self.elapsed - self.stagger * index as f32;
+        if self.duration <= 0.0 {
+            return if local >= 0.0 { 1.0 } else { 0.0 };
+        }
+        self.easing.apply(local / self.duration)
+    }
+
+    /// Vertical pixel offset of the button at `index` from its final position
+    pub fn offset(&self, index: usize) -> f32 {
+        let remaining = self.distance * (1.0 - self.progress(index));
+        match self.direction {
+            EntranceDirection::FromTop => -remaining,
+            EntranceDirection::FromBottom => remaining,
+        }
+    }
+
+    /// Whether all of `count` buttons have arrived
+    pub fn is_finished(&self, count: usize) -> bool {
+        let last_start = self.stagger * count.saturating_sub(1) as f32;
+        self.elapsed >= last_start + self.duration
+    }
+}
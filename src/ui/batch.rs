@@ -0,0 +1,193 @@
+use egui_wgpu::wgpu::{self, Buffer, BufferUsages, Device, Queue};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of distinct batches kept alive across frames. A frame that draws
+/// more keeps them all until a later frame, see `BatchCache`.
+const MAX_CACHED_BATCHES: usize = 8;
+
+/// Current frame number, advanced by `begin_frame`
+static FRAME: AtomicU64 = AtomicU64::new(0);
+
+/// Start a new frame for every `BatchCache`; call once per frame before
+/// anything draws. Until the next call, buffers drawn from are never
+/// overwritten, so a cache that is never told about frames only grows.
+pub fn begin_frame() {
+    FRAME.fetch_add(1, Ordering::Relaxed);
+}
+
+struct CachedBatch {
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    last_used: u64,
+    /// Frame the batch was last drawn in
+    frame: u64,
+}
+
+/// Vertex/index buffers cached by a hash of their contents.
+///
+/// Unchanged geometry skips the upload entirely. Changed geometry reuses the
+/// oldest slot through `queue.write_buffer` and only allocates new GPU buffers
+/// when that slot is too small. Writes land before the frame is submitted,
+/// so a slot drawn earlier in the current frame is never reused: that draw
+/// would show the new data. A frame drawing more than `MAX_CACHED_BATCHES`
+/// batches allocates extra slots instead, trimmed in later frames.
+#[derive(Default)]
+pub struct BatchCache {
+    batches: HashMap<u64, CachedBatch>,
+    tick: u64,
+    allocations: usize,
+    uploads: usize,
+}
+
+impl BatchCache {
+    /// Return GPU buffers holding `vertices` and `indices`, uploading only if
+    /// this exact geometry isn't cached yet
+    pub fn buffers(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        label: &str,
+        vertices: &[u8],
+        indices: &[u8],
+    ) -> (&Buffer, &Buffer) {
+        let mut hasher = DefaultHasher::new();
+        vertices.hash(&mut hasher);
+        indices.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        self.tick += 1;
+        if !self.batches.contains_key(&hash) {
+            let batch = self.upload(device, queue, label, vertices, indices);
+            self.batches.insert(hash, batch);
+        }
+
+        let batch = self.batches.get_mut(&hash).expect("batch was cached above");
+        batch.last_used = self.tick;
+        batch.frame = FRAME.load(Ordering::Relaxed);
+        (&batch.vertex_buffer, &batch.index_buffer)
+    }
+
+    /// Total number of GPU buffer pairs created so far
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Total number of times geometry had to be sent to the GPU
+    pub fn uploads(&self) -> usize {
+        self.uploads
+    }
+
+    fn upload(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        label: &str,
+        vertices: &[u8],
+        indices: &[u8],
+    ) -> CachedBatch {
+        self.uploads += 1;
+        let frame = FRAME.load(Ordering::Relaxed);
+
+        // Once the cache is full, drop the least recently used slots of
+        // earlier frames down to the limit and recycle the last one dropped
+        let mut recycled = None;
+        while self.batches.len() >= MAX_CACHED_BATCHES {
+            let Some(oldest) = self
+                .batches
+                .iter()
+                .filter(|(_, batch)| batch.frame != frame)
+                .min_by_key(|(_, batch)| batch.last_used)
+                .map(|(hash, _)| *hash)
+            else {
+                break;
+            };
+            recycled = self.batches.remove(&oldest);
+        }
+        if let Some(mut batch) = recycled {
+            if batch.vertex_buffer.size() >= vertices.len() as u64
+                && batch.index_buffer.size() >= indices.len() as u64
+            {
+                queue.write_buffer(&batch.vertex_buffer, 0, vertices);
+                queue.write_buffer(&batch.index_buffer, 0, indices);
+                batch.last_used = self.tick;
+                batch.frame = frame;
+                return batch;
+            }
+        }
+
+        self.allocations += 1;
+        CachedBatch {
+            vertex_buffer: Self::create_buffer(
+                device,
+                queue,
+                &format!("{} Vertex Buffer", label),
+                vertices,
+                BufferUsages::VERTEX,
+            ),
+            index_buffer: Self::create_buffer(
+                device,
+                queue,
+                &format!("{} Index Buffer", label),
+                indices,
+                BufferUsages::INDEX,
+            ),
+            last_used: self.tick,
+            frame,
+        }
+    }
+
+    fn create_buffer(
+        device: &Device,
+        queue: &Queue,
+        label: &str,
+        contents: &[u8],
+        usage: BufferUsages,
+    ) -> Buffer {
+        // Round up so a recycled slot can absorb small growth without reallocating
+        let size = (contents.len() as u64)
+            .max(wgpu::COPY_BUFFER_ALIGNMENT)
+            .next_power_of_two();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: usage | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&buffer, 0, contents);
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support;
+
+    #[test]
+    fn batches_drawn_this_frame_are_not_overwritten() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut cache = BatchCache::default();
+        let batch = |i: u32| (i.to_le_bytes(), (i + 1).to_le_bytes());
+
+        begin_frame();
+        let count = MAX_CACHED_BATCHES as u32 + 4;
+        for i in 0..count {
+            let (vertices, indices) = batch(i);
+            cache.buffers(&device, &queue, "Test", &vertices, &indices);
+        }
+        // Every batch of the frame kept a slot of its own
+        assert_eq!(cache.allocations(), count as usize);
+
+        begin_frame();
+        let (vertices, indices) = batch(count);
+        cache.buffers(&device, &queue, "Test", &vertices, &indices);
+        // A slot of the previous frame was recycled and the extras dropped
+        assert_eq!(cache.allocations(), count as usize);
+        assert_eq!(cache.batches.len(), MAX_CACHED_BATCHES);
+    }
+}
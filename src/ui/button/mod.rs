@@ -5,21 +5,59 @@ pub mod utils;
 
 // Re-export types for convenience
 pub use styles::*;
-pub use types::{ButtonAnchor, ButtonPosition, ButtonSpacing, ButtonState, ButtonStyle, TextAlign};
+pub use types::{
+    ButtonAnchor, ButtonPosition, ButtonSpacing, ButtonState, ButtonStyle, ButtonStyleBuilder,
+    ClickButtons, Padding, RepeatConfig, StateTransition, TextAlign, TransitionCause,
+    TransitionLog, VerticalAlign,
+};
 pub use utils::ColorExt;
 
-use crate::ui::icon::{Icon, IconRenderer};
+use crate::ui::animation::EntranceAnimation;
+use crate::ui::clip::{scissor_rect, ClipRegion};
+use crate::ui::drag::Draggable;
+use crate::ui::icon::{Icon, IconFit, IconMask, IconRenderer};
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::progress_bar::ProgressBar;
 use crate::ui::rectangle::{Rectangle, RectangleRenderer};
-use crate::ui::text::{TextPosition, TextRenderer, TextStyle};
+use crate::ui::text::{SharedTextRenderer, TextDirection, TextPosition, TextRenderer, TextStyle};
+use crate::ui::text_input::TextInput;
+use crate::ui::tooltip::TooltipPlacement;
 use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
 use glyphon::{Color, Style, Weight};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, MouseButton, WindowEvent};
-use winit::window::Window;
+use winit::event::{ElementState, WindowEvent};
+use winit::window::{CursorIcon, Window};
+
+/// Tall buttons grow 10% while hovered
+const TALL_HOVER_SCALE: f32 = 1.1;
+/// and 5% while pressed
+const TALL_PRESSED_SCALE: f32 = 1.05;
+/// Gap between a floating tooltip and its button, in pixels
+const TOOLTIP_GAP: f32 = 8.0;
+/// Space between the tooltip box edge and its text, in pixels
+const TOOLTIP_PADDING: f32 = 8.0;
+/// Id of the text buffer of the floating tooltip, see `Button::with_tooltip`
+const FLOATING_TOOLTIP_ID: &str = "floating_tooltip";
+/// `(texture id, path)` of the textures every `ButtonManager` loads
+const BUILTIN_TEXTURES: [(&str, &str); 1] = [("blank_icon", "assets/icons/blank-icon.png")];
+
+/// Left edge for a label buffer whose text should start at `text_x`.
+/// Right-to-left buffers align lines to their right edge, so the buffer is
+/// shifted left by the width the text doesn't use.
+fn label_buffer_x(text_x: f32, text_width: f32, max_width: f32, style: &TextStyle) -> f32 {
+    match style.direction {
+        TextDirection::Rtl => text_x + text_width - max_width,
+        _ => text_x,
+    }
+}
 
+/// A clickable button. `A` is the action reported when it is clicked,
+/// typically a menu's action enum.
 #[derive(Debug)]
-pub struct Button {
+pub struct Button<A = String> {
     pub id: String,
     pub text: String,
     pub style: ButtonStyle,
@@ -27,12 +65,38 @@ pub struct Button {
     pub enabled: bool,
     pub visible: bool,
     pub state: ButtonState,
+    /// Shown as `ButtonState::Selected` whenever it isn't hovered; see `set_selected`
+    pub selected: bool,
     pub text_id: String,
     pub level_text_id: Option<String>, // For additional text like "Level 1"
     pub tooltip_text_id: Option<String>, // For tooltip text below level text
+    /// Texture drawn on the button; see `with_icon`
+    pub icon_id: Option<String>,
+    /// Returned by `ButtonManager::handle_input` when the button is clicked
+    pub action: Option<A>,
+    /// Shown in the tooltip while the button is disabled and hovered
+    pub disabled_reason: Option<String>,
+    /// Tooltip text replaced by `disabled_reason` while it is shown
+    saved_tooltip: Option<String>,
+    /// Shown in a box next to the button while it is hovered; see `with_tooltip`
+    pub tooltip: Option<String>,
+    /// Side of the button the `tooltip` box appears on
+    pub tooltip_placement: TooltipPlacement,
+    /// Multiplied into the background, label and icon alpha; see `ButtonManager::set_opacity`
+    pub opacity: f32,
+    /// Fire on press and then repeatedly while held, instead of once on release
+    pub repeat: Option<RepeatConfig>,
+    /// Seconds the button has been held down, for `repeat`
+    held_for: f32,
+    /// Label size from the last layout, see `ButtonManager::measured_size`
+    measured_text: (f32, f32),
+    /// Vertical displacement from the layout position while the entrance animation runs
+    entrance_offset: f32,
+    /// Fade applied by the entrance animation
+    entrance_alpha: f32,
 }
 
-impl Button {
+impl<A> Button<A> {
     pub fn new(id: &str, text: &str) -> Self {
         let text_id = format!("button_{}", id);
         Self {
@@ -43,12 +107,45 @@ impl Button {
             enabled: true,
             visible: true,
             state: ButtonState::Normal,
+            selected: false,
             text_id,
             level_text_id: None,
             tooltip_text_id: None,
+            icon_id: None,
+            action: None,
+            disabled_reason: None,
+            saved_tooltip: None,
+            tooltip: None,
+            tooltip_placement: TooltipPlacement::default(),
+            opacity: 1.0,
+            repeat: None,
+            held_for: 0.0,
+            measured_text: (0.0, 0.0),
+            entrance_offset: 0.0,
+            entrance_alpha: 1.0,
         }
     }
 
+    /// Start out selected, e.g. the default tab
+    pub fn with_selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Associate an action value (typically a menu's action enum) with this button
+    pub fn with_id_action(mut self, action: A) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// The button's action, if it has one
+    pub fn action(&self) -> Option<A>
+    where
+        A: Clone,
+    {
+        self.action.clone()
+    }
+
     pub fn with_style(mut self, style: ButtonStyle) -> Self {
         self.style = style;
         self
@@ -74,75 +171,593 @@ impl Button {
         self
     }
 
+    /// Show `text` in a box next to the button while it is hovered, unlike
+    /// `with_tooltip_text` which draws inside the button
+    pub fn with_tooltip(mut self, text: &str) -> Self {
+        self.tooltip = Some(text.to_string());
+        self
+    }
+
+    /// Side of the button the `with_tooltip` box appears on; `Auto` by default
+    pub fn with_tooltip_placement(mut self, placement: TooltipPlacement) -> Self {
+        self.tooltip_placement = placement;
+        self
+    }
+
+    /// Draw `texture_id` (loaded into the icon renderer) on the button. Tall buttons show
+    /// it in place of the blank icon; a button with empty text becomes icon-only, sized
+    /// around the icon and centering it.
+    pub fn with_icon(mut self, texture_id: &str) -> Self {
+        self.icon_id = Some(texture_id.to_string());
+        self
+    }
+
+    /// Draw the whole button at `opacity`, clamped to 0.0..=1.0
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fire on press and then every `repeat.interval` seconds while held
+    pub fn with_repeat(mut self, repeat: RepeatConfig) -> Self {
+        self.repeat = Some(repeat);
+        self
+    }
+
+    /// Opacity including the entrance fade
+    fn alpha(&self) -> f32 {
+        self.opacity * self.entrance_alpha
+    }
+
+    /// An icon with no label
+    pub fn is_icon_only(&self) -> bool {
+        self.icon_id.is_some() && self.text.is_empty()
+    }
+
+    /// Explain why the button is disabled. Needs `with_tooltip_text`, since the
+    /// reason is shown in the tooltip when the disabled button is hovered.
+    pub fn with_disabled_reason(mut self, reason: &str) -> Self {
+        self.disabled_reason = Some(reason.to_string());
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
     pub fn set_visible(&mut self, visible: bool) {
         self.visible = visible;
     }
 
+    /// Only flips the flag; use `ButtonManager::set_enabled` to also restyle the text
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Tall and icon-only buttons grow while hovered or pressed
+    fn scales_on_hover(&self) -> bool {
+        matches!(self.style.spacing, ButtonSpacing::Tall(_)) || self.is_icon_only()
+    }
+
+    /// Scale the renderer applies to this button: Tall and icon-only buttons grow
+    /// while hovered or pressed, everything else is drawn at its layout size
+    pub fn visual_scale(&self) -> f32 {
+        if !self.scales_on_hover() {
+            return 1.0;
+        }
+        match self.state {
+            ButtonState::Hover => TALL_HOVER_SCALE,
+            ButtonState::Pressed => TALL_PRESSED_SCALE,
+            _ => 1.0,
+        }
+    }
+
+    /// Largest scale this button is drawn at while hovered or pressed
+    pub fn hover_scale(&self) -> f32 {
+        if self.scales_on_hover() {
+            TALL_HOVER_SCALE.max(TALL_PRESSED_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    /// Measured label size, or a one-line square for icon-only buttons so an
+    /// empty string doesn't collapse the layout
+    fn label_size(&self, measured_width: f32, measured_height: f32) -> (f32, f32) {
+        if self.is_icon_only() {
+            let line_height = self.style.text_style.line_height;
+            (line_height, line_height)
+        } else {
+            (measured_width, measured_height)
+        }
+    }
+
+    /// `(width, height)` of the button around a `text_width` x `text_height`
+    /// label in a window of `window_size`
+    fn fit_size(
+        &self,
+        text_width: f32,
+        text_height: f32,
+        window_size: PhysicalSize<u32>,
+    ) -> (f32, f32) {
+        match self.style.spacing {
+            ButtonSpacing::Wrap => {
+                let width = self.style.padding.fit_width(text_width);
+                let height = self.style.padding.fit_height(text_height);
+                (width, height)
+            }
+            ButtonSpacing::Hbar(_) => {
+                let width = self
+                    .style
+                    .hbar_width(window_size.width as f32)
+                    .unwrap_or_default();
+                let height = self.style.padding.fit_height(text_height);
+                (width, height)
+            }
+            ButtonSpacing::Tall(height_proportion) => {
+                // Tall buttons use a proportion of the window height
+                // Use the position width if it's set, otherwise use text width
+                let width = if self.position.width > 0.0 {
+                    self.position.width
+                } else {
+                    self.style.padding.fit_width(text_width)
+                };
+                let height = window_size.height as f32 * height_proportion;
+                (width, height)
+            }
+            ButtonSpacing::Fixed(width, height) => (width, height),
+        }
+    }
+
+    /// Label color and weight for the button's current state
+    fn label_appearance(&self) -> (Color, Weight) {
+        match self.state {
+            ButtonState::Normal => (
+                self.style.background_color.darken(0.35), // 35% darker than bg
+                self.style.text_style.weight,
+            ),
+            ButtonState::Hover => (
+                self.style.hover_color.saturate(0.90), // much brighter and more saturated
+                Weight::BOLD,
+            ),
+            ButtonState::Pressed => (
+                self.style.pressed_color.brighten(0.15).saturate(0.35), // brighter and more saturated
+                Weight::MEDIUM,
+            ),
+            ButtonState::Selected => (
+                self.style.selected_color.brighten(0.15).saturate(0.35),
+                Weight::BOLD,
+            ),
+            ButtonState::Disabled => (
+                // Muted like the normal label, but following the theme's disabled color
+                self.style
+                    .disabled_text_color
+                    .unwrap_or_else(|| self.style.disabled_color.darken(0.35)),
+                Weight::NORMAL,
+            ),
+        }
+    }
+
+    /// `(horizontal, vertical)` padding in pixels at the button's current size
+    pub fn padding(&self) -> (f32, f32) {
+        self.style
+            .padding
+            .resolve(self.position.width, self.position.height)
+    }
+
+    /// `(x, y, width, height)` of the button scaled by `scale` around its center
+    pub fn scaled_bounds(&self, scale: f32) -> (f32, f32, f32, f32) {
+        let (actual_x, actual_y) = self.position.calculate_actual_position();
+        let scaled_width = self.position.width * scale;
+        let scaled_height = self.position.height * scale;
+        (
+            actual_x - (scaled_width - self.position.width) / 2.0,
+            actual_y - (scaled_height - self.position.height) / 2.0 + self.entrance_offset,
+            scaled_width,
+            scaled_height,
+        )
+    }
+
+    /// Bounds as currently drawn, including hover scaling
+    pub fn visual_bounds(&self) -> (f32, f32, f32, f32) {
+        self.scaled_bounds(self.visual_scale())
+    }
+
+    /// Corner radius as drawn: scaled with the button, and never more than
+    /// half its smaller side
+    pub fn visual_corner_radius(&self) -> f32 {
+        let (_x, _y, width, height) = self.visual_bounds();
+        (self.style.corner_radius * self.visual_scale())
+            .min(width.min(height) / 2.0)
+            .max(0.0)
+    }
+
+    /// Hit test against the bounds as currently drawn, so a hovered Tall
+    /// button is clickable everywhere it appears
     pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        self.contains_point_scaled(x, y, self.visual_scale())
+    }
+
+    /// Whether the cursor is over this visible button, even if it is disabled.
+    /// Use `contains_point` for clicks.
+    pub fn is_over(&self, x: f32, y: f32) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        let (bounds_x, bounds_y, width, height) = self.visual_bounds();
+
+        x >= bounds_x && x <= bounds_x + width && y >= bounds_y && y <= bounds_y + height
+    }
+
+    /// Hit test against the bounds scaled by `scale` around the center
+    pub fn contains_point_scaled(&self, x: f32, y: f32, scale: f32) -> bool {
         if !self.visible || !self.enabled {
             return false;
         }
 
-        let (actual_x, actual_y) = self.position.calculate_actual_position();
+        let (bounds_x, bounds_y, width, height) = self.scaled_bounds(scale);
+
+        x >= bounds_x && x <= bounds_x + width && y >= bounds_y && y <= bounds_y + height
+    }
+
+    /// State with the cursor away from the button
+    fn resting_state(&self) -> ButtonState {
+        if !self.visible || !self.enabled {
+            ButtonState::Disabled
+        } else if self.selected {
+            ButtonState::Selected
+        } else {
+            ButtonState::Normal
+        }
+    }
+
+    /// State for a cursor at `(x, y)` with the mouse button `pressed`.
+    ///
+    /// Hover has hysteresis: it starts inside the layout rect, but only ends
+    /// once the cursor leaves the grown hover rect, so scaling can't toggle it
+    /// at the edge.
+    fn next_state(&self, x: f32, y: f32, pressed: bool) -> ButtonState {
+        if !self.visible || !self.enabled {
+            return ButtonState::Disabled;
+        }
 
-        x >= actual_x
-            && x <= actual_x + self.position.width
-            && y >= actual_y
-            && y <= actual_y + self.position.height
+        let was_hovered = matches!(self.state, ButtonState::Hover | ButtonState::Pressed);
+        let scale = if was_hovered { self.hover_scale() } else { 1.0 };
+        let is_hovered = self.contains_point_scaled(x, y, scale);
+
+        if pressed && is_hovered {
+            ButtonState::Pressed
+        } else if is_hovered {
+            ButtonState::Hover
+        } else if self.selected {
+            ButtonState::Selected
+        } else {
+            ButtonState::Normal
+        }
     }
 }
 
-pub struct ButtonManager {
-    pub buttons: HashMap<String, Button>,
-    pub button_order: Vec<String>, // Track the order buttons were added
-    pub text_renderer: TextRenderer,
+/// Lays out, draws and hit-tests a set of buttons whose clicks report
+/// actions of type `A`
+///
+/// Positions are in UI coordinates: physical pixels of the render target,
+/// `window_size` (the surface size), from the top-left corner with y down.
+/// Cursor events arrive in the window's physical pixels and are mapped into
+/// UI coordinates through `cursor_space` when the two sizes differ.
+pub struct ButtonManager<A = String> {
+    /// Prefer `iter_ordered` for reading; the map has no order
+    pub buttons: HashMap<String, Button<A>>,
+    pub button_order: Vec<String>, // Render order: as added, unless moved with bring_to_front
+    /// May be shared with other components, see `with_text_renderer`
+    pub text_renderer: SharedTextRenderer,
+    /// Namespace of every text buffer this manager creates, see `text_id`
+    pub text_namespace: String,
     pub rectangle_renderer: RectangleRenderer,
     pub icon_renderer: IconRenderer,
     pub window_size: PhysicalSize<u32>,
+    /// Size of the window the cursor positions are reported in, when it may
+    /// differ from `window_size`; see `set_cursor_space`
+    pub cursor_space: Option<PhysicalSize<u32>>,
+    /// Last cursor position, in UI coordinates
     pub mouse_position: (f32, f32),
     pub mouse_pressed: bool,
     pub just_clicked: Option<String>,
     pub container_rect: Option<Rectangle>, // For upgrade menu container
+    pub progress_bars: Vec<ProgressBar>,   // Drawn after buttons, in the order added
     pub last_mouse_position: (f32, f32),   // Cache for mouse position changes
     pub last_mouse_pressed: bool,          // Cache for mouse press state
+    pub entrance: Option<EntranceAnimation>, // Running menu entrance, if any
+    /// Handles that move groups of buttons when dragged, see `add_draggable`
+    pub draggables: Vec<Draggable>,
+    /// Text entry boxes, drawn after the progress bars; see `add_text_input`
+    pub text_inputs: Vec<TextInput>,
+    /// Area everything but the overlay is clipped to; `None` draws anywhere
+    pub clip: Option<ClipRegion>,
+    /// Mouse buttons that press and click buttons
+    pub click_buttons: ClickButtons,
+    /// Whether the UI used the last event passed to `handle_input`, see `input_consumed`
+    input_consumed: bool,
+    /// Whether the mouse button currently held went down over the UI, so
+    /// its release belongs to the UI too
+    press_consumed: bool,
+    /// Background of the floating tooltip while one is shown
+    tooltip_box: Option<Rectangle>,
+    /// Button whose floating tooltip text is currently laid out
+    tooltip_button: Option<String>,
+    /// Recent state changes for debugging input; `None` records nothing
+    transition_log: Option<TransitionLog>,
+    /// Reported for the transitions of the next refresh
+    transition_cause: TransitionCause,
+    /// Called with the button id when the cursor starts hovering a button
+    on_hover_enter: Option<Box<dyn FnMut(&str)>>,
+    /// Called with the button id when a button is clicked
+    on_click: Option<Box<dyn FnMut(&str)>>,
 }
 
-impl ButtonManager {
-    pub fn new(
+impl<A: Clone> ButtonManager<A> {
+    /// Sized to the window's current inner size
+    pub fn from_window(
         device: &Device,
         queue: &Queue,
         surface_format: wgpu::TextureFormat,
         window: &Window,
     ) -> Self {
-        let text_renderer = TextRenderer::new(device, queue, surface_format, window);
-        let rectangle_renderer = RectangleRenderer::new(device, surface_format);
-        let mut icon_renderer = IconRenderer::new(device, surface_format);
-        let window_size = window.inner_size();
-
-        // Load the blank icon texture
-        if let Err(e) =
-            icon_renderer.load_texture(device, queue, "assets/icons/blank-icon.png", "blank_icon")
-        {
-            println!("Failed to load blank icon texture: {}", e);
+        Self::new(device, queue, surface_format, window.inner_size())
+    }
+
+    /// Build without a window, e.g. for offscreen rendering. A texture that
+    /// fails to load is logged and its icons are skipped; use `try_new` to
+    /// treat that as an error instead.
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`,
+    /// e.g. a depth buffer shared with a 3D scene
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        let text_renderer =
+            TextRenderer::new_with_config(device, queue, surface_format, window_size, config);
+        let mut button_manager = Self::without_textures(
+            device,
+            surface_format,
+            window_size,
+            config,
+            Rc::new(RefCell::new(text_renderer)),
+            "",
+        );
+        if let Err(e) = button_manager.load_builtin_textures(device, queue) {
+            println!("{}", e);
+        }
+        button_manager
+    }
+
+    /// Like `new_with_config`, drawing text through a renderer shared with
+    /// other components, so they all use one glyph atlas and `prepare`. The
+    /// manager's buffers are kept apart from theirs under `namespace`.
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+        namespace: &str,
+    ) -> Self {
+        let mut button_manager = Self::without_textures(
+            device,
+            surface_format,
+            window_size,
+            config,
+            Rc::clone(text_renderer),
+            namespace,
+        );
+        if let Err(e) = button_manager.load_builtin_textures(device, queue) {
+            println!("{}", e);
+        }
+        button_manager
+    }
+
+    /// Like `new`, but fails if a built-in texture such as the blank upgrade
+    /// icon can't be loaded, so the host can decide what to do
+    pub fn try_new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Result<Self, String> {
+        let config = PipelineConfig::default();
+        let text_renderer =
+            TextRenderer::new_with_config(device, queue, surface_format, window_size, &config);
+        let mut button_manager = Self::without_textures(
+            device,
+            surface_format,
+            window_size,
+            &config,
+            Rc::new(RefCell::new(text_renderer)),
+            "",
+        );
+        button_manager.load_builtin_textures(device, queue)?;
+        Ok(button_manager)
+    }
+
+    /// Load every texture in `BUILTIN_TEXTURES`, trying all of them before
+    /// reporting the failures
+    fn load_builtin_textures(&mut self, device: &Device, queue: &Queue) -> Result<(), String> {
+        let errors: Vec<String> = BUILTIN_TEXTURES
+            .iter()
+            .filter_map(|(texture_id, path)| {
+                self.icon_renderer
+                    .load_texture(device, queue, path, texture_id)
+                    .err()
+                    .map(|e| {
+                        format!(
+                            "Failed to load texture '{}' from {}: {}",
+                            texture_id, path, e
+                        )
+                    })
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Ids of textures the buttons draw with that aren't loaded: the built-in
+    /// ones, button icons and background textures. Empty when nothing is missing.
+    pub fn missing_textures(&self) -> Vec<String> {
+        let builtin = BUILTIN_TEXTURES.iter().map(|(texture_id, _)| *texture_id);
+        let used = self.buttons.values().flat_map(|button| {
+            [
+                button.icon_id.as_deref(),
+                button.style.background_texture_id.as_deref(),
+                button.style.hover_texture_id.as_deref(),
+                button.style.pressed_texture_id.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+        });
+
+        let mut missing: Vec<String> = Vec::new();
+        for texture_id in builtin.chain(used) {
+            if !self.icon_renderer.has_texture(texture_id)
+                && !missing.iter().any(|id| id == texture_id)
+            {
+                missing.push(texture_id.to_string());
+            }
         }
+        missing
+    }
+
+    fn without_textures(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: SharedTextRenderer,
+        text_namespace: &str,
+    ) -> Self {
+        let rectangle_renderer = RectangleRenderer::new_with_config(device, surface_format, config);
+        let icon_renderer = IconRenderer::new_with_config(device, surface_format, config);
 
         Self {
             buttons: HashMap::new(),
             button_order: Vec::new(), // Initialize the order tracking
             text_renderer,
+            text_namespace: text_namespace.to_string(),
             rectangle_renderer,
             icon_renderer,
             window_size,
+            cursor_space: None,
             mouse_position: (0.0, 0.0),
             mouse_pressed: false,
             just_clicked: None,
             container_rect: None,
+            progress_bars: Vec::new(),
             last_mouse_position: (0.0, 0.0),
             last_mouse_pressed: false,
+            entrance: None,
+            draggables: Vec::new(),
+            text_inputs: Vec::new(),
+            clip: None,
+            click_buttons: ClickButtons::default(),
+            input_consumed: false,
+            press_consumed: false,
+            tooltip_box: None,
+            tooltip_button: None,
+            transition_log: None,
+            transition_cause: TransitionCause::Refresh,
+            on_hover_enter: None,
+            on_click: None,
+        }
+    }
+
+    /// Id of the text buffer `id` inside this manager's namespace, for text
+    /// created next to the buttons, e.g. a title
+    pub fn text_id(&self, id: &str) -> String {
+        TextRenderer::namespaced(&self.text_namespace, id)
+    }
+
+    /// `(width, height)` `add_button` would give `button` in the current window,
+    /// so neighbouring elements can be laid out before it is added
+    pub fn preview_size(&self, button: &Button<A>) -> (f32, f32) {
+        self.layout_size(button).0
+    }
+
+    /// Last measured `(width, height)` of a button's label text
+    pub fn measured_size(&self, id: &str) -> Option<(f32, f32)> {
+        self.buttons.get(id).map(|button| button.measured_text)
+    }
+
+    /// Change a button's label, re-measuring it and updating the layout
+    pub fn set_text(&mut self, id: &str, text: &str) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        if button.text == text {
+            return Ok(());
         }
+        button.text = text.to_string();
+        self.text_renderer
+            .borrow_mut()
+            .set_text(&button.text_id, text)?;
+        self.update_button_positions();
+        Ok(())
+    }
+
+    /// Button size for the current window and the measured label size
+    fn layout_size(&self, button: &Button<A>) -> ((f32, f32), (f32, f32)) {
+        let style = &button.style;
+        let (_min_x, text_width, text_height) = self
+            .text_renderer
+            .borrow()
+            .measure_text(&button.text, &style.text_style);
+        let (text_width, text_height) = button.label_size(text_width, text_height);
+        let button_size = button.fit_size(text_width, text_height, self.window_size);
+        (button_size, (text_width, text_height))
     }
 
-    pub fn add_button(&mut self, button: Button) {
+    pub fn add_button(&mut self, mut button: Button<A>) {
+        button.text_id = self.text_id(&button.text_id);
+        button.level_text_id = button.level_text_id.map(|id| self.text_id(&id));
+        button.tooltip_text_id = button.tooltip_text_id.map(|id| self.text_id(&id));
+
+        // Out-of-range proportions would put the button off screen
+        let spacing = button.style.spacing.clamped();
+        if spacing != button.style.spacing {
+            println!(
+                "Warning: button '{}' has out-of-range spacing {:?}, using {:?}",
+                button.id, button.style.spacing, spacing
+            );
+            button.style.spacing = spacing;
+        }
+
+        // Start in the state the first refresh would pick with the mouse elsewhere,
+        // so the label is drawn correctly even if input never arrives
+        button.state = button.resting_state();
+        let (text_color, text_weight) = button.label_appearance();
+
         let text_id = button.text_id.clone();
         let text = button.text.clone();
         let style = button.style.clone();
@@ -150,70 +765,48 @@ impl ButtonManager {
         let level_text_id = button.level_text_id.clone();
         let tooltip_text_id = button.tooltip_text_id.clone();
 
-        let horizontal_padding = style.padding.0;
-        let vertical_padding = style.padding.1;
-        let window_width = self.window_size.width as f32;
-
         // Measure the actual text size for positioning, allowing wrapping
-        let (_min_x, text_width, text_height) = self.text_renderer.measure_text(
-            &text,
-            &TextStyle {
-                ..style.text_style.clone()
-            },
-        );
-
-        let (button_width, button_height) = match style.spacing {
-            ButtonSpacing::Wrap => {
-                let width = text_width + 2.0 * vertical_padding;
-                let height = text_height + 2.0 * vertical_padding;
-                (width, height)
-            }
-            ButtonSpacing::Hbar(prop) => {
-                let width = window_width * prop;
-                let height = text_height + 2.0 * vertical_padding;
-                (width, height)
-            }
-            ButtonSpacing::Tall(height_proportion) => {
-                // Tall buttons use a proportion of the window height
-                // Use the position width if it's set, otherwise use text width
-                let width = if button.position.width > 0.0 {
-                    button.position.width
-                } else {
-                    text_width + 2.0 * vertical_padding
-                };
-                let height = self.window_size.height as f32 * height_proportion;
-                (width, height)
-            }
-        };
+        let ((button_width, button_height), (text_width, text_height)) = self.layout_size(&button);
+        let (horizontal_padding, vertical_padding) =
+            style.padding.resolve(button_width, button_height);
 
         // Update the button's position with the calculated dimensions
         let mut button_with_size = button;
         button_with_size.position.width = button_width;
         button_with_size.position.height = button_height;
+        button_with_size.measured_text = (text_width, text_height);
 
         // Calculate the actual position using the same transformation as hit detection
         let (actual_x, actual_y) = button_with_size.position.calculate_actual_position();
 
+        // Text wider than the button wraps inside the padding, so center on what fits
+        let max_text_width = button_width - 2.0 * horizontal_padding;
+        let text_width = text_width.min(max_text_width);
+
         // Calculate text position based on alignment using actual coordinates
-        let text_x = match style.text_align {
+        let text_x = match style.text_align.resolve(style.text_style.direction) {
             TextAlign::Left => actual_x + horizontal_padding,
             TextAlign::Right => actual_x + button_width - horizontal_padding - text_width,
             TextAlign::Center => actual_x + (button_width - text_width) / 2.0,
         };
-        let text_y = actual_y + vertical_padding;
+        let text_y =
+            style
+                .vertical_align
+                .text_y(actual_y, button_height, text_height, vertical_padding);
 
         let text_position = TextPosition {
-            x: text_x,
+            x: label_buffer_x(text_x, text_width, max_text_width, &style.text_style),
             y: text_y,
-            max_width: Some(button_width - 2.0 * horizontal_padding),
+            max_width: Some(max_text_width),
             max_height: Some(button_height - 2.0 * vertical_padding),
         };
 
-        self.text_renderer.create_text_buffer(
+        self.text_renderer.borrow_mut().create_text_buffer(
             &text_id,
             &text,
             Some(TextStyle {
-                color: style.background_color.darken(0.35), // Use proper color, not transparent
+                color: text_color,
+                weight: text_weight,
                 ..style.text_style.clone()
             }),
             Some(text_position),
@@ -226,14 +819,17 @@ impl ButtonManager {
             level_style.font_size = style.text_style.font_size * 0.7; // 70% of main text size
             level_style.line_height = style.text_style.line_height * 0.7;
             level_style.style = Style::Italic;
-            level_style.color = style.background_color.darken(0.35); // Use same color as main text, not transparent
+            level_style.color = text_color; // Use same color as main text
+            level_style.weight = text_weight;
 
             // Position level text higher up, below the main text but above the icon
             let level_text = "Level 1";
-            let (_min_x, level_text_width, level_text_height) =
-                self.text_renderer.measure_text(level_text, &level_style);
+            let (_min_x, level_text_width, level_text_height) = self
+                .text_renderer
+                .borrow()
+                .measure_text(level_text, &level_style);
 
-            let level_text_x = match style.text_align {
+            let level_text_x = match style.text_align.resolve(style.text_style.direction) {
                 TextAlign::Left => actual_x + horizontal_padding,
                 TextAlign::Right => actual_x + button_width - horizontal_padding - level_text_width,
                 TextAlign::Center => actual_x + (button_width - level_text_width) / 2.0,
@@ -247,7 +843,7 @@ impl ButtonManager {
                 max_height: Some(level_text_height),
             };
 
-            self.text_renderer.create_text_buffer(
+            self.text_renderer.borrow_mut().create_text_buffer(
                 &level_id,
                 level_text,
                 Some(level_style),
@@ -262,13 +858,14 @@ impl ButtonManager {
             tooltip_style.font_size = style.text_style.font_size * 0.55; // 55% of main text size
             tooltip_style.line_height = tooltip_style.font_size * 1.05;
             tooltip_style.style = Style::Normal;
-            tooltip_style.color = style.background_color.darken(0.35); // Use same color as main text, not transparent
+            tooltip_style.color = text_color; // Use same color as main text
+            tooltip_style.weight = text_weight;
 
             // Position tooltip text below the level text
             let tooltip_text = "This is a place to describe an upgrade, and what effects it has on the game in a little more detail.";
             let extra_tooltip_padding = 10.0;
             let tooltip_horizontal_padding = horizontal_padding + extra_tooltip_padding;
-            let tooltip_text_x = match style.text_align {
+            let tooltip_text_x = match style.text_align.resolve(style.text_style.direction) {
                 TextAlign::Left => actual_x + tooltip_horizontal_padding,
                 TextAlign::Right => actual_x + button_width - tooltip_horizontal_padding,
                 TextAlign::Center => actual_x + tooltip_horizontal_padding, // Start from left padding, let text wrap
@@ -282,7 +879,7 @@ impl ButtonManager {
                 max_height: Some(button_height * 0.28), // Allow for more lines
             };
 
-            self.text_renderer.create_text_buffer(
+            self.text_renderer.borrow_mut().create_text_buffer(
                 &tooltip_id,
                 tooltip_text,
                 Some(tooltip_style),
@@ -290,39 +887,49 @@ impl ButtonManager {
             );
         }
 
-        // Track button order
-        if !self.button_order.contains(&button_id) {
+        // Track button order; a replaced button keeps its place
+        if !self.buttons.contains_key(&button_id) {
             self.button_order.push(button_id.clone());
         }
 
+        Self::sync_text_opacity(&mut self.text_renderer.borrow_mut(), &button_with_size);
+        Self::sync_text_visibility(&mut self.text_renderer.borrow_mut(), &button_with_size);
         self.buttons
             .insert(button_with_size.id.clone(), button_with_size);
     }
 
+    /// Add several buttons in order, laying everything out once at the end
+    /// instead of after each one
+    pub fn add_buttons(&mut self, buttons: impl IntoIterator<Item = Button<A>>) {
+        let buttons = buttons.into_iter();
+        let (additional, _) = buttons.size_hint();
+        self.buttons.reserve(additional);
+        self.button_order.reserve(additional);
+        for button in buttons {
+            self.add_button(button);
+        }
+        self.update_button_positions();
+    }
+
     pub fn update_icon_positions(&mut self) {
         // Clear existing icons
         self.icon_renderer.clear_icons();
 
-        // Only add icons to buttons with ButtonSpacing::Tall (upgrade menu buttons)
+        // Icons go on Tall buttons (upgrade menu buttons) and icon-only buttons
         for button_id in &self.button_order {
             if let Some(button) = self.buttons.get(button_id) {
                 if button.visible {
+                    // Textured background goes first so the button's icon draws over it
+                    if let Some(icon) = self.background_icon(button) {
+                        self.icon_renderer.add_icon(icon);
+                    }
+
                     // Only add icons to Tall buttons (upgrade menu buttons)
                     if let ButtonSpacing::Tall(_) = button.style.spacing {
-                        let (actual_x, actual_y) = button.position.calculate_actual_position();
-
                         // Calculate scale for hover effect on upgrade buttons
-                        let scale = match button.state {
-                            ButtonState::Hover => 1.1,    // 10% bigger on hover
-                            ButtonState::Pressed => 1.05, // 5% bigger when pressed
-                            _ => 1.0,                     // Normal size
-                        };
-
-                        // Calculate scaled button dimensions
-                        let scaled_width = button.position.width * scale;
-                        let scaled_height = button.position.height * scale;
-                        let scaled_x = actual_x - (scaled_width - button.position.width) / 2.0;
-                        let scaled_y = actual_y - (scaled_height - button.position.height) / 2.0;
+                        let scale = button.visual_scale();
+                        let (scaled_x, scaled_y, scaled_width, scaled_height) =
+                            button.visual_bounds();
 
                         // Calculate icon size and position with scaling
                         let margin = 16.0 * scale; // Scale margin too
@@ -336,251 +943,980 @@ impl ButtonManager {
                         let icon_x = scaled_x + (scaled_width - icon_size) / 2.0;
                         let icon_y = scaled_y + scaled_height * 0.5;
 
-                        let icon = Icon::new(
-                            icon_x,
-                            icon_y,
-                            icon_size,
-                            icon_size,
-                            "blank_icon".to_string(),
-                        );
+                        let texture_id = button.icon_id.as_deref().unwrap_or("blank_icon");
+                        let icon =
+                            Icon::new(icon_x, icon_y, icon_size, icon_size, texture_id.to_string())
+                                .with_tint([1.0, 1.0, 1.0, button.alpha()]);
                         self.icon_renderer.add_icon(icon);
+                    } else if button.is_icon_only() {
+                        // Square icon centered inside the vertical padding
+                        let scale = button.visual_scale();
+                        let (x, y, width, height) = button.visual_bounds();
+                        let icon_size =
+                            (width.min(height) - 2.0 * button.padding().1 * scale).max(0.0);
+
+                        if let Some(texture_id) = &button.icon_id {
+                            let icon = Icon::new(
+                                x + (width - icon_size) / 2.0,
+                                y + (height - icon_size) / 2.0,
+                                icon_size,
+                                icon_size,
+                                texture_id.clone(),
+                            )
+                            .with_tint([1.0, 1.0, 1.0, button.alpha()]);
+                            self.icon_renderer.add_icon(icon);
+                        }
                     }
                 }
             }
         }
     }
 
-    pub fn get_button_mut(&mut self, id: &str) -> Option<&mut Button> {
-        self.buttons.get_mut(id)
+    /// The background image for a textured button, if its texture is loaded
+    fn background_icon(&self, button: &Button<A>) -> Option<Icon> {
+        let state = if button.enabled {
+            &button.state
+        } else {
+            &ButtonState::Disabled
+        };
+        let (texture_id, tint) = button.style.background_texture(state)?;
+        if !self.icon_renderer.has_texture(texture_id) {
+            return None;
+        }
+
+        let (x, y, width, height) = button.visual_bounds();
+        let [r, g, b, a] = tint;
+        Some(
+            Icon::new(x, y, width, height, texture_id.to_string())
+                .with_tint([r, g, b, a * button.alpha()])
+                .with_mask(IconMask::RoundedRect(button.visual_corner_radius()))
+                // Backgrounds always cover the whole button
+                .with_fit(IconFit::Stretch),
+        )
     }
 
-    pub fn is_button_clicked(&mut self, id: &str) -> bool {
-        if let Some(clicked_id) = &self.just_clicked {
-            if clicked_id == id {
-                self.just_clicked = None; // Reset after checking
-                if let Some(button) = self.buttons.get(id) {
-                    let clean_text = button
-                        .text
-                        .replace(|c: char| c.is_whitespace(), " ")
-                        .split_whitespace()
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    println!("Button '{}' was clicked!", clean_text.trim());
-                } else {
-                    let clean_id = id
-                        .replace(|c: char| c.is_whitespace(), " ")
-                        .split_whitespace()
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    println!("Button '{}' was clicked!", clean_id.trim());
-                }
-                return true;
-            }
-        }
-        false
+    /// Buttons in render order: the order they were added, apart from any
+    /// moved with `bring_to_front` or `send_to_back`
+    pub fn iter_ordered(&self) -> impl Iterator<Item = (&str, &Button<A>)> + '_ {
+        self.button_order
+            .iter()
+            .filter_map(|id| self.buttons.get(id).map(|button| (id.as_str(), button)))
     }
 
-    pub fn handle_input(&mut self, event: &WindowEvent) {
-        match event {
-            WindowEvent::MouseInput {
-                state: ElementState::Pressed,
-                button: MouseButton::Left,
-                ..
-            } => {
-                self.mouse_pressed = true;
-                self.update_button_states();
-            }
-            WindowEvent::MouseInput {
-                state: ElementState::Released,
-                button: MouseButton::Left,
-                ..
-            } => {
-                // Check for button clicks when mouse is released
-                for button in self.buttons.values() {
-                    if button.visible && button.enabled && button.state == ButtonState::Pressed {
-                        // Button was clicked
-                        self.just_clicked = Some(button.id.clone());
-                        break;
-                    }
-                }
+    /// Draw button `id` after all others, e.g. so a hovered button that
+    /// grows isn't covered by its neighbors
+    pub fn bring_to_front(&mut self, id: &str) -> Result<(), String> {
+        self.move_in_order(id, true)
+    }
 
-                self.mouse_pressed = false;
-                self.update_button_states();
-            }
-            WindowEvent::CursorMoved { position, .. } => {
-                self.mouse_position = (position.x as f32, position.y as f32);
-                self.update_button_states();
-            }
-            WindowEvent::Resized(size) => {
-                self.window_size = *size;
-                self.update_button_positions();
-            }
-            _ => {}
-        }
+    /// Draw button `id` before all others
+    pub fn send_to_back(&mut self, id: &str) -> Result<(), String> {
+        self.move_in_order(id, false)
     }
 
-    pub fn update_button_states(&mut self) {
-        // Early exit if mouse state hasn't changed
-        if self.mouse_position == self.last_mouse_position
-            && self.mouse_pressed == self.last_mouse_pressed
-        {
-            return;
+    fn move_in_order(&mut self, id: &str, to_front: bool) -> Result<(), String> {
+        let index = self
+            .button_order
+            .iter()
+            .position(|button_id| button_id == id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        let target = if to_front {
+            self.button_order.len() - 1
+        } else {
+            0
+        };
+        if index != target {
+            let button_id = self.button_order.remove(index);
+            self.button_order.insert(target, button_id);
+            // Icons are queued in render order too
+            self.update_icon_positions();
         }
+        Ok(())
+    }
 
-        // Update cached mouse state
-        self.last_mouse_position = self.mouse_position;
-        self.last_mouse_pressed = self.mouse_pressed;
+    /// Whether a button with `id` has been added
+    pub fn contains(&self, id: &str) -> bool {
+        self.buttons.contains_key(id)
+    }
 
-        for button in self.buttons.values_mut() {
-            if !button.visible || !button.enabled {
-                if button.state != ButtonState::Disabled {
-                    button.state = ButtonState::Disabled;
-                    // Hide text if not visible
-                    let _ = self.text_renderer.update_style(
-                        &button.text_id,
-                        TextStyle {
-                            color: Color::rgba(0, 0, 0, 0),
-                            ..button.style.text_style.clone()
-                        },
-                    );
-                    // Hide level text if not visible
-                    if let Some(level_id) = &button.level_text_id {
-                        let _ = self.text_renderer.update_style(
-                            level_id,
-                            TextStyle {
-                                color: Color::rgba(0, 0, 0, 0),
-                                ..button.style.text_style.clone()
-                            },
-                        );
-                    }
-                    // Hide tooltip text if not visible
-                    if let Some(tooltip_id) = &button.tooltip_text_id {
-                        let _ = self.text_renderer.update_style(
-                            tooltip_id,
-                            TextStyle {
-                                color: Color::rgba(0, 0, 0, 0),
-                                ..button.style.text_style.clone()
-                            },
-                        );
-                    }
-                }
-                continue;
-            }
+    /// Id of the visible, enabled button under the cursor, if any
+    pub fn hovered_button(&self) -> Option<&str> {
+        self.iter_ordered()
+            .find(|(_, button)| {
+                button.visible
+                    && button.enabled
+                    && matches!(button.state, ButtonState::Hover | ButtonState::Pressed)
+            })
+            .map(|(id, _)| id)
+    }
 
-            let is_hovered = button.contains_point(self.mouse_position.0, self.mouse_position.1);
+    /// Cursor to show for the current mouse position: a pointer over an
+    /// enabled button, "not allowed" over a disabled one, a hand over drag
+    /// handles, `None` elsewhere
+    pub fn desired_cursor(&self) -> Option<CursorIcon> {
+        if self.is_dragging() {
+            return Some(CursorIcon::Grabbing);
+        }
+        let (mouse_x, mouse_y) = self.mouse_position;
+        if self
+            .text_inputs
+            .iter()
+            .any(|input| input.visible && input.contains_point(mouse_x, mouse_y))
+        {
+            return Some(CursorIcon::Text);
+        }
+        match self
+            .buttons
+            .values()
+            .find(|button| button.is_over(mouse_x, mouse_y))
+        {
+            Some(button) if button.enabled => Some(CursorIcon::Pointer),
+            Some(_) => Some(CursorIcon::NotAllowed),
+            None => self
+                .draggables
+                .iter()
+                .any(|draggable| draggable.contains_point(mouse_x, mouse_y))
+                .then_some(CursorIcon::Grab),
+        }
+    }
 
-            // Determine new state
-            let new_state = if self.mouse_pressed && is_hovered {
-                ButtonState::Pressed
-            } else if is_hovered {
-                ButtonState::Hover
-            } else {
-                ButtonState::Normal
-            };
+    /// On-screen `(x, y, width, height)` of a visible button as last drawn,
+    /// including hover scaling
+    pub fn button_rect(&self, id: &str) -> Option<(f32, f32, f32, f32)> {
+        self.buttons
+            .get(id)
+            .filter(|button| button.visible)
+            .map(|button| button.visual_bounds())
+    }
 
-            // Only update if state actually changed
-            if button.state == new_state {
+    /// Run `hook` once each time the cursor starts hovering an enabled button,
+    /// e.g. to play a sound
+    pub fn set_on_hover_enter(&mut self, hook: impl FnMut(&str) + 'static) {
+        self.on_hover_enter = Some(Box::new(hook));
+    }
+
+    /// Run `hook` once for every click, including ones made with `click`
+    pub fn set_on_click(&mut self, hook: impl FnMut(&str) + 'static) {
+        self.on_click = Some(Box::new(hook));
+    }
+
+    pub fn get_button_mut(&mut self, id: &str) -> Option<&mut Button<A>> {
+        self.buttons.get_mut(id)
+    }
+
+    /// Remove a button together with its label, level and tooltip text, and
+    /// return it. Drag handles keep moving whichever of their buttons remain.
+    pub fn remove_button(&mut self, id: &str) -> Result<Button<A>, String> {
+        let button = self
+            .buttons
+            .remove(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        self.button_order.retain(|other| other != id);
+        if self.just_clicked.as_deref() == Some(id) {
+            self.just_clicked = None;
+        }
+
+        let text_ids = std::iter::once(&button.text_id)
+            .chain(button.level_text_id.as_ref())
+            .chain(button.tooltip_text_id.as_ref());
+        for text_id in text_ids {
+            if let Err(e) = self.text_renderer.borrow_mut().remove_text_buffer(text_id) {
+                println!("Failed to remove button text: {}", e);
+            }
+        }
+
+        // Its floating tooltip may be up
+        self.sync_floating_tooltip();
+        self.update_icon_positions();
+        Ok(button)
+    }
+
+    /// Start tracking a drag handle; one with the same id is replaced
+    pub fn add_draggable(&mut self, draggable: Draggable) {
+        self.remove_draggable(&draggable.id);
+        self.draggables.push(draggable);
+    }
+
+    pub fn remove_draggable(&mut self, id: &str) {
+        self.draggables.retain(|draggable| draggable.id != id);
+    }
+
+    pub fn get_draggable_mut(&mut self, id: &str) -> Option<&mut Draggable> {
+        self.draggables
+            .iter_mut()
+            .find(|draggable| draggable.id == id)
+    }
+
+    /// Whether a drag handle is currently being dragged
+    pub fn is_dragging(&self) -> bool {
+        self.draggables.iter().any(Draggable::is_dragging)
+    }
+
+    /// Move a button by `(dx, dy)` pixels, laying out only that button
+    pub fn translate_button(&mut self, id: &str, dx: f32, dy: f32) -> Result<(), String> {
+        let mut position = self
+            .buttons
+            .get(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?
+            .position
+            .clone();
+        position.x += dx;
+        position.y += dy;
+        self.set_position(id, position)
+    }
+
+    /// Move the members of every active drag to follow the mouse
+    fn update_drags(&mut self) {
+        let (x, y) = self.mouse_position;
+        let mut moves = Vec::new();
+        for draggable in &mut self.draggables {
+            if let Some((dx, dy)) = draggable.drag_to(x, y) {
+                moves.push((draggable.buttons.clone(), draggable.moves_container, dx, dy));
+            }
+        }
+
+        for (buttons, moves_container, dx, dy) in moves {
+            for id in &buttons {
+                if let Err(e) = self.translate_button(id, dx, dy) {
+                    println!("Failed to drag button: {}", e);
+                }
+            }
+            if moves_container {
+                if let Some(container) = &mut self.container_rect {
+                    container.x += dx;
+                    container.y += dy;
+                }
+            }
+        }
+    }
+
+    /// Add a text entry box, replacing any with the same id
+    pub fn add_text_input(&mut self, mut text_input: TextInput) {
+        text_input.text_id = self.text_id(&text_input.text_id);
+        self.text_renderer.borrow_mut().create_text_buffer(
+            &text_input.text_id,
+            &text_input.value,
+            Some(text_input.style.clone()),
+            None,
+        );
+        let _ = self
+            .text_renderer
+            .borrow_mut()
+            .set_visible(&text_input.text_id, text_input.visible);
+
+        self.text_inputs.retain(|input| input.id != text_input.id);
+        self.text_inputs.push(text_input);
+        if let Some(text_input) = self.text_inputs.last_mut() {
+            Self::layout_text_input(&mut self.text_renderer.borrow_mut(), text_input);
+        }
+    }
+
+    pub fn get_text_input(&self, id: &str) -> Option<&TextInput> {
+        self.text_inputs.iter().find(|input| input.id == id)
+    }
+
+    pub fn get_text_input_mut(&mut self, id: &str) -> Option<&mut TextInput> {
+        self.text_inputs.iter_mut().find(|input| input.id == id)
+    }
+
+    /// Current value of a text entry box
+    pub fn text_input_value(&self, id: &str) -> Option<&str> {
+        self.get_text_input(id).map(TextInput::value)
+    }
+
+    /// Replace the value of a text entry box and show it
+    pub fn set_text_input_value(&mut self, id: &str, value: &str) -> Result<(), String> {
+        let text_input = self
+            .text_inputs
+            .iter_mut()
+            .find(|input| input.id == id)
+            .ok_or_else(|| format!("Text input '{}' not found", id))?;
+        text_input.set_value(value);
+        Self::layout_text_input(&mut self.text_renderer.borrow_mut(), text_input);
+        Ok(())
+    }
+
+    /// Show or hide a text entry box with its value; hiding drops focus
+    pub fn set_text_input_visible(&mut self, id: &str, visible: bool) -> Result<(), String> {
+        let text_input = self
+            .text_inputs
+            .iter_mut()
+            .find(|input| input.id == id)
+            .ok_or_else(|| format!("Text input '{}' not found", id))?;
+        text_input.visible = visible;
+        if !visible && text_input.focused {
+            text_input.set_focused(false);
+        }
+        self.text_renderer
+            .borrow_mut()
+            .set_visible(&text_input.text_id, visible)
+    }
+
+    /// Show the value inside the box and measure where the caret goes
+    fn layout_text_input(text_renderer: &mut TextRenderer, text_input: &mut TextInput) {
+        let (_min_x, caret_offset, _height) =
+            text_renderer.measure_text(text_input.text_before_caret(), &text_input.style);
+        text_input.caret_offset = caret_offset;
+
+        let (text_x, text_y) = text_input.text_origin();
+        let _ = text_renderer.set_text(&text_input.text_id, &text_input.value);
+        let _ = text_renderer.update_position(
+            &text_input.text_id,
+            TextPosition {
+                x: text_x,
+                y: text_y,
+                max_width: Some(text_input.position.width - 2.0 * text_input.padding),
+                max_height: Some(text_input.style.line_height),
+            },
+        );
+    }
+
+    /// Add a progress bar, creating its label text centered over the bar
+    pub fn add_progress_bar(&mut self, mut progress_bar: ProgressBar) {
+        progress_bar.label_id = self.text_id(&progress_bar.label_id);
+        if let Some(label) = &progress_bar.label {
+            let (_min_x, text_width, text_height) = self
+                .text_renderer
+                .borrow()
+                .measure_text(label, &progress_bar.label_style);
+            let label_position = progress_bar.label_position(text_width, text_height);
+            self.text_renderer.borrow_mut().create_text_buffer(
+                &progress_bar.label_id,
+                label,
+                Some(progress_bar.label_style.clone()),
+                Some(label_position),
+            );
+        }
+
+        self.progress_bars.retain(|bar| bar.id != progress_bar.id);
+        self.progress_bars.push(progress_bar);
+    }
+
+    pub fn get_progress_bar_mut(&mut self, id: &str) -> Option<&mut ProgressBar> {
+        self.progress_bars.iter_mut().find(|bar| bar.id == id)
+    }
+
+    /// Move a progress bar, e.g. on resize, and its label with it
+    pub fn set_progress_bar_position(
+        &mut self,
+        id: &str,
+        position: ButtonPosition,
+    ) -> Result<(), String> {
+        let progress_bar = self
+            .progress_bars
+            .iter_mut()
+            .find(|bar| bar.id == id)
+            .ok_or_else(|| format!("Progress bar '{}' not found", id))?;
+        progress_bar.position = position;
+        Self::layout_progress_label(&mut self.text_renderer.borrow_mut(), progress_bar);
+        Ok(())
+    }
+
+    /// Center a progress bar's label over the bar at its current position
+    fn layout_progress_label(text_renderer: &mut TextRenderer, progress_bar: &ProgressBar) {
+        if let Some(label) = &progress_bar.label {
+            let (_min_x, text_width, text_height) =
+                text_renderer.measure_text(label, &progress_bar.label_style);
+            let _ = text_renderer.update_position(
+                &progress_bar.label_id,
+                progress_bar.label_position(text_width, text_height),
+            );
+        }
+    }
+
+    /// Set a progress bar's filled fraction, clamped to 0.0..=1.0
+    pub fn set_progress(&mut self, id: &str, value: f32) -> Result<(), String> {
+        let progress_bar = self
+            .get_progress_bar_mut(id)
+            .ok_or_else(|| format!("Progress bar '{}' not found", id))?;
+        progress_bar.set_value(value);
+        Ok(())
+    }
+
+    /// Show or hide a progress bar together with its label
+    pub fn set_progress_bar_visible(&mut self, id: &str, visible: bool) -> Result<(), String> {
+        let progress_bar = self
+            .progress_bars
+            .iter_mut()
+            .find(|bar| bar.id == id)
+            .ok_or_else(|| format!("Progress bar '{}' not found", id))?;
+        progress_bar.visible = visible;
+        if progress_bar.label.is_some() {
+            self.text_renderer
+                .borrow_mut()
+                .set_visible(&progress_bar.label_id, visible)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_button_clicked(&mut self, id: &str) -> bool {
+        if let Some(clicked_id) = &self.just_clicked {
+            if clicked_id == id {
+                self.just_clicked = None; // Reset after checking
+                self.log_click(id);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// If the last clicked button carries an action, consume the click and
+    /// return it. Clicks on buttons without one are left for `is_button_clicked`.
+    pub fn take_clicked_action(&mut self) -> Option<A> {
+        let clicked_id = self.just_clicked.clone()?;
+        let action = self.buttons.get(&clicked_id)?.action()?;
+        self.just_clicked = None;
+        self.log_click(&clicked_id);
+        Some(action)
+    }
+
+    /// Click a button as if the mouse was released over it, e.g. for keyboard
+    /// activation or tutorials. Hidden and disabled buttons can't be clicked.
+    pub fn click(&mut self, id: &str) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        if !button.visible || !button.enabled {
+            return Err(format!("Button '{}' is not clickable", id));
+        }
+        self.just_clicked = Some(id.to_string());
+        if let Some(on_click) = &mut self.on_click {
+            on_click(id);
+        }
+        Ok(())
+    }
+
+    fn log_click(&self, id: &str) {
+        let label = self
+            .buttons
+            .get(id)
+            .map(|button| button.text.as_str())
+            .unwrap_or(id);
+        let clean_label = label
+            .replace(|c: char| c.is_whitespace(), " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("Button '{}' was clicked!", clean_label.trim());
+    }
+
+    /// Report cursor positions as relative to a window of `size` physical
+    /// pixels, e.g. the window's inner size while the surface has another size
+    pub fn set_cursor_space(&mut self, size: PhysicalSize<u32>) {
+        self.cursor_space = Some(size);
+    }
+
+    /// Map a cursor position in window pixels to UI coordinates, stretching
+    /// `cursor_space` over `window_size`
+    pub fn cursor_to_ui(&self, x: f64, y: f64) -> (f32, f32) {
+        match self.cursor_space {
+            Some(space) if space.width > 0 && space.height > 0 => (
+                (x * self.window_size.width as f64 / space.width as f64) as f32,
+                (y * self.window_size.height as f64 / space.height as f64) as f32,
+            ),
+            _ => (x as f32, y as f32),
+        }
+    }
+
+    /// Whether the UI used the last event passed to `handle_input`, so the
+    /// host must not also apply it to the game. Presses over a button, text
+    /// input, drag handle or the container are consumed, as is the release
+    /// that ends them, and so are key presses while a text input is focused.
+    /// Cursor movement and resizes are never consumed, every layer needs them.
+    pub fn input_consumed(&self) -> bool {
+        self.input_consumed
+    }
+
+    /// Whether `(x, y)` is over anything this manager draws and reacts to
+    fn is_over_ui(&self, x: f32, y: f32) -> bool {
+        self.buttons.values().any(|button| button.is_over(x, y))
+            || self
+                .text_inputs
+                .iter()
+                .any(|input| input.visible && input.contains_point(x, y))
+            || self
+                .draggables
+                .iter()
+                .any(|draggable| draggable.contains_point(x, y))
+            || self.container_rect.as_ref().is_some_and(|container| {
+                x >= container.x
+                    && x <= container.x + container.width
+                    && y >= container.y
+                    && y <= container.y + container.height
+            })
+    }
+
+    /// Update hover/press state from `event` and return the actions of any
+    /// buttons it clicked. Clicks on buttons without an action are left for
+    /// `is_button_clicked`; see `input_consumed` for whether the game should
+    /// still see the event.
+    pub fn handle_input(&mut self, event: &WindowEvent) -> Vec<A> {
+        let mut actions = Vec::new();
+        self.input_consumed = false;
+        match event {
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } if self.click_buttons.accepts(*button) => {
+                self.mouse_pressed = true;
+                self.transition_cause = TransitionCause::MousePressed;
+                self.update_button_states();
+
+                let (x, y) = self.mouse_position;
+                self.press_consumed = self.is_over_ui(x, y);
+                self.input_consumed = self.press_consumed;
+
+                // A press focuses the text input under it and unfocuses the rest
+                for text_input in &mut self.text_inputs {
+                    let focused = text_input.visible && text_input.contains_point(x, y);
+                    if focused != text_input.focused {
+                        text_input.set_focused(focused);
+                    }
+                }
+
+                // Presses that miss every button may grab a drag handle
+                if self.hovered_button().is_none()
+                    && !self.text_inputs.iter().any(|input| input.focused)
+                {
+                    let (x, y) = self.mouse_position;
+                    if let Some(draggable) = self
+                        .draggables
+                        .iter_mut()
+                        .find(|draggable| draggable.contains_point(x, y))
+                    {
+                        draggable.start_drag(x, y);
+                    }
+                }
+
+                // Repeat buttons fire as soon as they are pressed
+                let pressed: Vec<String> = self
+                    .buttons
+                    .values_mut()
+                    .filter(|button| {
+                        button.repeat.is_some() && button.state == ButtonState::Pressed
+                    })
+                    .map(|button| {
+                        button.held_for = 0.0;
+                        button.id.clone()
+                    })
+                    .collect();
+                actions.extend(pressed.iter().filter_map(|id| self.fire_repeat(id)));
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } if self.click_buttons.accepts(*button) => {
+                // Check for button clicks when mouse is released
+                for button in self.buttons.values() {
+                    // Repeat buttons already fired while held
+                    if button.visible
+                        && button.enabled
+                        && button.state == ButtonState::Pressed
+                        && button.repeat.is_none()
+                    {
+                        // Button was clicked
+                        self.just_clicked = Some(button.id.clone());
+                        if let Some(on_click) = &mut self.on_click {
+                            on_click(&button.id);
+                        }
+                        break;
+                    }
+                }
+                if let Some(action) = self.take_clicked_action() {
+                    actions.push(action);
+                }
+
+                self.mouse_pressed = false;
+                self.input_consumed = std::mem::take(&mut self.press_consumed);
+                for draggable in &mut self.draggables {
+                    draggable.stop_drag();
+                }
+                self.transition_cause = TransitionCause::MouseReleased;
+                self.update_button_states();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = self.cursor_to_ui(position.x, position.y);
+                self.update_drags();
+                self.transition_cause = TransitionCause::MouseMoved;
+                self.update_button_states();
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let Some(text_input) = self.text_inputs.iter_mut().find(|input| input.focused) {
+                    self.input_consumed = true;
+                    if text_input.handle_key(event) {
+                        Self::layout_text_input(&mut self.text_renderer.borrow_mut(), text_input);
+                    }
+                }
+            }
+            WindowEvent::Resized(size) => {
+                self.window_size = *size;
+                self.update_button_positions();
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    /// Mark a button selected, so it keeps its selected look after the cursor
+    /// leaves, or clear the mark
+    pub fn set_selected(&mut self, id: &str, selected: bool) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+
+        if button.selected == selected {
+            return Ok(());
+        }
+        button.selected = selected;
+
+        self.transition_cause = TransitionCause::Changed;
+        self.refresh_button_states();
+        Ok(())
+    }
+
+    /// Enable or disable a button and immediately refresh its rendered style
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+
+        if button.enabled == enabled {
+            return Ok(());
+        }
+        button.set_enabled(enabled);
+
+        self.transition_cause = TransitionCause::Changed;
+        self.refresh_button_states();
+        Ok(())
+    }
+
+    /// Slide and fade the buttons in, staggered by their position in `button_order`
+    pub fn start_entrance(&mut self, animation: EntranceAnimation) {
+        self.entrance = Some(animation);
+        self.apply_entrance();
+    }
+
+    /// Advance animations and held repeat buttons by `delta_time` seconds; call
+    /// once per frame. Returns the actions of repeat buttons that fired.
+    pub fn update(&mut self, delta_time: f32) -> Vec<A> {
+        if let Some(entrance) = &mut self.entrance {
+            entrance.advance(delta_time);
+            self.apply_entrance();
+
+            if self
+                .entrance
+                .as_ref()
+                .is_some_and(|entrance| entrance.is_finished(self.button_order.len()))
+            {
+                self.entrance = None;
+            }
+        }
+
+        for text_input in &mut self.text_inputs {
+            text_input.update(delta_time);
+        }
+
+        let mut fired = Vec::new();
+        for button in self.buttons.values_mut() {
+            let Some(repeat) = button.repeat else {
+                continue;
+            };
+            if button.state != ButtonState::Pressed {
+                button.held_for = 0.0;
                 continue;
             }
+            let before = repeat.repeats_after(button.held_for);
+            button.held_for += delta_time;
+            let due = repeat.repeats_after(button.held_for) - before;
+            fired.extend(std::iter::repeat_n(button.id.clone(), due as usize));
+        }
+        fired.iter().filter_map(|id| self.fire_repeat(id)).collect()
+    }
+
+    /// Report a click from a repeat button: on press and on each repeat
+    fn fire_repeat(&mut self, id: &str) -> Option<A> {
+        self.just_clicked = Some(id.to_string());
+        if let Some(on_click) = &mut self.on_click {
+            on_click(id);
+        }
+        self.take_clicked_action()
+    }
+
+    /// End the entrance animation immediately, leaving every button in place
+    pub fn stop_entrance(&mut self) {
+        if let Some(entrance) = &mut self.entrance {
+            entrance.finish();
+            self.apply_entrance();
+            self.entrance = None;
+        }
+    }
+
+    /// Whether the entrance animation is running, a repeat button is held or
+    /// a caret is blinking, so frames must keep coming
+    pub fn is_animating(&self) -> bool {
+        self.entrance.is_some()
+            || self.text_inputs.iter().any(|input| input.focused)
+            || self
+                .buttons
+                .values()
+                .any(|button| button.repeat.is_some() && button.state == ButtonState::Pressed)
+    }
+
+    /// Fade a single button, clamped to 0.0..=1.0. Combines with the entrance fade.
+    pub fn set_opacity(&mut self, id: &str, opacity: f32) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        button.opacity = opacity.clamp(0.0, 1.0);
+        Self::sync_text_opacity(&mut self.text_renderer.borrow_mut(), button);
+        self.update_icon_positions();
+        Ok(())
+    }
+
+    /// Show or hide a button. Hidden buttons aren't drawn, hit-tested or
+    /// clickable, and neither is their text.
+    pub fn set_visible(&mut self, id: &str, visible: bool) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        button.set_visible(visible);
+        self.transition_cause = TransitionCause::Changed;
+        self.refresh_button_states();
+        self.update_icon_positions();
+        Ok(())
+    }
+
+    /// Show or hide every button at once
+    pub fn set_all_visible(&mut self, visible: bool) {
+        if self
+            .buttons
+            .values()
+            .all(|button| button.visible == visible)
+        {
+            return;
+        }
+        for button in self.buttons.values_mut() {
+            button.set_visible(visible);
+        }
+        self.transition_cause = TransitionCause::Changed;
+        self.refresh_button_states();
+        self.update_icon_positions();
+    }
 
-            button.state = new_state;
-
-            // Update text color and weight based on button state
-            let (text_color, text_weight) = match button.state {
-                ButtonState::Normal => (
-                    button.style.background_color.darken(0.35), // 35% darker than bg
-                    button.style.text_style.weight,
-                ),
-                ButtonState::Hover => (
-                    button.style.hover_color.saturate(0.90), // much brighter and more saturated
-                    Weight::BOLD,
-                ),
-                ButtonState::Pressed => (
-                    button.style.pressed_color.brighten(0.15).saturate(0.35), // brighter and more saturated
-                    Weight::MEDIUM,
-                ),
-                ButtonState::Disabled => (
-                    Color::rgb(100, 116, 139), // slate-500 - muted text
-                    Weight::NORMAL,
-                ),
+    /// Match the visibility of the button's text buffers to the button.
+    /// Returns whether the text was hidden and is now shown.
+    fn sync_text_visibility(text_renderer: &mut TextRenderer, button: &Button<A>) -> bool {
+        let appeared = button.visible && !text_renderer.is_visible(&button.text_id);
+        let text_ids = std::iter::once(&button.text_id)
+            .chain(button.level_text_id.as_ref())
+            .chain(button.tooltip_text_id.as_ref());
+        for text_id in text_ids {
+            let _ = text_renderer.set_visible(text_id, button.visible);
+        }
+        appeared
+    }
+
+    /// Apply the button's opacity to its label, level and tooltip text
+    fn sync_text_opacity(text_renderer: &mut TextRenderer, button: &Button<A>) {
+        let text_ids = std::iter::once(&button.text_id)
+            .chain(button.level_text_id.as_ref())
+            .chain(button.tooltip_text_id.as_ref());
+        for text_id in text_ids {
+            let _ = text_renderer.set_opacity(text_id, button.alpha());
+        }
+    }
+
+    /// Push the entrance animation's current offsets and fades to buttons and their text
+    fn apply_entrance(&mut self) {
+        let Some(entrance) = &self.entrance else {
+            return;
+        };
+
+        for (index, button_id) in self.button_order.iter().enumerate() {
+            let Some(button) = self.buttons.get_mut(button_id) else {
+                continue;
             };
+            button.entrance_offset = entrance.offset(index);
+            button.entrance_alpha = entrance.progress(index);
+
+            let text_ids = std::iter::once(&button.text_id)
+                .chain(button.level_text_id.as_ref())
+                .chain(button.tooltip_text_id.as_ref());
+            for text_id in text_ids {
+                let _ = self.text_renderer.borrow_mut().set_offset(
+                    text_id,
+                    0.0,
+                    button.entrance_offset,
+                );
+            }
+            Self::sync_text_opacity(&mut self.text_renderer.borrow_mut(), button);
+        }
 
-            // Update text size based on hover state for upgrade buttons
-            let text_size_scale = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                match button.state {
-                    ButtonState::Hover => 1.2,   // 20% bigger on hover
-                    ButtonState::Pressed => 1.1, // 10% bigger when pressed
-                    _ => 1.0,                    // Normal size
-                }
-            } else {
-                1.0 // No scaling for non-tall buttons
+        self.update_icon_positions();
+    }
+
+    pub fn update_button_states(&mut self) {
+        // Early exit if mouse state hasn't changed
+        if self.mouse_position == self.last_mouse_position
+            && self.mouse_pressed == self.last_mouse_pressed
+        {
+            self.transition_cause = TransitionCause::Refresh;
+            return;
+        }
+
+        // Update cached mouse state
+        self.last_mouse_position = self.mouse_position;
+        self.last_mouse_pressed = self.mouse_pressed;
+
+        self.refresh_button_states();
+    }
+
+    /// Swap a button's tooltip text for its disabled reason, or restore it
+    fn sync_disabled_reason(text_renderer: &mut TextRenderer, button: &mut Button<A>, show: bool) {
+        let Some(tooltip_id) = &button.tooltip_text_id else {
+            return;
+        };
+        if show == button.saved_tooltip.is_some() {
+            return;
+        }
+
+        if show {
+            let Some(reason) = &button.disabled_reason else {
+                return;
             };
+            button.saved_tooltip = text_renderer
+                .text_buffers
+                .get(tooltip_id)
+                .map(|buffer| buffer.text_content.clone());
+            if let Err(e) = text_renderer.set_text(tooltip_id, reason) {
+                println!("Failed to show disabled reason: {}", e);
+            }
+        } else if let Some(saved) = button.saved_tooltip.take() {
+            if let Err(e) = text_renderer.set_text(tooltip_id, &saved) {
+                println!("Failed to restore tooltip: {}", e);
+            }
+        }
+    }
 
-            // Only update style if color, weight, or size changed
-            let mut new_style = button.style.text_style.clone();
-            new_style.color = text_color;
-            new_style.weight = text_weight;
-            new_style.font_size = button.style.text_style.font_size * text_size_scale;
-            new_style.line_height = button.style.text_style.line_height * text_size_scale;
+    /// Record the last `capacity` state transitions, readable with
+    /// `transitions`; `None` turns recording off and drops the log
+    pub fn set_transition_log(&mut self, capacity: Option<usize>) {
+        self.transition_log = capacity.map(TransitionLog::new);
+    }
 
-            // Make text visible now that color is correct
-            let _ = self
-                .text_renderer
-                .update_style(&button.text_id, new_style.clone());
-
-            // Update level text if it exists
-            if let Some(level_id) = &button.level_text_id {
-                // Create level text style with smaller size and italic
-                let mut level_style = button.style.text_style.clone();
-                level_style.font_size = button.style.text_style.font_size * 0.7 * text_size_scale;
-                level_style.line_height =
-                    button.style.text_style.line_height * 0.7 * text_size_scale;
-                level_style.style = Style::Italic;
-                level_style.color = text_color; // Use same color as main text
-                level_style.weight = text_weight;
-
-                let _ = self.text_renderer.update_style(level_id, level_style);
+    /// Recorded state transitions, oldest first; empty while the log is off
+    pub fn transitions(&self) -> impl Iterator<Item = &StateTransition> + '_ {
+        self.transition_log.iter().flat_map(TransitionLog::iter)
+    }
+
+    /// The most recent recorded state transition
+    pub fn last_transition(&self) -> Option<&StateTransition> {
+        self.transition_log.as_ref()?.last()
+    }
+
+    /// Set the state of `button`, logging the change if the log is on
+    fn transition(
+        log: &mut Option<TransitionLog>,
+        cause: TransitionCause,
+        button: &mut Button<A>,
+        state: ButtonState,
+    ) {
+        if let Some(log) = log {
+            if button.state != state {
+                log.push(StateTransition {
+                    button_id: button.id.clone(),
+                    from: button.state.clone(),
+                    to: state.clone(),
+                    cause,
+                });
             }
+        }
+        button.state = state;
+    }
 
-            // Update tooltip text if it exists
-            if let Some(tooltip_id) = &button.tooltip_text_id {
-                // Create tooltip text style with smaller size
-                let mut tooltip_style = button.style.text_style.clone();
-                tooltip_style.font_size = button.style.text_style.font_size * 0.6 * text_size_scale;
-                tooltip_style.line_height = tooltip_style.font_size * 1.05;
-                tooltip_style.style = Style::Normal;
-                tooltip_style.color = text_color; // Use same color as main text
-                tooltip_style.weight = text_weight;
-
-                let _ = self.text_renderer.update_style(tooltip_id, tooltip_style);
+    /// Recompute every button's state and text style from the current mouse state
+    fn refresh_button_states(&mut self) {
+        let cause = std::mem::replace(&mut self.transition_cause, TransitionCause::Refresh);
+        for button in self.buttons.values_mut() {
+            // Hidden buttons keep their text styled but don't draw it, so it
+            // reappears correctly; a button that just reappeared is restyled
+            let appeared = Self::sync_text_visibility(&mut self.text_renderer.borrow_mut(), button);
+            if !button.visible {
+                Self::sync_disabled_reason(&mut self.text_renderer.borrow_mut(), button, false);
+                Self::transition(
+                    &mut self.transition_log,
+                    cause,
+                    button,
+                    ButtonState::Disabled,
+                );
+                continue;
             }
 
+            let (mouse_x, mouse_y) = self.mouse_position;
+
+            // Disabled buttons still react to hover by explaining why they're disabled
+            let show_reason = !button.enabled
+                && button.disabled_reason.is_some()
+                && button.is_over(mouse_x, mouse_y);
+            Self::sync_disabled_reason(&mut self.text_renderer.borrow_mut(), button, show_reason);
+
+            let was_hovered = matches!(button.state, ButtonState::Hover | ButtonState::Pressed);
+            let new_state = button.next_state(mouse_x, mouse_y, self.mouse_pressed);
+
+            // Only update if state actually changed
+            if button.state == new_state && !appeared {
+                continue;
+            }
+
+            Self::transition(&mut self.transition_log, cause, button, new_state);
+
+            if !was_hovered && matches!(button.state, ButtonState::Hover | ButtonState::Pressed) {
+                if let Some(on_hover_enter) = &mut self.on_hover_enter {
+                    on_hover_enter(&button.id);
+                }
+            }
+
+            let new_style = Self::apply_label_style(&mut self.text_renderer.borrow_mut(), button);
+
             // Update text position for Tall buttons to handle hover scaling
             if let ButtonSpacing::Tall(_) = button.style.spacing {
                 let (actual_x, actual_y) = button.position.calculate_actual_position();
-                let horizontal_padding = button.style.padding.0;
-                let vertical_padding = button.style.padding.1;
+                let (horizontal_padding, vertical_padding) = button.padding();
 
-                let scale = match button.state {
-                    ButtonState::Hover => 1.1,
-                    ButtonState::Pressed => 1.05,
-                    _ => 1.0,
-                };
+                let scale = button.visual_scale();
 
                 let scaled_max_text_width =
                     (button.position.width - 2.0 * horizontal_padding) * scale;
-                let (_min_x, wrap_width, wrap_height) =
-                    self.text_renderer.measure_text(&button.text, &new_style);
+                let (_min_x, wrap_width, wrap_height) = self
+                    .text_renderer
+                    .borrow()
+                    .measure_text(&button.text, &new_style);
 
                 // Calculate base text position (without scaling)
-                let base_text_x = match button.style.text_align {
+                let base_text_x = match button
+                    .style
+                    .text_align
+                    .resolve(button.style.text_style.direction)
+                {
                     TextAlign::Left => actual_x + horizontal_padding,
                     TextAlign::Right => {
                         actual_x + button.position.width - horizontal_padding - wrap_width
                     }
                     TextAlign::Center => actual_x + (button.position.width - wrap_width) / 2.0,
                 };
-                let base_text_y = actual_y + vertical_padding; // Top positioning for Tall buttons
+                let base_text_y = button.style.vertical_align.text_y(
+                    actual_y,
+                    button.position.height,
+                    wrap_height,
+                    vertical_padding,
+                );
 
                 // Apply scaling transformation relative to button center
                 let scaled_text_height = wrap_height * scale;
@@ -598,7 +1934,7 @@ impl ButtonManager {
                 let scaled_text_y = button_center_y + text_offset_y * scale;
 
                 let text_position = TextPosition {
-                    x: scaled_text_x,
+                    x: label_buffer_x(scaled_text_x, wrap_width, scaled_max_text_width, &new_style),
                     y: scaled_text_y,
                     max_width: Some(scaled_max_text_width),
                     max_height: Some(scaled_text_height),
@@ -606,6 +1942,7 @@ impl ButtonManager {
 
                 if let Err(e) = self
                     .text_renderer
+                    .borrow_mut()
                     .update_position(&button.text_id, text_position)
                 {
                     println!("Failed to update text position: {}", e);
@@ -619,11 +1956,17 @@ impl ButtonManager {
                     level_style.style = Style::Italic;
 
                     let level_text = "Level 1";
-                    let (_min_x, level_text_width, level_text_height) =
-                        self.text_renderer.measure_text(level_text, &level_style);
+                    let (_min_x, level_text_width, level_text_height) = self
+                        .text_renderer
+                        .borrow()
+                        .measure_text(level_text, &level_style);
 
                     // Calculate base level text position (without scaling)
-                    let base_level_x = match button.style.text_align {
+                    let base_level_x = match button
+                        .style
+                        .text_align
+                        .resolve(button.style.text_style.direction)
+                    {
                         TextAlign::Left => actual_x + horizontal_padding,
                         TextAlign::Right => {
                             actual_x + button.position.width - horizontal_padding - level_text_width
@@ -654,6 +1997,7 @@ impl ButtonManager {
 
                     if let Err(e) = self
                         .text_renderer
+                        .borrow_mut()
                         .update_position(level_id, level_text_position)
                     {
                         println!("Failed to update level text position: {}", e);
@@ -669,7 +2013,11 @@ impl ButtonManager {
 
                     let extra_tooltip_padding = 10.0;
                     let tooltip_horizontal_padding = horizontal_padding + extra_tooltip_padding;
-                    let base_tooltip_x = match button.style.text_align {
+                    let base_tooltip_x = match button
+                        .style
+                        .text_align
+                        .resolve(button.style.text_style.direction)
+                    {
                         TextAlign::Left => actual_x + tooltip_horizontal_padding,
                         TextAlign::Right => {
                             actual_x + button.position.width - tooltip_horizontal_padding
@@ -700,6 +2048,7 @@ impl ButtonManager {
 
                     if let Err(e) = self
                         .text_renderer
+                        .borrow_mut()
                         .update_position(tooltip_id, tooltip_text_position)
                     {
                         println!("Failed to update tooltip text position: {}", e);
@@ -710,234 +2059,399 @@ impl ButtonManager {
 
         // Update icon positions to match button scaling
         self.update_icon_positions();
+        self.sync_floating_tooltip();
+    }
+
+    /// Show the hovered button's `tooltip` in a box placed by its
+    /// `tooltip_placement`, or hide it when nothing with one is hovered
+    fn sync_floating_tooltip(&mut self) {
+        let text_id = self.text_id(FLOATING_TOOLTIP_ID);
+        let (mouse_x, mouse_y) = self.mouse_position;
+        let hovered = self.iter_ordered().find_map(|(id, button)| {
+            let text = button.tooltip.as_ref()?;
+            button
+                .is_over(mouse_x, mouse_y)
+                .then(|| (id.to_string(), text.clone(), button))
+        });
+
+        let Some((button_id, text, button)) = hovered else {
+            self.tooltip_box = None;
+            if self.tooltip_button.take().is_some() {
+                if let Err(e) = self.text_renderer.borrow_mut().set_visible(&text_id, false) {
+                    println!("Failed to hide tooltip: {}", e);
+                }
+            }
+            return;
+        };
+
+        let mut style = button.style.text_style.clone();
+        style.font_size = button.style.text_style.font_size * 0.55;
+        style.line_height = style.font_size * 1.2;
+        style.style = Style::Normal;
+        style.weight = Weight::NORMAL;
+        style.color = Color::rgb(248, 250, 252); // slate-50
+
+        let (_min_x, text_width, text_height) =
+            self.text_renderer.borrow().measure_text(&text, &style);
+        let size = (
+            text_width + 2.0 * TOOLTIP_PADDING,
+            text_height + 2.0 * TOOLTIP_PADDING,
+        );
+        let (x, y) = button.tooltip_placement.place(
+            button.visual_bounds(),
+            size,
+            (
+                self.window_size.width as f32,
+                self.window_size.height as f32,
+            ),
+            TOOLTIP_GAP,
+        );
+        let position = TextPosition {
+            x: x + TOOLTIP_PADDING,
+            y: y + TOOLTIP_PADDING,
+            max_width: Some(text_width + 1.0),
+            max_height: Some(text_height),
+        };
+
+        // Shape the text only when another button's tooltip comes up; while
+        // the same one stays hovered it just follows the button
+        let mut text_renderer = self.text_renderer.borrow_mut();
+        if self.tooltip_button.as_deref() == Some(button_id.as_str()) {
+            if let Err(e) = text_renderer.update_position(&text_id, position) {
+                println!("Failed to update tooltip position: {}", e);
+            }
+        } else {
+            text_renderer.create_text_buffer(&text_id, &text, Some(style), Some(position));
+            if let Err(e) = text_renderer.set_z_index(&text_id, 100) {
+                println!("Failed to raise tooltip: {}", e);
+            }
+        }
+        drop(text_renderer);
+
+        self.tooltip_box = Some(
+            Rectangle::new(x, y, size.0, size.1, [0.06, 0.09, 0.16, 0.95]) // slate-900
+                .with_corner_radius(6.0),
+        );
+        self.tooltip_button = Some(button_id);
+    }
+
+    /// Style the label, level and tooltip text for the button's state and
+    /// return the label's style
+    fn apply_label_style(text_renderer: &mut TextRenderer, button: &Button<A>) -> TextStyle {
+        // Update text color and weight based on button state
+        let (text_color, text_weight) = button.label_appearance();
+
+        // Update text size based on hover state for upgrade buttons
+        let text_size_scale = if let ButtonSpacing::Tall(_) = button.style.spacing {
+            match button.state {
+                ButtonState::Hover => 1.2,   // 20% bigger on hover
+                ButtonState::Pressed => 1.1, // 10% bigger when pressed
+                _ => 1.0,                    // Normal size
+            }
+        } else {
+            1.0 // No scaling for non-tall buttons
+        };
+
+        // Only update style if color, weight, or size changed
+        let mut new_style = button.style.text_style.clone();
+        new_style.color = text_color;
+        new_style.weight = text_weight;
+        new_style.font_size = button.style.text_style.font_size * text_size_scale;
+        new_style.line_height = button.style.text_style.line_height * text_size_scale;
+
+        // Make text visible now that color is correct
+        let _ = text_renderer.update_style(&button.text_id, new_style.clone());
+
+        // Update level text if it exists
+        if let Some(level_id) = &button.level_text_id {
+            // Create level text style with smaller size and italic
+            let mut level_style = button.style.text_style.clone();
+            level_style.font_size = button.style.text_style.font_size * 0.7 * text_size_scale;
+            level_style.line_height = button.style.text_style.line_height * 0.7 * text_size_scale;
+            level_style.style = Style::Italic;
+            level_style.color = text_color; // Use same color as main text
+            level_style.weight = text_weight;
+
+            let _ = text_renderer.update_style(level_id, level_style);
+        }
+
+        // Update tooltip text if it exists
+        if let Some(tooltip_id) = &button.tooltip_text_id {
+            // Create tooltip text style with smaller size
+            let mut tooltip_style = button.style.text_style.clone();
+            tooltip_style.font_size = button.style.text_style.font_size * 0.6 * text_size_scale;
+            tooltip_style.line_height = tooltip_style.font_size * 1.05;
+            tooltip_style.style = Style::Normal;
+            tooltip_style.color = text_color; // Use same color as main text
+            tooltip_style.weight = text_weight;
+
+            let _ = text_renderer.update_style(tooltip_id, tooltip_style);
+        }
+
+        new_style
     }
 
     pub fn update_button_positions(&mut self) {
         for button in self.buttons.values_mut() {
-            let (actual_x, actual_y) = button.position.calculate_actual_position();
-            let horizontal_padding = button.style.padding.0;
-            let vertical_padding = button.style.padding.1;
-
-            // Calculate scale for hover effect on upgrade buttons
-            let scale = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                match button.state {
-                    ButtonState::Hover => 1.1,    // 10% bigger on hover
-                    ButtonState::Pressed => 1.05, // 5% bigger when pressed
-                    _ => 1.0,                     // Normal size
-                }
-            } else {
-                1.0 // No scaling for non-tall buttons
-            };
+            Self::layout_button_text(&mut self.text_renderer.borrow_mut(), button);
+        }
+        for progress_bar in &self.progress_bars {
+            Self::layout_progress_label(&mut self.text_renderer.borrow_mut(), progress_bar);
+        }
 
-            let scaled_max_text_width = (button.position.width - 2.0 * horizontal_padding) * scale;
-            let (_min_x, wrap_width, wrap_height) = self
-                .text_renderer
-                .measure_text(&button.text, &button.style.text_style);
+        // Update icon positions to match button positions
+        self.update_icon_positions();
+    }
+
+    /// Move one button and lay out only its label, level and tooltip text,
+    /// instead of every button like `update_button_positions`
+    pub fn set_position(&mut self, id: &str, position: ButtonPosition) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        button.position = position;
+        Self::layout_button_text(&mut self.text_renderer.borrow_mut(), button);
+        self.update_icon_positions();
+        Ok(())
+    }
+
+    /// Give a button a new style, e.g. to mark an upgrade as bought: resizes
+    /// it for the style's spacing, restyles its texts for the current state
+    /// and lays them out again
+    pub fn set_style(&mut self, id: &str, style: ButtonStyle) -> Result<(), String> {
+        let button = self
+            .buttons
+            .get_mut(id)
+            .ok_or_else(|| format!("Button '{}' not found", id))?;
+        button.style = ButtonStyle {
+            spacing: style.spacing.clamped(),
+            ..style
+        };
+
+        let ((width, height), _) = self.layout_size(&self.buttons[id]);
+        if let Some(button) = self.buttons.get_mut(id) {
+            button.position.width = width;
+            button.position.height = height;
+            Self::apply_label_style(&mut self.text_renderer.borrow_mut(), button);
+            Self::layout_button_text(&mut self.text_renderer.borrow_mut(), button);
+        }
+        self.update_icon_positions();
+        Ok(())
+    }
+
+    /// Position a button's texts inside its bounds and fit its height to the label
+    fn layout_button_text(text_renderer: &mut TextRenderer, button: &mut Button<A>) {
+        let (actual_x, actual_y) = button.position.calculate_actual_position();
+        let (horizontal_padding, vertical_padding) = button.padding();
+
+        // Calculate scale for hover effect on upgrade buttons
+        let scale = button.visual_scale();
+
+        let scaled_max_text_width = (button.position.width - 2.0 * horizontal_padding) * scale;
+        let (_min_x, wrap_width, wrap_height) =
+            text_renderer.measure_text(&button.text, &button.style.text_style);
+        let (wrap_width, wrap_height) = button.label_size(wrap_width, wrap_height);
+        button.measured_text = (wrap_width, wrap_height);
+        // Wider text wraps inside the padding, so center on what fits
+        let wrap_width = wrap_width.min(button.position.width - 2.0 * horizontal_padding);
+
+        // Position text horizontally by text_align, vertically by vertical_align
+        let base_text_x = match button
+            .style
+            .text_align
+            .resolve(button.style.text_style.direction)
+        {
+            TextAlign::Left => actual_x + horizontal_padding,
+            TextAlign::Right => actual_x + button.position.width - horizontal_padding - wrap_width,
+            TextAlign::Center => actual_x + (button.position.width - wrap_width) / 2.0,
+        };
+
+        let base_text_y = button.style.vertical_align.text_y(
+            actual_y,
+            button.position.height,
+            wrap_height,
+            vertical_padding,
+        );
+
+        // Apply scaling transformation for Tall buttons
+        let (text_x, text_y) = if let ButtonSpacing::Tall(_) = button.style.spacing {
+            // Calculate button center
+            let button_center_x = actual_x + button.position.width / 2.0;
+            let button_center_y = actual_y + button.position.height / 2.0;
+
+            // Calculate text position relative to button center
+            let text_offset_x = base_text_x - button_center_x;
+            let text_offset_y = base_text_y - button_center_y;
+
+            // Scale the offset and apply to button center
+            let scaled_text_x = button_center_x + text_offset_x * scale;
+            let scaled_text_y = button_center_y + text_offset_y * scale;
+            (scaled_text_x, scaled_text_y)
+        } else {
+            (base_text_x, base_text_y)
+        };
+
+        let text_position = TextPosition {
+            x: label_buffer_x(
+                text_x,
+                wrap_width,
+                scaled_max_text_width,
+                &button.style.text_style,
+            ),
+            y: text_y,
+            max_width: Some(scaled_max_text_width),
+            max_height: Some(wrap_height * scale), // Scale the max height too
+        };
+
+        if let Err(e) = text_renderer.update_position(&button.text_id, text_position) {
+            println!("Failed to update button position: {}", e);
+        }
 
-            // Position text - for Tall buttons, put text at the top
-            let base_text_x = match button.style.text_align {
+        // Update level text position if it exists
+        if let Some(level_id) = &button.level_text_id {
+            // Create level text style for measurement
+            let mut level_style = button.style.text_style.clone();
+            level_style.font_size = button.style.text_style.font_size * 0.7;
+            level_style.line_height = button.style.text_style.line_height * 0.7;
+            level_style.style = Style::Italic;
+
+            let level_text = "Level 1";
+            let (_min_x, level_text_width, level_text_height) =
+                text_renderer.measure_text(level_text, &level_style);
+
+            // Position level text below the icon (which is at 50% of button height)
+            let level_text_x = match button
+                .style
+                .text_align
+                .resolve(button.style.text_style.direction)
+            {
                 TextAlign::Left => actual_x + horizontal_padding,
                 TextAlign::Right => {
-                    actual_x + button.position.width - horizontal_padding - wrap_width
+                    actual_x + button.position.width - horizontal_padding - level_text_width
                 }
-                TextAlign::Center => actual_x + (button.position.width - wrap_width) / 2.0,
+                TextAlign::Center => actual_x + (button.position.width - level_text_width) / 2.0,
             };
 
-            let base_text_y = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                // For tall buttons, position text at the top with padding
-                actual_y + vertical_padding
+            let level_text_y = if let ButtonSpacing::Tall(_) = button.style.spacing {
+                // For tall buttons, position just below the icon
+                actual_y + button.position.height * 0.55
             } else {
-                // For other buttons, center text vertically
-                actual_y + (button.position.height - wrap_height) / 2.0
+                // For other buttons, position at the bottom
+                actual_y + button.position.height - level_text_height - vertical_padding
             };
 
             // Apply scaling transformation for Tall buttons
-            let (text_x, text_y) = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                // Calculate button center
-                let button_center_x = actual_x + button.position.width / 2.0;
-                let button_center_y = actual_y + button.position.height / 2.0;
+            let (scaled_level_x, scaled_level_y) =
+                if let ButtonSpacing::Tall(_) = button.style.spacing {
+                    // Calculate button center
+                    let button_center_x = actual_x + button.position.width / 2.0;
+                    let button_center_y = actual_y + button.position.height / 2.0;
 
-                // Calculate text position relative to button center
-                let text_offset_x = base_text_x - button_center_x;
-                let text_offset_y = base_text_y - button_center_y;
+                    // Calculate level text position relative to button center
+                    let level_offset_x = level_text_x - button_center_x;
+                    let level_offset_y = level_text_y - button_center_y;
 
-                // Scale the offset and apply to button center
-                let scaled_text_x = button_center_x + text_offset_x * scale;
-                let scaled_text_y = button_center_y + text_offset_y * scale;
-                (scaled_text_x, scaled_text_y)
-            } else {
-                (base_text_x, base_text_y)
-            };
+                    // Scale the offset and apply to button center
+                    let scaled_level_x = button_center_x + level_offset_x * scale;
+                    let scaled_level_y = button_center_y + level_offset_y * scale;
+                    (scaled_level_x, scaled_level_y)
+                } else {
+                    (level_text_x, level_text_y)
+                };
 
-            let text_position = TextPosition {
-                x: text_x,
-                y: text_y,
-                max_width: Some(scaled_max_text_width),
-                max_height: Some(wrap_height * scale), // Scale the max height too
+            let level_text_position = TextPosition {
+                x: scaled_level_x,
+                y: scaled_level_y,
+                max_width: Some(level_text_width * scale),
+                max_height: Some(level_text_height * scale),
             };
 
-            if let Err(e) = self
-                .text_renderer
-                .update_position(&button.text_id, text_position)
-            {
-                println!("Failed to update button position: {}", e);
+            if let Err(e) = text_renderer.update_position(level_id, level_text_position) {
+                println!("Failed to update level text position: {}", e);
             }
+        }
 
-            // Update level text position if it exists
-            if let Some(level_id) = &button.level_text_id {
-                // Create level text style for measurement
-                let mut level_style = button.style.text_style.clone();
-                level_style.font_size = button.style.text_style.font_size * 0.7;
-                level_style.line_height = button.style.text_style.line_height * 0.7;
-                level_style.style = Style::Italic;
-
-                let level_text = "Level 1";
-                let (_min_x, level_text_width, level_text_height) =
-                    self.text_renderer.measure_text(level_text, &level_style);
-
-                // Position level text below the icon (which is at 50% of button height)
-                let level_text_x = match button.style.text_align {
-                    TextAlign::Left => actual_x + horizontal_padding,
-                    TextAlign::Right => {
-                        actual_x + button.position.width - horizontal_padding - level_text_width
-                    }
-                    TextAlign::Center => {
-                        actual_x + (button.position.width - level_text_width) / 2.0
-                    }
-                };
-
-                let level_text_y = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                    // For tall buttons, position just below the icon
-                    actual_y + button.position.height * 0.55
-                } else {
-                    // For other buttons, position at the bottom
-                    actual_y + button.position.height - level_text_height - vertical_padding
-                };
-
-                // Apply scaling transformation for Tall buttons
-                let (scaled_level_x, scaled_level_y) =
-                    if let ButtonSpacing::Tall(_) = button.style.spacing {
-                        // Calculate button center
-                        let button_center_x = actual_x + button.position.width / 2.0;
-                        let button_center_y = actual_y + button.position.height / 2.0;
-
-                        // Calculate level text position relative to button center
-                        let level_offset_x = level_text_x - button_center_x;
-                        let level_offset_y = level_text_y - button_center_y;
-
-                        // Scale the offset and apply to button center
-                        let scaled_level_x = button_center_x + level_offset_x * scale;
-                        let scaled_level_y = button_center_y + level_offset_y * scale;
-                        (scaled_level_x, scaled_level_y)
-                    } else {
-                        (level_text_x, level_text_y)
-                    };
+        // Update tooltip text position if it exists
+        if let Some(tooltip_id) = &button.tooltip_text_id {
+            // Get the existing tooltip text from the buffer for measurement
+            let tooltip_text = if let Some(buffer) = text_renderer.text_buffers.get(tooltip_id) {
+                buffer.text_content.clone()
+            } else {
+                "This is a place to describe an upgrade, and what effects it has on the game in a little more detail.".to_string()
+            };
 
-                let level_text_position = TextPosition {
-                    x: scaled_level_x,
-                    y: scaled_level_y,
-                    max_width: Some(level_text_width * scale),
-                    max_height: Some(level_text_height * scale),
-                };
+            // Create tooltip text style for measurement - use the same style as in add_button
+            let mut tooltip_style = button.style.text_style.clone();
+            tooltip_style.font_size = button.style.text_style.font_size * 0.55; // 55% of main text size
+            tooltip_style.line_height = tooltip_style.font_size * 1.05;
+            tooltip_style.style = Style::Normal;
 
-                if let Err(e) = self
-                    .text_renderer
-                    .update_position(level_id, level_text_position)
-                {
-                    println!("Failed to update level text position: {}", e);
-                }
-            }
+            let (_min_x, _tooltip_text_width, tooltip_text_height) =
+                text_renderer.measure_text(&tooltip_text, &tooltip_style);
 
-            // Update tooltip text position if it exists
-            if let Some(tooltip_id) = &button.tooltip_text_id {
-                // Get the existing tooltip text from the buffer for measurement
-                let tooltip_text = if let Some(buffer) =
-                    self.text_renderer.text_buffers.get(tooltip_id)
-                {
-                    buffer.text_content.clone()
-                } else {
-                    "This is a place to describe an upgrade, and what effects it has on the game in a little more detail.".to_string()
-                };
+            // Position tooltip text below the level text
+            let extra_tooltip_padding = 10.0;
+            let tooltip_horizontal_padding = horizontal_padding + extra_tooltip_padding;
+            let tooltip_text_x = match button
+                .style
+                .text_align
+                .resolve(button.style.text_style.direction)
+            {
+                TextAlign::Left => actual_x + tooltip_horizontal_padding,
+                TextAlign::Right => actual_x + button.position.width - tooltip_horizontal_padding,
+                TextAlign::Center => actual_x + tooltip_horizontal_padding,
+            };
 
-                // Create tooltip text style for measurement - use the same style as in add_button
-                let mut tooltip_style = button.style.text_style.clone();
-                tooltip_style.font_size = button.style.text_style.font_size * 0.55; // 55% of main text size
-                tooltip_style.line_height = tooltip_style.font_size * 1.05;
-                tooltip_style.style = Style::Normal;
+            let tooltip_text_y = if let ButtonSpacing::Tall(_) = button.style.spacing {
+                // For tall buttons, position below the level text
+                actual_y + button.position.height * 0.68
+            } else {
+                // For other buttons, position at the bottom
+                actual_y + button.position.height - tooltip_text_height - vertical_padding
+            };
 
-                let (_min_x, _tooltip_text_width, tooltip_text_height) = self
-                    .text_renderer
-                    .measure_text(&tooltip_text, &tooltip_style);
+            // Apply scaling transformation for Tall buttons
+            let (scaled_tooltip_x, scaled_tooltip_y) =
+                if let ButtonSpacing::Tall(_) = button.style.spacing {
+                    // Calculate button center
+                    let button_center_x = actual_x + button.position.width / 2.0;
+                    let button_center_y = actual_y + button.position.height / 2.0;
 
-                // Position tooltip text below the level text
-                let extra_tooltip_padding = 10.0;
-                let tooltip_horizontal_padding = horizontal_padding + extra_tooltip_padding;
-                let tooltip_text_x = match button.style.text_align {
-                    TextAlign::Left => actual_x + tooltip_horizontal_padding,
-                    TextAlign::Right => {
-                        actual_x + button.position.width - tooltip_horizontal_padding
-                    }
-                    TextAlign::Center => actual_x + tooltip_horizontal_padding,
-                };
+                    // Calculate tooltip text position relative to button center
+                    let tooltip_offset_x = tooltip_text_x - button_center_x;
+                    let tooltip_offset_y = tooltip_text_y - button_center_y;
 
-                let tooltip_text_y = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                    // For tall buttons, position below the level text
-                    actual_y + button.position.height * 0.68
+                    // Scale the offset and apply to button center
+                    let scaled_tooltip_x = button_center_x + tooltip_offset_x * scale;
+                    let scaled_tooltip_y = button_center_y + tooltip_offset_y * scale;
+                    (scaled_tooltip_x, scaled_tooltip_y)
                 } else {
-                    // For other buttons, position at the bottom
-                    actual_y + button.position.height - tooltip_text_height - vertical_padding
-                };
-
-                // Apply scaling transformation for Tall buttons
-                let (scaled_tooltip_x, scaled_tooltip_y) =
-                    if let ButtonSpacing::Tall(_) = button.style.spacing {
-                        // Calculate button center
-                        let button_center_x = actual_x + button.position.width / 2.0;
-                        let button_center_y = actual_y + button.position.height / 2.0;
-
-                        // Calculate tooltip text position relative to button center
-                        let tooltip_offset_x = tooltip_text_x - button_center_x;
-                        let tooltip_offset_y = tooltip_text_y - button_center_y;
-
-                        // Scale the offset and apply to button center
-                        let scaled_tooltip_x = button_center_x + tooltip_offset_x * scale;
-                        let scaled_tooltip_y = button_center_y + tooltip_offset_y * scale;
-                        (scaled_tooltip_x, scaled_tooltip_y)
-                    } else {
-                        (tooltip_text_x, tooltip_text_y)
-                    };
-
-                let tooltip_text_position = TextPosition {
-                    x: scaled_tooltip_x,
-                    y: scaled_tooltip_y,
-                    max_width: Some(
-                        (button.position.width - 2.0 * tooltip_horizontal_padding) * scale,
-                    ),
-                    max_height: Some(button.position.height * 0.28 * scale), // Allow for more lines
+                    (tooltip_text_x, tooltip_text_y)
                 };
 
-                if let Err(e) = self
-                    .text_renderer
-                    .update_position(tooltip_id, tooltip_text_position)
-                {
-                    println!("Failed to update tooltip text position: {}", e);
-                }
-            }
+            let tooltip_text_position = TextPosition {
+                x: scaled_tooltip_x,
+                y: scaled_tooltip_y,
+                max_width: Some((button.position.width - 2.0 * tooltip_horizontal_padding) * scale),
+                max_height: Some(button.position.height * 0.28 * scale), // Allow for more lines
+            };
 
-            // Only update height here, but respect Tall spacing
-            if let ButtonSpacing::Tall(_) = button.style.spacing {
-                // Don't override height for Tall buttons, it's already set correctly
-            } else {
-                button.position.height = wrap_height + 2.0 * button.style.padding.1;
+            if let Err(e) = text_renderer.update_position(tooltip_id, tooltip_text_position) {
+                println!("Failed to update tooltip text position: {}", e);
             }
         }
 
-        // Update icon positions to match button positions
-        self.update_icon_positions();
+        // Only update height here, but respect Tall and Fixed spacing
+        if let ButtonSpacing::Tall(_) | ButtonSpacing::Fixed(..) = button.style.spacing {
+            // Don't override height for Tall or Fixed buttons, it's already set correctly
+        } else {
+            button.position.height = button.style.padding.fit_height(wrap_height);
+        }
     }
 
     pub fn resize(&mut self, queue: &Queue, resolution: glyphon::Resolution) {
-        self.text_renderer.resize(queue, resolution);
+        self.text_renderer.borrow_mut().resize(queue, resolution);
         self.rectangle_renderer
             .resize(resolution.width as f32, resolution.height as f32);
         self.icon_renderer
@@ -950,17 +2464,83 @@ impl ButtonManager {
         queue: &Queue,
         surface_config: &SurfaceConfiguration,
     ) -> Result<(), glyphon::PrepareError> {
-        self.text_renderer.prepare(device, queue, surface_config)
+        self.text_renderer
+            .borrow_mut()
+            .prepare(device, queue, surface_config)
+    }
+
+    /// Prepare and draw everything into a render pass the host owns, after
+    /// a full-window `overlay` rectangle if given. Preparing is free when a
+    /// shared text renderer was already prepared and nothing changed since.
+    ///
+    /// Only issues draws: the host begins the pass, clears, and sets up depth.
+    /// The pass must target a texture of the format given to `new`, sized to
+    /// `window_size`.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+        overlay: Option<[f32; 4]>,
+    ) -> Result<(), String> {
+        self.prepare(device, queue, surface_config)
+            .map_err(|e| format!("Failed to prepare buttons: {}", e))?;
+        if let Some(color) = overlay {
+            self.render_overlay(device, queue, render_pass, color);
+        }
+        self.render(device, queue, render_pass)
+            .map_err(|e| format!("Failed to render buttons: {}", e))
+    }
+
+    /// Draw a full-window rectangle of `color`, e.g. to dim the scene behind a menu
+    pub fn render_overlay(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        render_pass: &mut RenderPass,
+        color: [f32; 4],
+    ) {
+        self.rectangle_renderer.clear_rectangles();
+        self.rectangle_renderer.add_rectangle(Rectangle::new(
+            0.0,
+            0.0,
+            self.window_size.width as f32,
+            self.window_size.height as f32,
+            color,
+        ));
+        self.rectangle_renderer.render(device, queue, render_pass);
+    }
+
+    /// Clip everything drawn by `render` to `clip`, or stop clipping with `None`
+    pub fn set_clip(&mut self, clip: Option<ClipRegion>) {
+        self.clip = clip;
+    }
+
+    /// Scissor rect for `clip` on the render target: `Some(None)` when the
+    /// region is entirely off-screen, `None` when not clipping at all
+    fn clip_scissor(&self) -> Option<Option<(u32, u32, u32, u32)>> {
+        let region = self.clip?.bounds(self.container_rect.as_ref())?;
+        Some(scissor_rect(region, self.window_size))
     }
 
     pub fn render(
         &mut self,
         device: &Device,
+        queue: &Queue,
         render_pass: &mut RenderPass,
     ) -> Result<(), glyphon::RenderError> {
         // Clear previous rectangles
         self.rectangle_renderer.clear_rectangles();
 
+        let scissor = self.clip_scissor();
+        match scissor {
+            Some(Some((x, y, width, height))) => render_pass.set_scissor_rect(x, y, width, height),
+            // Clipped away entirely
+            Some(None) => return Ok(()),
+            None => {}
+        }
+
         // Render container rectangle first (if it exists)
         if let Some(container_rect) = &self.container_rect {
             self.rectangle_renderer
@@ -970,9 +2550,9 @@ impl ButtonManager {
         // Render buttons in the order they were added
         for button_id in &self.button_order {
             if let Some(button) = self.buttons.get(button_id) {
-                if button.visible {
-                    let (actual_x, actual_y) = button.position.calculate_actual_position();
-
+                // Textured buttons are drawn by the icon renderer; the solid
+                // color is only the fallback while the texture isn't loaded
+                if button.visible && self.background_icon(button).is_none() {
                     // Use the button's style colors for each state
                     let color = if !button.enabled {
                         button.style.disabled_color
@@ -981,6 +2561,7 @@ impl ButtonManager {
                             ButtonState::Normal => button.style.background_color,
                             ButtonState::Hover => button.style.hover_color,
                             ButtonState::Pressed => button.style.pressed_color,
+                            ButtonState::Selected => button.style.selected_color,
                             ButtonState::Disabled => button.style.disabled_color,
                         }
                     };
@@ -988,25 +2569,11 @@ impl ButtonManager {
                         color.r() as f32 / 255.0,
                         color.g() as f32 / 255.0,
                         color.b() as f32 / 255.0,
-                        color.a() as f32 / 255.0,
+                        color.a() as f32 / 255.0 * button.alpha(),
                     ];
 
-                    // Calculate scale for hover effect on upgrade buttons
-                    let scale = if let ButtonSpacing::Tall(_) = button.style.spacing {
-                        match button.state {
-                            ButtonState::Hover => 1.1,    // 10% bigger on hover
-                            ButtonState::Pressed => 1.05, // 5% bigger when pressed
-                            _ => 1.0,                     // Normal size
-                        }
-                    } else {
-                        1.0 // No scaling for non-tall buttons
-                    };
-
-                    // Calculate scaled dimensions and position
-                    let scaled_width = button.position.width * scale;
-                    let scaled_height = button.position.height * scale;
-                    let scaled_x = actual_x - (scaled_width - button.position.width) / 2.0; // Center the scaling
-                    let scaled_y = actual_y - (scaled_height - button.position.height) / 2.0; // Center the scaling
+                    // Scaled for the hover effect on upgrade buttons, centered on the layout rect
+                    let (scaled_x, scaled_y, scaled_width, scaled_height) = button.visual_bounds();
 
                     let rectangle = Rectangle::new(
                         scaled_x,
@@ -1015,20 +2582,333 @@ impl ButtonManager {
                         scaled_height,
                         color_array,
                     )
-                    .with_corner_radius(button.style.corner_radius * scale); // Scale corner radius too
+                    .with_corner_radius(button.visual_corner_radius());
 
                     self.rectangle_renderer.add_rectangle(rectangle);
                 }
             }
         }
 
+        // Progress bars go on top of the buttons
+        for progress_bar in self.progress_bars.iter().filter(|bar| bar.visible) {
+            for rectangle in progress_bar.rectangles() {
+                self.rectangle_renderer.add_rectangle(rectangle);
+            }
+        }
+
+        for text_input in self.text_inputs.iter().filter(|input| input.visible) {
+            for rectangle in text_input.rectangles() {
+                self.rectangle_renderer.add_rectangle(rectangle);
+            }
+        }
+
+        // The floating tooltip covers everything else
+        if let Some(tooltip_box) = &self.tooltip_box {
+            self.rectangle_renderer.add_rectangle(tooltip_box.clone());
+        }
+
         // Render the rectangles first (backgrounds)
-        self.rectangle_renderer.render(device, render_pass);
+        self.rectangle_renderer.render(device, queue, render_pass);
 
         // Then render the icons
-        self.icon_renderer.render(device, render_pass);
+        self.icon_renderer.render(device, queue, render_pass);
 
         // Finally render the text on top
-        self.text_renderer.render(render_pass)
+        let result = self
+            .text_renderer
+            .borrow()
+            .render_namespace(&self.text_namespace, render_pass);
+
+        // Later draws in the host's pass are not clipped
+        if scissor.is_some() {
+            render_pass.set_scissor_rect(0, 0, self.window_size.width, self.window_size.height);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::{self, FORMAT, WINDOW_SIZE};
+    use winit::event::{DeviceId, MouseButton};
+
+    /// A 200x50 button at (100, 100)
+    fn button() -> Button {
+        Button::new("test", "Test").with_position(ButtonPosition::new(100.0, 100.0, 200.0, 50.0))
+    }
+
+    fn mouse(state: ElementState) -> WindowEvent {
+        mouse_button(state, MouseButton::Left)
+    }
+
+    fn mouse_button(state: ElementState, button: MouseButton) -> WindowEvent {
+        WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button,
+        }
+    }
+
+    #[test]
+    fn toggling_enabled_switches_to_and_from_disabled() {
+        let mut button = button();
+        button.state = button.next_state(150.0, 120.0, false);
+        assert_eq!(button.state, ButtonState::Hover);
+
+        button.set_enabled(false);
+        button.state = button.next_state(150.0, 120.0, false);
+        assert_eq!(button.state, ButtonState::Disabled);
+
+        button.set_enabled(true);
+        button.state = button.next_state(150.0, 120.0, false);
+        assert_eq!(button.state, ButtonState::Hover);
+        button.state = button.next_state(10.0, 10.0, false);
+        assert_eq!(button.state, ButtonState::Normal);
+    }
+
+    #[test]
+    fn disabled_button_is_never_pressed() {
+        let button = button().with_enabled(false);
+        assert!(!button.contains_point(150.0, 120.0));
+        assert_eq!(button.next_state(150.0, 120.0, true), ButtonState::Disabled);
+    }
+
+    #[test]
+    fn disabled_button_ignores_clicks() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(button().with_enabled(false));
+        manager.mouse_position = (150.0, 120.0);
+
+        manager.handle_input(&mouse(ElementState::Pressed));
+        manager.handle_input(&mouse(ElementState::Released));
+        assert!(manager.just_clicked.is_none());
+        assert!(manager.click("test").is_err());
+        assert!(!manager.is_button_clicked("test"));
+
+        manager.set_enabled("test", true).unwrap();
+        assert_eq!(manager.buttons["test"].state, ButtonState::Hover);
+        manager.set_enabled("test", false).unwrap();
+        assert_eq!(manager.buttons["test"].state, ButtonState::Disabled);
+    }
+
+    #[test]
+    fn scaled_bounds_grow_around_the_center() {
+        let button = button();
+        assert_eq!(button.scaled_bounds(1.0), (100.0, 100.0, 200.0, 50.0));
+        assert_eq!(button.scaled_bounds(1.1), (90.0, 97.5, 220.0, 55.0));
+    }
+
+    #[test]
+    fn hit_test_matches_the_scaled_rect() {
+        let button = button();
+        // Just inside each edge of the rect grown to 1.1
+        assert!(button.contains_point_scaled(90.5, 120.0, 1.1));
+        assert!(button.contains_point_scaled(309.5, 120.0, 1.1));
+        assert!(button.contains_point_scaled(200.0, 98.0, 1.1));
+        assert!(button.contains_point_scaled(200.0, 152.0, 1.1));
+        // Just outside each edge
+        assert!(!button.contains_point_scaled(89.5, 120.0, 1.1));
+        assert!(!button.contains_point_scaled(310.5, 120.0, 1.1));
+        assert!(!button.contains_point_scaled(200.0, 97.0, 1.1));
+        assert!(!button.contains_point_scaled(200.0, 153.0, 1.1));
+        // Inside the grown rect but outside the layout rect
+        assert!(!button.contains_point_scaled(95.0, 120.0, 1.0));
+    }
+
+    #[test]
+    fn hover_keeps_but_does_not_start_between_the_rects() {
+        let mut button = button();
+        button.style.spacing = ButtonSpacing::Tall(0.5);
+        // Outside the 200x50 layout rect, inside the 1.1x hover rect
+        let (x, y) = (95.0, 120.0);
+
+        assert_eq!(button.next_state(x, y, false), ButtonState::Normal);
+
+        button.state = ButtonState::Hover;
+        assert_eq!(button.next_state(x, y, false), ButtonState::Hover);
+        button.state = ButtonState::Pressed;
+        assert_eq!(button.next_state(x, y, true), ButtonState::Pressed);
+
+        // Leaving the hover rect ends it
+        button.state = ButtonState::Hover;
+        assert_eq!(button.next_state(85.0, y, false), ButtonState::Normal);
+    }
+
+    #[test]
+    fn click_reports_the_button_as_clicked() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(Button::new("resume", "Resume"));
+
+        manager.click("resume").unwrap();
+        assert!(manager.is_button_clicked("resume"));
+        // Checking consumes the click
+        assert!(!manager.is_button_clicked("resume"));
+    }
+
+    #[test]
+    fn click_rejects_hidden_disabled_and_unknown_buttons() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(Button::new("hidden", "Hidden"));
+        manager.add_button(Button::new("disabled", "Disabled").with_enabled(false));
+        manager.set_visible("hidden", false).unwrap();
+
+        assert!(manager.click("hidden").is_err());
+        assert!(manager.click("disabled").is_err());
+        assert!(manager.click("missing").is_err());
+        assert!(manager.just_clicked.is_none());
+    }
+
+    #[test]
+    fn resting_state_label_is_visible() {
+        for button in [
+            button(),
+            button().with_selected(true),
+            button().with_enabled(false),
+        ] {
+            let mut button = button;
+            button.state = button.resting_state();
+            assert_eq!(button.state, button.next_state(-1000.0, -1000.0, false));
+            assert!(button.label_appearance().0.a() > 0);
+        }
+    }
+
+    #[test]
+    fn added_button_label_is_not_transparent() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(button());
+
+        let text_id = &manager.buttons["test"].text_id;
+        let renderer = manager.text_renderer.borrow();
+        assert!(renderer.text_buffers[text_id].style.color.a() > 0);
+        assert_eq!(manager.buttons["test"].state, ButtonState::Normal);
+    }
+
+    #[test]
+    fn two_line_wrap_button_pads_width_horizontally() {
+        let mut button = button();
+        button.style.spacing = ButtonSpacing::Wrap;
+        button.style.padding = Padding::Pixels(16.0, 8.0);
+        // Two 24px lines, the longer one 120px wide
+        let (width, height) = button.fit_size(120.0, 48.0, WINDOW_SIZE);
+        assert_eq!(width, 120.0 + 2.0 * 16.0);
+        assert_eq!(height, 48.0 + 2.0 * 8.0);
+    }
+
+    #[test]
+    fn disabled_danger_button_uses_themed_text_color() {
+        let style = create_danger_button_style();
+        let mut button = button().with_style(style.clone()).with_enabled(false);
+        button.state = button.resting_state();
+
+        let (color, _weight) = button.label_appearance();
+        assert_eq!(color, style.disabled_color.darken(0.35));
+        assert_ne!(color, Color::rgb(100, 116, 139));
+
+        button.style.disabled_text_color = Some(Color::rgb(200, 80, 80));
+        assert_eq!(button.label_appearance().0, Color::rgb(200, 80, 80));
+    }
+
+    #[test]
+    fn hidden_button_has_no_hit_area() {
+        let mut button = button();
+        button.set_visible(false);
+        assert!(!button.contains_point(150.0, 120.0));
+        assert!(!button.is_over(150.0, 120.0));
+        assert_eq!(button.next_state(150.0, 120.0, true), ButtonState::Disabled);
+
+        button.set_visible(true);
+        assert!(button.contains_point(150.0, 120.0));
+        assert_eq!(button.next_state(150.0, 120.0, false), ButtonState::Hover);
+    }
+
+    #[test]
+    fn toggling_visibility_hides_text_and_clicks() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(button());
+        manager.mouse_position = (150.0, 120.0);
+        let text_id = manager.buttons["test"].text_id.clone();
+
+        manager.set_visible("test", false).unwrap();
+        assert!(!manager.text_renderer.borrow().is_visible(&text_id));
+        manager.handle_input(&mouse(ElementState::Pressed));
+        manager.handle_input(&mouse(ElementState::Released));
+        assert!(!manager.is_button_clicked("test"));
+
+        manager.set_visible("test", true).unwrap();
+        assert!(manager.text_renderer.borrow().is_visible(&text_id));
+        assert_eq!(manager.buttons["test"].state, ButtonState::Hover);
+        manager.handle_input(&mouse(ElementState::Pressed));
+        manager.handle_input(&mouse(ElementState::Released));
+        assert!(manager.is_button_clicked("test"));
+    }
+
+    #[test]
+    fn right_click_only_counts_when_any_button_is_accepted() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_button(button());
+        manager.mouse_position = (150.0, 120.0);
+
+        manager.handle_input(&mouse_button(ElementState::Pressed, MouseButton::Right));
+        manager.handle_input(&mouse_button(ElementState::Released, MouseButton::Right));
+        assert!(!manager.is_button_clicked("test"));
+
+        manager.click_buttons = ClickButtons::Any;
+        manager.handle_input(&mouse_button(ElementState::Pressed, MouseButton::Right));
+        manager.handle_input(&mouse_button(ElementState::Released, MouseButton::Right));
+        assert!(manager.is_button_clicked("test"));
+    }
+
+    #[test]
+    fn oversized_corner_radius_is_clamped() {
+        let mut button = button();
+        button.position.height = 40.0;
+        button.style.corner_radius = 100.0;
+        assert_eq!(button.visual_corner_radius(), 20.0);
+
+        // Still half the height when grown by hovering
+        button.style.spacing = ButtonSpacing::Tall(0.5);
+        button.state = ButtonState::Hover;
+        assert_eq!(button.visual_corner_radius(), 20.0 * TALL_HOVER_SCALE);
+    }
+
+    #[test]
+    fn moving_a_progress_bar_moves_its_label() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut manager: ButtonManager = ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        manager.add_progress_bar(
+            ProgressBar::new("load", ButtonPosition::new(100.0, 200.0, 400.0, 20.0))
+                .with_label("50%", TextStyle::default()),
+        );
+        manager
+            .set_progress_bar_position("load", ButtonPosition::new(50.0, 100.0, 800.0, 40.0))
+            .unwrap();
+
+        let label_id = manager.progress_bars[0].label_id.clone();
+        let renderer = manager.text_renderer.borrow();
+        let position = &renderer.text_buffers[&label_id].position;
+        assert_eq!(position.max_width, Some(800.0));
+        assert_eq!(position.max_height, Some(40.0));
+        assert!(position.x > 50.0 && position.x < 450.0);
     }
 }
@@ -1,5 +1,8 @@
-use crate::ui::text::TextStyle;
+use crate::ui::button::utils::{dpi_scale, REFERENCE_HEIGHT};
+use crate::ui::text::{TextDirection, TextStyle, TextWrap};
 use glyphon::{Color, Style, Weight};
+use std::collections::VecDeque;
+use winit::event::MouseButton;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -10,11 +13,134 @@ pub enum TextAlign {
     Center,
 }
 
+impl TextAlign {
+    /// Resolve `Left`/`Right` as start/end of a line in `direction`,
+    /// so they swap for right-to-left text
+    pub fn resolve(&self, direction: TextDirection) -> TextAlign {
+        match (self, direction) {
+            (TextAlign::Left, TextDirection::Rtl) => TextAlign::Right,
+            (TextAlign::Right, TextDirection::Rtl) => TextAlign::Left,
+            (align, _) => align.clone(),
+        }
+    }
+}
+
+/// Vertical placement of the label inside a button
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VerticalAlign {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+impl VerticalAlign {
+    /// Y coordinate of a text block of `text_height` inside a box at `top` of `height`.
+    /// `padding` is kept between the text and the edge it is aligned to.
+    pub fn text_y(&self, top: f32, height: f32, text_height: f32, padding: f32) -> f32 {
+        match self {
+            VerticalAlign::Top => top + padding,
+            VerticalAlign::Center => top + (height - text_height) / 2.0,
+            VerticalAlign::Bottom => top + height - text_height - padding,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ButtonSpacing {
-    Wrap,      // Square, fits text
-    Hbar(f32), // Proportional width (0.0-1.0)
-    Tall(f32), // Tall buttons that fill container height with margin
+    Wrap,            // Fits text plus padding on each side
+    Hbar(f32),       // Proportional width (0.0-1.0)
+    Tall(f32),       // Tall buttons that fill container height with margin
+    Fixed(f32, f32), // Exact (width, height) in pixels
+}
+
+impl ButtonSpacing {
+    /// Smallest Hbar/Tall proportion; anything thinner can't be seen or clicked
+    pub const MIN_PROPORTION: f32 = 0.05;
+    /// Largest Hbar/Tall proportion; a button can't be bigger than the window
+    pub const MAX_PROPORTION: f32 = 1.0;
+
+    /// The same spacing with proportions clamped to
+    /// `MIN_PROPORTION..=MAX_PROPORTION` and fixed sizes to at least zero
+    pub fn clamped(&self) -> ButtonSpacing {
+        let clamp_proportion = |prop: f32| {
+            if prop.is_nan() {
+                Self::MAX_PROPORTION
+            } else {
+                prop.clamp(Self::MIN_PROPORTION, Self::MAX_PROPORTION)
+            }
+        };
+        match *self {
+            ButtonSpacing::Wrap => ButtonSpacing::Wrap,
+            ButtonSpacing::Hbar(prop) => ButtonSpacing::Hbar(clamp_proportion(prop)),
+            ButtonSpacing::Tall(prop) => ButtonSpacing::Tall(clamp_proportion(prop)),
+            ButtonSpacing::Fixed(width, height) => {
+                ButtonSpacing::Fixed(width.max(0.0), height.max(0.0))
+            }
+        }
+    }
+}
+
+/// Space between a button's edge and its label. The horizontal part is kept
+/// left and right of the label and the vertical part above and below it, so a
+/// button sized to its text is `text + 2 * padding` along each axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Padding {
+    /// Fixed `(horizontal, vertical)` padding in pixels
+    Pixels(f32, f32),
+    /// `(horizontal, vertical)` padding as a fraction of the button's width and
+    /// height, so it grows with the button on high-DPI screens
+    Relative(f32, f32),
+}
+
+impl Padding {
+    /// Largest relative padding per side; beyond it no room is left for the label
+    pub const MAX_RELATIVE: f32 = 0.45;
+
+    /// `(horizontal, vertical)` padding in pixels for a button of `width` x `height`
+    pub fn resolve(&self, width: f32, height: f32) -> (f32, f32) {
+        match *self {
+            Padding::Pixels(horizontal, vertical) => (horizontal, vertical),
+            Padding::Relative(horizontal, vertical) => (
+                width * Self::clamp_relative(horizontal),
+                height * Self::clamp_relative(vertical),
+            ),
+        }
+    }
+
+    /// Button width that fits `content` between the horizontal padding
+    pub fn fit_width(&self, content: f32) -> f32 {
+        match *self {
+            Padding::Pixels(horizontal, _) => content + 2.0 * horizontal,
+            Padding::Relative(horizontal, _) => {
+                content / (1.0 - 2.0 * Self::clamp_relative(horizontal))
+            }
+        }
+    }
+
+    /// Button height that fits `content` between the vertical padding
+    pub fn fit_height(&self, content: f32) -> f32 {
+        match *self {
+            Padding::Pixels(_, vertical) => content + 2.0 * vertical,
+            Padding::Relative(_, vertical) => {
+                content / (1.0 - 2.0 * Self::clamp_relative(vertical))
+            }
+        }
+    }
+
+    fn clamp_relative(fraction: f32) -> f32 {
+        if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, Self::MAX_RELATIVE)
+        }
+    }
+}
+
+impl Default for Padding {
+    fn default() -> Self {
+        Padding::Pixels(16.0, 8.0)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,28 +148,45 @@ pub struct ButtonStyle {
     pub background_color: Color,
     pub hover_color: Color,
     pub pressed_color: Color,
+    /// Background of a selected button that isn't hovered
+    pub selected_color: Color,
     pub disabled_color: Color,
+    /// Label color while disabled; `None` derives it from `disabled_color`
+    pub disabled_text_color: Option<Color>,
     pub border_color: Color,
     pub border_width: f32,
     pub corner_radius: f32,
-    pub padding: (f32, f32), // (horizontal, vertical)
+    pub padding: Padding,
     pub text_style: TextStyle,
     pub text_align: TextAlign,
+    pub vertical_align: VerticalAlign,
     pub spacing: ButtonSpacing,
+    /// Lower bound on the width of `Hbar` buttons
+    pub min_width: Option<f32>,
+    /// Upper bound on the width of `Hbar` buttons
+    pub max_width: Option<f32>,
+    /// Image drawn instead of the solid background, stretched to the button
+    pub background_texture_id: Option<String>,
+    /// Swapped in while hovered; without it the background is tinted lighter
+    pub hover_texture_id: Option<String>,
+    /// Swapped in while pressed; without it the background is tinted darker
+    pub pressed_texture_id: Option<String>,
 }
 
 impl Default for ButtonStyle {
     fn default() -> Self {
-        let scale = crate::ui::button::utils::dpi_scale(1080.0); // Assuming a default window height for default values
+        let scale = dpi_scale(REFERENCE_HEIGHT); // Assuming a default window height for default values
         Self {
             background_color: Color::rgb(55, 65, 81),  // slate-700
             hover_color: Color::rgb(71, 85, 105),      // slate-600
             pressed_color: Color::rgb(30, 41, 59),     // slate-800
+            selected_color: Color::rgb(30, 41, 59),    // slate-800
             disabled_color: Color::rgb(148, 163, 184), // slate-400
-            border_color: Color::rgb(71, 85, 105),     // slate-600
+            disabled_text_color: None,
+            border_color: Color::rgb(71, 85, 105), // slate-600
             border_width: 1.0,
             corner_radius: 8.0,
-            padding: (16.0, 8.0),
+            padding: Padding::default(),
             text_style: TextStyle {
                 font_family: "HankenGrotesk".to_string(),
                 font_size: 18.0 * scale,
@@ -51,13 +194,177 @@ impl Default for ButtonStyle {
                 color: Color::rgb(248, 250, 252), // slate-50
                 weight: Weight::MEDIUM,
                 style: Style::Normal,
+                wrap: TextWrap::Word,
+                direction: TextDirection::Auto,
+                letter_spacing: 0.0,
             },
             text_align: TextAlign::Center,
+            vertical_align: VerticalAlign::Center,
             spacing: ButtonSpacing::Hbar(0.3),
+            min_width: None,
+            max_width: None,
+            background_texture_id: None,
+            hover_texture_id: None,
+            pressed_texture_id: None,
         }
     }
 }
 
+impl ButtonStyle {
+    /// Start a style from the defaults and override only what differs
+    pub fn builder() -> ButtonStyleBuilder {
+        ButtonStyleBuilder::default()
+    }
+
+    /// Width of an `Hbar` button in a window `window_width` wide, kept within
+    /// `min_width..=max_width`. `None` for other spacings.
+    pub fn hbar_width(&self, window_width: f32) -> Option<f32> {
+        let ButtonSpacing::Hbar(prop) = self.spacing else {
+            return None;
+        };
+        let mut width = window_width * prop;
+        if let Some(max_width) = self.max_width {
+            width = width.min(max_width);
+        }
+        if let Some(min_width) = self.min_width {
+            width = width.max(min_width);
+        }
+        Some(width)
+    }
+
+    /// Texture id and tint for the background in `state`, or `None` for a solid color
+    pub fn background_texture(&self, state: &ButtonState) -> Option<(&str, [f32; 4])> {
+        let base = self.background_texture_id.as_deref()?;
+        let (swapped, tint) = match state {
+            ButtonState::Normal => (None, [1.0, 1.0, 1.0, 1.0]),
+            ButtonState::Hover => (self.hover_texture_id.as_deref(), [1.15, 1.15, 1.15, 1.0]),
+            ButtonState::Pressed | ButtonState::Selected => {
+                (self.pressed_texture_id.as_deref(), [0.8, 0.8, 0.8, 1.0])
+            }
+            ButtonState::Disabled => (None, [0.5, 0.5, 0.5, 0.6]),
+        };
+        match swapped {
+            // A dedicated texture already looks the part, so draw it untinted
+            Some(texture_id) => Some((texture_id, [1.0, 1.0, 1.0, 1.0])),
+            None => Some((base, tint)),
+        }
+    }
+}
+
+/// Builds a `ButtonStyle` as a set of changes to `ButtonStyle::default()`
+#[derive(Debug, Clone, Default)]
+pub struct ButtonStyleBuilder {
+    style: ButtonStyle,
+}
+
+impl ButtonStyleBuilder {
+    /// Background colors per state. The border follows the hover color unless
+    /// `border` is called afterwards, and selected buttons look pressed unless
+    /// `selected_color` is.
+    pub fn colors(mut self, background: Color, hover: Color, pressed: Color) -> Self {
+        self.style.background_color = background;
+        self.style.hover_color = hover;
+        self.style.pressed_color = pressed;
+        self.style.selected_color = pressed;
+        self.style.border_color = hover;
+        self
+    }
+
+    pub fn selected_color(mut self, color: Color) -> Self {
+        self.style.selected_color = color;
+        self
+    }
+
+    pub fn disabled_color(mut self, color: Color) -> Self {
+        self.style.disabled_color = color;
+        self
+    }
+
+    pub fn disabled_text_color(mut self, color: Color) -> Self {
+        self.style.disabled_text_color = Some(color);
+        self
+    }
+
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.style.border_color = color;
+        self.style.border_width = width;
+        self
+    }
+
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.style.corner_radius = radius;
+        self
+    }
+
+    /// Padding in pixels
+    pub fn padding(mut self, horizontal: f32, vertical: f32) -> Self {
+        self.style.padding = Padding::Pixels(horizontal, vertical);
+        self
+    }
+
+    /// Padding as a fraction of the button's width and height
+    pub fn relative_padding(mut self, horizontal: f32, vertical: f32) -> Self {
+        self.style.padding = Padding::Relative(horizontal, vertical);
+        self
+    }
+
+    pub fn text_style(mut self, text_style: TextStyle) -> Self {
+        self.style.text_style = text_style;
+        self
+    }
+
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.style.text_style.color = color;
+        self
+    }
+
+    /// Font size and line height of the label
+    pub fn font_size(mut self, font_size: f32, line_height: f32) -> Self {
+        self.style.text_style.font_size = font_size;
+        self.style.text_style.line_height = line_height;
+        self
+    }
+
+    pub fn text_align(mut self, text_align: TextAlign) -> Self {
+        self.style.text_align = text_align;
+        self
+    }
+
+    pub fn vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.style.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn spacing(mut self, spacing: ButtonSpacing) -> Self {
+        self.style.spacing = spacing;
+        self
+    }
+
+    /// Keep `Hbar` buttons between `min` and `max` pixels wide
+    pub fn width_bounds(mut self, min: f32, max: f32) -> Self {
+        self.style.min_width = Some(min);
+        self.style.max_width = Some(max);
+        self
+    }
+
+    /// Draw `texture_id` (loaded into the icon renderer) as the background
+    pub fn background_texture(mut self, texture_id: &str) -> Self {
+        self.style.background_texture_id = Some(texture_id.to_string());
+        self
+    }
+
+    /// Textures swapped in while hovered and pressed
+    pub fn state_textures(mut self, hover: Option<&str>, pressed: Option<&str>) -> Self {
+        self.style.hover_texture_id = hover.map(str::to_string);
+        self.style.pressed_texture_id = pressed.map(str::to_string);
+        self
+    }
+
+    pub fn build(self) -> ButtonStyle {
+        self.style
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ButtonPosition {
     pub x: f32,
@@ -111,4 +418,215 @@ pub enum ButtonState {
     Hover,
     Pressed,
     Disabled,
+    /// Not hovered, but marked selected with `ButtonManager::set_selected`,
+    /// e.g. the active tab or a toggle that is on
+    Selected,
+}
+
+/// Which mouse buttons count as a click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClickButtons {
+    /// Only the left (primary) button
+    #[default]
+    Left,
+    /// Any mouse button, e.g. for "click anywhere" screens
+    Any,
+}
+
+impl ClickButtons {
+    pub fn accepts(self, button: MouseButton) -> bool {
+        self == ClickButtons::Any || button == MouseButton::Left
+    }
+}
+
+/// What made a button change state, recorded in the transition log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionCause {
+    MouseMoved,
+    MousePressed,
+    MouseReleased,
+    /// Visibility or enabled state changed through the manager
+    Changed,
+    /// Any other refresh, e.g. `update_button_states`
+    Refresh,
+}
+
+/// One recorded `ButtonState` change
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTransition {
+    pub button_id: String,
+    pub from: ButtonState,
+    pub to: ButtonState,
+    pub cause: TransitionCause,
+}
+
+/// The last `capacity` state transitions, oldest first
+#[derive(Debug, Clone)]
+pub struct TransitionLog {
+    pub capacity: usize,
+    entries: VecDeque<StateTransition>,
+}
+
+impl TransitionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, transition: StateTransition) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(transition);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StateTransition> {
+        self.entries.iter()
+    }
+
+    pub fn last(&self) -> Option<&StateTransition> {
+        self.entries.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Timing for buttons that keep firing while held, like volume arrows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepeatConfig {
+    /// Seconds held before the first repeat
+    pub delay: f32,
+    /// Seconds between repeats after that
+    pub interval: f32,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self {
+            delay: 0.4,
+            interval: 0.08,
+        }
+    }
+}
+
+impl RepeatConfig {
+    /// How many repeats are due after holding for `held` seconds
+    pub fn repeats_after(&self, held: f32) -> u32 {
+        if held < self.delay {
+            return 0;
+        }
+        // A zero interval would fire without bound in a single frame
+        1 + ((held - self.delay) / self.interval.max(0.01)) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertical_align_places_text_in_the_box() {
+        // A 100px tall box at y = 50 holding 20px of text, 10px padding
+        assert_eq!(VerticalAlign::Top.text_y(50.0, 100.0, 20.0, 10.0), 60.0);
+        assert_eq!(VerticalAlign::Center.text_y(50.0, 100.0, 20.0, 10.0), 90.0);
+        assert_eq!(VerticalAlign::Bottom.text_y(50.0, 100.0, 20.0, 10.0), 120.0);
+    }
+
+    #[test]
+    fn vertical_align_with_text_taller_than_the_box() {
+        // 140px of text in the same box: top keeps its padding, center
+        // overflows evenly, bottom keeps the padding below the last line
+        assert_eq!(VerticalAlign::Top.text_y(50.0, 100.0, 140.0, 10.0), 60.0);
+        assert_eq!(VerticalAlign::Center.text_y(50.0, 100.0, 140.0, 10.0), 30.0);
+        assert_eq!(VerticalAlign::Bottom.text_y(50.0, 100.0, 140.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn out_of_range_proportions_are_clamped() {
+        let min = ButtonSpacing::MIN_PROPORTION;
+        let max = ButtonSpacing::MAX_PROPORTION;
+        assert_eq!(ButtonSpacing::Hbar(0.0).clamped(), ButtonSpacing::Hbar(min));
+        assert_eq!(ButtonSpacing::Hbar(1.5).clamped(), ButtonSpacing::Hbar(max));
+        assert_eq!(
+            ButtonSpacing::Hbar(-0.2).clamped(),
+            ButtonSpacing::Hbar(min)
+        );
+        assert_eq!(ButtonSpacing::Tall(0.0).clamped(), ButtonSpacing::Tall(min));
+        assert_eq!(ButtonSpacing::Tall(1.5).clamped(), ButtonSpacing::Tall(max));
+        assert_eq!(
+            ButtonSpacing::Tall(-0.2).clamped(),
+            ButtonSpacing::Tall(min)
+        );
+    }
+
+    #[test]
+    fn in_range_spacing_is_unchanged() {
+        assert_eq!(ButtonSpacing::Hbar(0.5).clamped(), ButtonSpacing::Hbar(0.5));
+        assert_eq!(ButtonSpacing::Tall(1.0).clamped(), ButtonSpacing::Tall(1.0));
+        assert_eq!(ButtonSpacing::Wrap.clamped(), ButtonSpacing::Wrap);
+    }
+
+    #[test]
+    fn clamped_spacing_at_its_bounds() {
+        let min = ButtonSpacing::MIN_PROPORTION;
+        let max = ButtonSpacing::MAX_PROPORTION;
+        assert_eq!(ButtonSpacing::Hbar(min).clamped(), ButtonSpacing::Hbar(min));
+        assert_eq!(ButtonSpacing::Hbar(max).clamped(), ButtonSpacing::Hbar(max));
+        assert_eq!(
+            ButtonSpacing::Tall(f32::NAN).clamped(),
+            ButtonSpacing::Tall(max)
+        );
+        assert_eq!(
+            ButtonSpacing::Fixed(-10.0, 40.0).clamped(),
+            ButtonSpacing::Fixed(0.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn pixel_padding_ignores_the_button_size() {
+        let padding = Padding::Pixels(12.0, 6.0);
+        assert_eq!(padding.resolve(200.0, 50.0), (12.0, 6.0));
+        assert_eq!(padding.resolve(400.0, 100.0), (12.0, 6.0));
+        assert_eq!(padding.fit_width(100.0), 124.0);
+        assert_eq!(padding.fit_height(20.0), 32.0);
+    }
+
+    #[test]
+    fn relative_padding_scales_with_the_button() {
+        let padding = Padding::Relative(0.1, 0.25);
+        assert_eq!(padding.resolve(200.0, 40.0), (20.0, 10.0));
+        assert_eq!(padding.resolve(400.0, 80.0), (40.0, 20.0));
+        // The content takes what the padding leaves: 80% of the width, half the height
+        assert_eq!(padding.fit_width(80.0), 100.0);
+        assert_eq!(padding.fit_height(20.0), 40.0);
+        // Fitting and resolving agree
+        let width = padding.fit_width(80.0);
+        assert_eq!(width - 2.0 * padding.resolve(width, 0.0).0, 80.0);
+    }
+
+    #[test]
+    fn relative_padding_is_clamped() {
+        let max = Padding::MAX_RELATIVE;
+        assert_eq!(
+            Padding::Relative(0.9, -1.0).resolve(100.0, 100.0),
+            (100.0 * max, 0.0)
+        );
+        assert_eq!(Padding::Relative(f32::NAN, 0.0).fit_width(50.0), 50.0);
+    }
+
+    #[test]
+    fn any_accepts_a_right_click_and_left_does_not() {
+        assert!(ClickButtons::Any.accepts(MouseButton::Right));
+        assert!(ClickButtons::Any.accepts(MouseButton::Middle));
+        assert!(ClickButtons::Any.accepts(MouseButton::Left));
+        assert!(!ClickButtons::Left.accepts(MouseButton::Right));
+        assert!(ClickButtons::Left.accepts(MouseButton::Left));
+    }
 }
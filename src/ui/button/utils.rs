@@ -26,38 +26,186 @@ impl ColorExt for Color {
             self.a(),
         )
     }
+    /// Raise the HSL saturation by `factor` (0.0..=1.0), keeping hue and
+    /// lightness, e.g. slate-600 `(71, 85, 105)` with 0.9 becomes the deep
+    /// blue `(0, 72, 176)` instead of clipping channels and shifting hue
     fn saturate(&self, factor: f32) -> Self {
-        // Convert RGB to HSL, increase saturation, then convert back
-        let r = self.r() as f32 / 255.0;
-        let g = self.g() as f32 / 255.0;
-        let b = self.b() as f32 / 255.0;
-        let max = r.max(g).max(b);
-        let min = r.min(g).min(b);
-        let l = (max + min) / 2.0;
-        let d = max - min;
-        let mut s = if d == 0.0 {
-            0.0
-        } else {
-            d / (1.0 - (2.0 * l - 1.0).abs())
-        };
-        s = (s + factor).min(1.0);
-        // Recompute RGB from HSL (approximate, since hue is not changed)
-        // We'll just scale the color channels away from the gray axis
-        let gray = l;
-        let scale = if s == 0.0 { 0.0 } else { s };
-        let new_r = gray + (r - gray) * (1.0 + scale);
-        let new_g = gray + (g - gray) * (1.0 + scale);
-        let new_b = gray + (b - gray) * (1.0 + scale);
-        Color::rgba(
-            (new_r.clamp(0.0, 1.0) * 255.0) as u8,
-            (new_g.clamp(0.0, 1.0) * 255.0) as u8,
-            (new_b.clamp(0.0, 1.0) * 255.0) as u8,
-            self.a(),
-        )
+        let (h, s, l) = rgb_to_hsl(self.r(), self.g(), self.b());
+        let s = (s + factor.clamp(0.0, 1.0)).min(1.0);
+        let (r, g, b) = hsl_to_rgb(h, s, l);
+        Color::rgba(r, g, b, self.a())
     }
 }
 
-// Add a helper function for DPI scaling
+/// Blend from `from` to `to` by `t` (0.0..=1.0) in HSL, taking the short way
+/// around the hue wheel, so green to red passes through yellow rather than
+/// a muddy brown
+pub fn lerp_hsl(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let (h1, s1, l1) = rgb_to_hsl(from.r(), from.g(), from.b());
+    let (h2, s2, l2) = rgb_to_hsl(to.r(), to.g(), to.b());
+    let mut dh = h2 - h1;
+    if dh > 0.5 {
+        dh -= 1.0;
+    } else if dh < -0.5 {
+        dh += 1.0;
+    }
+    let h = (h1 + dh * t).rem_euclid(1.0);
+    let (r, g, b) = hsl_to_rgb(h, s1 + (s2 - s1) * t, l1 + (l2 - l1) * t);
+    let a = from.a() as f32 + (to.a() as f32 - from.a() as f32) * t;
+    Color::rgba(r, g, b, a.round() as u8)
+}
+
+/// `(hue 0.0..1.0, saturation, lightness)` of an RGB color
+fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        // Gray, hue is undefined
+        return (0.0, 0.0, l);
+    }
+
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h / 6.0, s.min(1.0), l)
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h * 6.0;
+    let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    let channel = |value: f32| ((value + m).clamp(0.0, 1.0) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Screen pixels (origin top-left, Y down) to normalized device coordinates
+/// (origin center, Y up). Every renderer converts through here so shapes
+/// placed at the same screen position line up.
+pub fn screen_to_ndc(x: f32, y: f32, window_width: f32, window_height: f32) -> [f32; 2] {
+    [
+        (x / window_width) * 2.0 - 1.0,
+        1.0 - (y / window_height) * 2.0,
+    ]
+}
+
+/// `[top-left, bottom-right]` NDC corners of a screen rectangle. Icons and
+/// rectangles both place their quads through here.
+pub fn rect_to_ndc(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    window_width: f32,
+    window_height: f32,
+) -> [[f32; 2]; 2] {
+    [
+        screen_to_ndc(x, y, window_width, window_height),
+        screen_to_ndc(x + width, y + height, window_width, window_height),
+    ]
+}
+
+/// Window height at which UI sizes are used unscaled
+pub const REFERENCE_HEIGHT: f32 = 1080.0;
+/// Bounds on the scale factor, so tiny or huge windows stay usable
+pub const MIN_DPI_SCALE: f32 = 0.7;
+pub const MAX_DPI_SCALE: f32 = 2.0;
+
+/// Virtual DPI scale for a window height. Every size that scales with the
+/// window goes through here so all UI scales by the same factor.
 pub fn dpi_scale(window_height: f32) -> f32 {
-    (window_height / 1080.0).clamp(0.7, 2.0)
+    (window_height / REFERENCE_HEIGHT).clamp(MIN_DPI_SCALE, MAX_DPI_SCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dpi_scale_is_one_at_the_reference_height() {
+        assert_eq!(dpi_scale(REFERENCE_HEIGHT), 1.0);
+        assert_eq!(dpi_scale(1620.0), 1.5);
+    }
+
+    #[test]
+    fn dpi_scale_is_clamped() {
+        assert_eq!(dpi_scale(100.0), MIN_DPI_SCALE);
+        assert_eq!(dpi_scale(10_000.0), MAX_DPI_SCALE);
+    }
+
+    #[test]
+    fn screen_corners_and_center_map_to_ndc() {
+        assert_eq!(screen_to_ndc(0.0, 0.0, 800.0, 600.0), [-1.0, 1.0]);
+        assert_eq!(screen_to_ndc(800.0, 600.0, 800.0, 600.0), [1.0, -1.0]);
+        assert_eq!(screen_to_ndc(800.0, 0.0, 800.0, 600.0), [1.0, 1.0]);
+        assert_eq!(screen_to_ndc(0.0, 600.0, 800.0, 600.0), [-1.0, -1.0]);
+        assert_eq!(screen_to_ndc(400.0, 300.0, 800.0, 600.0), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn full_window_rect_covers_ndc() {
+        assert_eq!(
+            rect_to_ndc(0.0, 0.0, 800.0, 600.0, 800.0, 600.0),
+            [[-1.0, 1.0], [1.0, -1.0]]
+        );
+        // The top-left quarter, y up in NDC
+        assert_eq!(
+            rect_to_ndc(0.0, 0.0, 400.0, 300.0, 800.0, 600.0),
+            [[-1.0, 1.0], [0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn saturate_matches_the_doc_example() {
+        let slate_600 = Color::rgb(71, 85, 105);
+        assert_eq!(slate_600.saturate(0.9), Color::rgb(0, 72, 176));
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for (r, g, b) in [
+            (71, 85, 105),
+            (255, 0, 0),
+            (0, 255, 0),
+            (0, 0, 255),
+            (110, 20, 10),
+            (250, 200, 30),
+            (0, 0, 0),
+            (255, 255, 255),
+        ] {
+            let (h, s, l) = rgb_to_hsl(r, g, b);
+            assert_eq!(hsl_to_rgb(h, s, l), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn gray_has_no_saturation() {
+        for v in [0, 128, 255] {
+            let (h, s, l) = rgb_to_hsl(v, v, v);
+            assert!(h.is_finite() && l.is_finite());
+            assert_eq!(s, 0.0);
+            assert_eq!(hsl_to_rgb(h, s, l), (v, v, v));
+        }
+        // Saturating gray starts from hue 0 instead of dividing by zero
+        let saturated = Color::rgb(128, 128, 128).saturate(0.5);
+        assert_eq!(saturated.a(), 255);
+        assert!(saturated.r() > saturated.g());
+    }
 }
@@ -0,0 +1,98 @@
+use crate::ui::rectangle::Rectangle;
+use winit::dpi::PhysicalSize;
+
+/// Area a `ButtonManager` draws into, for panels and scroll areas whose
+/// content must not spill outside their bounds. Applied as a scissor rect
+/// around the whole group, text and icons included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipRegion {
+    /// The manager's `container_rect`, following it when it is dragged
+    Container,
+    /// A fixed area as `(x, y, width, height)` in UI coordinates
+    Rect(f32, f32, f32, f32),
+}
+
+impl ClipRegion {
+    /// `(x, y, width, height)` of the region, given the manager's container;
+    /// `None` for `Container` without one
+    pub fn bounds(self, container: Option<&Rectangle>) -> Option<(f32, f32, f32, f32)> {
+        match self {
+            ClipRegion::Container => {
+                container.map(|rect| (rect.x, rect.y, rect.width, rect.height))
+            }
+            ClipRegion::Rect(x, y, width, height) => Some((x, y, width, height)),
+        }
+    }
+}
+
+/// Pixel scissor rect `(x, y, width, height)` covering `region`, clamped to a
+/// render target of `target_size`. `None` when nothing of it is on the target.
+pub fn scissor_rect(
+    region: (f32, f32, f32, f32),
+    target_size: PhysicalSize<u32>,
+) -> Option<(u32, u32, u32, u32)> {
+    let (x, y, width, height) = region;
+    // Round outwards so antialiased edges on the boundary aren't cut
+    let left = x.floor().max(0.0) as u32;
+    let top = y.floor().max(0.0) as u32;
+    let right = ((x + width).ceil().max(0.0) as u32).min(target_size.width);
+    let bottom = ((y + height).ceil().max(0.0) as u32).min(target_size.height);
+
+    if left >= right || top >= bottom {
+        return None;
+    }
+    Some((left, top, right - left, bottom - top))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: PhysicalSize<u32> = PhysicalSize::new(800, 600);
+
+    fn rect_scissor(x: f32, y: f32, width: f32, height: f32) -> Option<(u32, u32, u32, u32)> {
+        scissor_rect(ClipRegion::Rect(x, y, width, height).bounds(None)?, TARGET)
+    }
+
+    #[test]
+    fn rect_region_becomes_its_scissor_rect() {
+        assert_eq!(
+            rect_scissor(100.0, 50.0, 200.0, 100.0),
+            Some((100, 50, 200, 100))
+        );
+        // Fractional edges round outwards
+        assert_eq!(
+            rect_scissor(10.5, 20.5, 100.0, 50.0),
+            Some((10, 20, 101, 51))
+        );
+    }
+
+    #[test]
+    fn rect_region_is_clamped_to_the_target() {
+        assert_eq!(
+            rect_scissor(-50.0, -20.0, 200.0, 100.0),
+            Some((0, 0, 150, 80))
+        );
+        assert_eq!(
+            rect_scissor(700.0, 500.0, 200.0, 200.0),
+            Some((700, 500, 100, 100))
+        );
+    }
+
+    #[test]
+    fn empty_or_off_target_region_has_no_scissor_rect() {
+        assert_eq!(rect_scissor(100.0, 100.0, 0.0, 50.0), None);
+        assert_eq!(rect_scissor(900.0, 100.0, 50.0, 50.0), None);
+        assert_eq!(rect_scissor(-100.0, -100.0, 50.0, 50.0), None);
+    }
+
+    #[test]
+    fn container_region_follows_the_container() {
+        assert_eq!(ClipRegion::Container.bounds(None), None);
+        let container = Rectangle::new(10.0, 20.0, 300.0, 200.0, [1.0; 4]);
+        assert_eq!(
+            ClipRegion::Container.bounds(Some(&container)),
+            Some((10.0, 20.0, 300.0, 200.0))
+        );
+    }
+}
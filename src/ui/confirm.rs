@@ -0,0 +1,310 @@
+use crate::ui::button::{
+    create_danger_button_style, create_primary_button_style, utils::dpi_scale, Button,
+    ButtonAnchor, ButtonManager, ButtonPosition, ButtonSpacing, ButtonStyle, TextAlign,
+    DEFAULT_OVERLAY_COLOR,
+};
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::rectangle::Rectangle;
+use crate::ui::text::{SharedTextRenderer, TextPosition, TextStyle};
+use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
+use glyphon::{Color, Resolution};
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, WindowEvent};
+use winit::keyboard::{Key, NamedKey};
+use winit::window::CursorIcon;
+
+const MESSAGE_TEXT_ID: &str = "confirm_message";
+pub(crate) const CONFIRM_BUTTON_ID: &str = "confirm_yes";
+pub(crate) const CANCEL_BUTTON_ID: &str = "confirm_no";
+
+/// The player's answer to a `ConfirmDialog`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    Confirmed,
+    Cancelled,
+}
+
+/// A small centered panel asking a yes/no question, e.g. before quitting.
+/// While shown it is modal: the host sends it every event and nothing else
+/// should react to them. Escape answers `Cancelled`.
+pub struct ConfirmDialog {
+    pub button_manager: ButtonManager<ConfirmAction>,
+    /// Color of the full-window rectangle dimming whatever is behind the panel
+    pub overlay_color: [f32; 4],
+    /// Color of the panel behind the message and buttons
+    pub panel_color: [f32; 4],
+    message: String,
+    visible: bool,
+}
+
+impl ConfirmDialog {
+    /// Build for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+        ))
+    }
+
+    /// Like `new_with_config`, drawing text through `text_renderer` under
+    /// `namespace`
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+        namespace: &str,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::with_text_renderer(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+            text_renderer,
+            namespace,
+        ))
+    }
+
+    fn with_button_manager(button_manager: ButtonManager<ConfirmAction>) -> Self {
+        let mut dialog = Self {
+            button_manager,
+            overlay_color: DEFAULT_OVERLAY_COLOR,
+            panel_color: [0.12, 0.16, 0.23, 0.98], // slate-800
+            message: String::new(),
+            visible: false,
+        };
+        dialog.create_layout();
+        dialog.set_visible(false);
+        dialog
+    }
+
+    fn text_style(font_size: f32, line_height: f32) -> TextStyle {
+        TextStyle {
+            font_family: "HankenGrotesk".to_string(),
+            font_size,
+            line_height,
+            color: Color::rgb(255, 255, 255),
+            weight: glyphon::Weight::MEDIUM,
+            ..TextStyle::default()
+        }
+    }
+
+    /// Size the panel around the wrapped message and center it, with the
+    /// two buttons side by side at the bottom
+    fn create_layout(&mut self) {
+        let window_width = self.button_manager.window_size.width as f32;
+        let window_height = self.button_manager.window_size.height as f32;
+        let scale = dpi_scale(window_height);
+
+        let panel_width = (560.0 * scale).min(window_width * 0.9);
+        let padding = 32.0 * scale;
+        let gap = 24.0 * scale;
+        let button_height = 56.0 * scale;
+        let inner_width = panel_width - 2.0 * padding;
+
+        // Wrap the message to the panel first to learn its height
+        let text_id = self.button_manager.text_id(MESSAGE_TEXT_ID);
+        let mut text_renderer = self.button_manager.text_renderer.borrow_mut();
+        text_renderer.create_text_buffer(
+            &text_id,
+            &self.message,
+            Some(Self::text_style(30.0 * scale, 38.0 * scale)),
+            Some(TextPosition {
+                x: 0.0,
+                y: 0.0,
+                max_width: Some(inner_width),
+                max_height: None,
+            }),
+        );
+        let (_x, _y, text_width, text_height) = text_renderer
+            .get_buffer_bounds(&text_id)
+            .unwrap_or_default();
+
+        let panel_height = padding + text_height + gap + button_height + padding;
+        let panel_x = (window_width - panel_width) / 2.0;
+        let panel_y = (window_height - panel_height) / 2.0;
+        let message_position = TextPosition {
+            x: panel_x + (panel_width - text_width) / 2.0,
+            y: panel_y + padding,
+            max_width: Some(text_width + 1.0),
+            max_height: Some(text_height),
+        };
+        if let Err(e) = text_renderer.update_position(&text_id, message_position) {
+            println!("Failed to lay out confirm dialog: {}", e);
+        }
+        drop(text_renderer);
+
+        self.button_manager.container_rect = Some(
+            Rectangle::new(
+                panel_x,
+                panel_y,
+                panel_width,
+                panel_height,
+                self.panel_color,
+            )
+            .with_corner_radius(12.0 * scale),
+        );
+
+        let button_width = (inner_width - gap) / 2.0;
+        let button_y = panel_y + panel_height - padding - button_height;
+        let label_style = Self::text_style(28.0 * scale, 34.0 * scale);
+        let buttons = [
+            (
+                CONFIRM_BUTTON_ID,
+                "Yes",
+                create_danger_button_style(),
+                ConfirmAction::Confirmed,
+            ),
+            (
+                CANCEL_BUTTON_ID,
+                "No",
+                create_primary_button_style(),
+                ConfirmAction::Cancelled,
+            ),
+        ];
+        for (i, (id, label, style, action)) in buttons.into_iter().enumerate() {
+            let position = ButtonPosition::new(
+                panel_x + padding + i as f32 * (button_width + gap),
+                button_y,
+                button_width,
+                button_height,
+            )
+            .with_anchor(ButtonAnchor::TopLeft);
+            let style = ButtonStyle {
+                spacing: ButtonSpacing::Fixed(button_width, button_height),
+                text_style: TextStyle {
+                    color: style.text_style.color,
+                    ..label_style.clone()
+                },
+                ..style
+            };
+            if self.button_manager.buttons.contains_key(id) {
+                let result = self
+                    .button_manager
+                    .set_position(id, position)
+                    .and_then(|_| self.button_manager.set_style(id, style));
+                if let Err(e) = result {
+                    println!("Failed to lay out confirm dialog: {}", e);
+                }
+            } else {
+                self.button_manager.add_button(
+                    Button::new(id, label)
+                        .with_id_action(action)
+                        .with_style(style)
+                        .with_text_align(TextAlign::Center)
+                        .with_position(position),
+                );
+            }
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.button_manager.set_all_visible(visible);
+        let text_id = self.button_manager.text_id(MESSAGE_TEXT_ID);
+        let result = self
+            .button_manager
+            .text_renderer
+            .borrow_mut()
+            .set_visible(&text_id, visible);
+        if let Err(e) = result {
+            println!("Failed to update confirm dialog: {}", e);
+        }
+    }
+
+    /// Ask `message`; the answer comes back from `handle_input`
+    pub fn show_confirm(&mut self, message: &str) {
+        self.message = message.to_string();
+        self.visible = true;
+        self.create_layout();
+        self.set_visible(true);
+        self.button_manager.update_button_states();
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            self.visible = false;
+            self.set_visible(false);
+            self.button_manager.rectangle_renderer.clear_rectangles();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The question currently or last asked
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Feed `event` to the dialog. Returns the answer once the player gives
+    /// one, hiding the dialog.
+    pub fn handle_input(&mut self, event: &WindowEvent) -> Option<ConfirmAction> {
+        if !self.visible {
+            return None;
+        }
+
+        let mut answer = self.button_manager.handle_input(event).pop();
+        if let WindowEvent::KeyboardInput { event: key, .. } = event {
+            if key.state == ElementState::Pressed && key.logical_key == Key::Named(NamedKey::Escape)
+            {
+                answer = Some(ConfirmAction::Cancelled);
+            }
+        }
+        if answer.is_some() {
+            self.hide();
+        }
+        answer
+    }
+
+    /// Cursor for the current mouse position while the dialog is open
+    pub fn desired_cursor(&self) -> Option<CursorIcon> {
+        if !self.visible {
+            return None;
+        }
+        self.button_manager.desired_cursor()
+    }
+
+    pub fn resize(&mut self, queue: &Queue, resolution: Resolution) {
+        self.button_manager.resize(queue, resolution);
+        self.button_manager.window_size = PhysicalSize {
+            width: resolution.width,
+            height: resolution.height,
+        };
+        self.create_layout();
+        self.set_visible(self.visible);
+    }
+
+    /// Draw the dialog, dimming what's behind it, into a render pass the host
+    /// owns; see `ButtonManager::render_to`. Draws nothing while hidden.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        self.button_manager.render_to(
+            device,
+            queue,
+            surface_config,
+            render_pass,
+            Some(self.overlay_color),
+        )
+    }
+}
@@ -0,0 +1,188 @@
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::rectangle::{Rectangle, RectangleRenderer};
+use egui_wgpu::wgpu::{self, Device, Queue, RenderPass};
+
+/// A dashed guide line, placed as a fraction of the window so it survives resizes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guide {
+    /// Vertical line at `fraction * window width`
+    Vertical(f32),
+    /// Horizontal line at `fraction * window height`
+    Horizontal(f32),
+}
+
+/// Development-only guides drawn over the scene, independent of any menu
+pub struct DebugOverlay {
+    rectangle_renderer: RectangleRenderer,
+    guides: Vec<Guide>,
+    visible: bool,
+    /// Also draw lines at 1/3 and 2/3 of each axis
+    show_thirds: bool,
+    dash_length: f32,
+    dash_gap: f32,
+    line_width: f32,
+    color: [f32; 4],
+    window_width: f32,
+    window_height: f32,
+}
+
+impl DebugOverlay {
+    pub fn new(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            surface_format,
+            width,
+            height,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        config: &PipelineConfig,
+    ) -> Self {
+        let mut rectangle_renderer =
+            RectangleRenderer::new_with_config(device, surface_format, config);
+        rectangle_renderer.resize(width as f32, height as f32);
+
+        Self {
+            rectangle_renderer,
+            guides: Vec::new(),
+            visible: false,
+            show_thirds: false,
+            dash_length: 16.0,
+            dash_gap: 12.0,
+            line_width: 3.0,
+            color: [0.1, 1.0, 0.1, 0.85], // bright green, mostly opaque
+            window_width: width as f32,
+            window_height: height as f32,
+        }
+    }
+
+    /// Add a vertical guide at `fraction` of the window width (0.5 = center)
+    pub fn add_vertical_guide(&mut self, fraction: f32) {
+        self.guides.push(Guide::Vertical(fraction));
+    }
+
+    /// Add a horizontal guide at `fraction` of the window height (0.5 = center)
+    pub fn add_horizontal_guide(&mut self, fraction: f32) {
+        self.guides.push(Guide::Horizontal(fraction));
+    }
+
+    pub fn clear_guides(&mut self) {
+        self.guides.clear();
+    }
+
+    /// Toggle the rule-of-thirds grid, drawn at half the guide opacity
+    pub fn set_show_thirds(&mut self, show_thirds: bool) {
+        self.show_thirds = show_thirds;
+    }
+
+    /// Length of each dash and of the gap between dashes, in pixels
+    pub fn set_dash(&mut self, dash_length: f32, dash_gap: f32) {
+        self.dash_length = dash_length.max(1.0);
+        self.dash_gap = dash_gap.max(0.0);
+    }
+
+    /// Thickness of the guide lines, in pixels
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width.max(1.0);
+    }
+
+    /// Guide color as RGBA; alpha is the opacity
+    pub fn set_color(&mut self, color: [f32; 4]) {
+        self.color = color;
+    }
+
+    /// Change only the opacity of the guide color
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.color[3] = opacity.clamp(0.0, 1.0);
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.window_width = width as f32;
+        self.window_height = height as f32;
+        self.rectangle_renderer
+            .resize(self.window_width, self.window_height);
+    }
+
+    pub fn render(&mut self, device: &Device, queue: &Queue, render_pass: &mut RenderPass) {
+        if !self.visible {
+            return;
+        }
+
+        self.rectangle_renderer.clear_rectangles();
+
+        if self.show_thirds {
+            let [r, g, b, a] = self.color;
+            let thirds_color = [r, g, b, a * 0.5];
+            for fraction in [1.0 / 3.0, 2.0 / 3.0] {
+                self.add_dashes(Guide::Vertical(fraction), thirds_color);
+                self.add_dashes(Guide::Horizontal(fraction), thirds_color);
+            }
+        }
+        for guide in self.guides.clone() {
+            self.add_dashes(guide, self.color);
+        }
+
+        self.rectangle_renderer.render(device, queue, render_pass);
+    }
+
+    fn add_dashes(&mut self, guide: Guide, color: [f32; 4]) {
+        let (dash_length, dash_gap, line_width) =
+            (self.dash_length, self.dash_gap, self.line_width);
+        match guide {
+            Guide::Vertical(fraction) => {
+                let x = self.window_width * fraction;
+                let mut y = 0.0;
+                while y < self.window_height {
+                    let dash_h = dash_length.min(self.window_height - y);
+                    self.rectangle_renderer.add_rectangle(Rectangle::new(
+                        x - line_width / 2.0,
+                        y,
+                        line_width,
+                        dash_h,
+                        color,
+                    ));
+                    y += dash_length + dash_gap;
+                }
+            }
+            Guide::Horizontal(fraction) => {
+                let y = self.window_height * fraction;
+                let mut x = 0.0;
+                while x < self.window_width {
+                    let dash_w = dash_length.min(self.window_width - x);
+                    self.rectangle_renderer.add_rectangle(Rectangle::new(
+                        x,
+                        y - line_width / 2.0,
+                        dash_w,
+                        line_width,
+                        color,
+                    ));
+                    x += dash_length + dash_gap;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// A rectangular handle that moves a group of widgets while it is dragged,
+/// e.g. the title strip of a floating panel.
+///
+/// Add it to a `ButtonManager` with `add_draggable`; the manager starts a drag
+/// when the left button is pressed inside `region` (and not over a button),
+/// moves the members on every `CursorMoved`, and stops on release.
+#[derive(Debug, Clone)]
+pub struct Draggable {
+    pub id: String,
+    /// Area that starts a drag when pressed, as `(x, y, width, height)`
+    pub region: (f32, f32, f32, f32),
+    /// Ids of the buttons moved along with the region
+    pub buttons: Vec<String>,
+    /// Whether the manager's `container_rect` moves too
+    pub moves_container: bool,
+    /// Mouse position the last delta was measured from while dragging
+    drag_origin: Option<(f32, f32)>,
+}
+
+impl Draggable {
+    pub fn new(id: &str, x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            id: id.to_string(),
+            region: (x, y, width, height),
+            buttons: Vec::new(),
+            moves_container: false,
+            drag_origin: None,
+        }
+    }
+
+    /// Move the button `id` with the region
+    pub fn with_button(mut self, id: &str) -> Self {
+        self.buttons.push(id.to_string());
+        self
+    }
+
+    /// Move the manager's container rectangle with the region
+    pub fn with_container(mut self) -> Self {
+        self.moves_container = true;
+        self
+    }
+
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (region_x, region_y, width, height) = self.region;
+        x >= region_x && x <= region_x + width && y >= region_y && y <= region_y + height
+    }
+
+    /// Begin dragging if `(x, y)` is inside the region. Returns whether it started.
+    pub fn start_drag(&mut self, x: f32, y: f32) -> bool {
+        if self.contains_point(x, y) {
+            self.drag_origin = Some((x, y));
+        }
+        self.is_dragging()
+    }
+
+    /// Follow the mouse to `(x, y)`, returning how far the region moved.
+    /// `None` when not dragging.
+    pub fn drag_to(&mut self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let (origin_x, origin_y) = self.drag_origin?;
+        let (dx, dy) = (x - origin_x, y - origin_y);
+        self.drag_origin = Some((x, y));
+        self.region.0 += dx;
+        self.region.1 += dy;
+        Some((dx, dy))
+    }
+
+    pub fn stop_drag(&mut self) {
+        self.drag_origin = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag_origin.is_some()
+    }
+}
@@ -1,11 +1,13 @@
+use crate::ui::batch::BatchCache;
+use crate::ui::button::utils::rect_to_ndc;
+use crate::ui::pipeline::PipelineConfig;
 use egui_wgpu::wgpu::{
-    self, util::DeviceExt, BindGroup, BindGroupLayout, BufferUsages, ColorTargetState, ColorWrites,
-    Device, FragmentState, MultisampleState, PrimitiveState, RenderPass, RenderPipeline,
-    SamplerBindingType, ShaderStages, Texture, TextureFormat, TextureView, VertexAttribute,
-    VertexBufferLayout, VertexFormat, VertexState,
+    self, BindGroup, BindGroupLayout, ColorTargetState, ColorWrites, Device, FragmentState,
+    PrimitiveState, Queue, RenderPass, RenderPipeline, SamplerBindingType, ShaderStages, Texture,
+    TextureFormat, TextureView, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState,
 };
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::path::Path;
 
@@ -14,6 +16,13 @@ use std::path::Path;
 struct IconVertex {
     position: [f32; 2],
     uv: [f32; 2],
+    tint: [f32; 4],
+    // Icon size in pixels, for the rounded-rect mask
+    size: [f32; 2],
+    // Mask kind (0 = circle, 1 = rounded rect) and corner radius in pixels
+    mask: [f32; 2],
+    // Position inside the quad, 0..1 from the top-left, for the masks
+    local: [f32; 2],
 }
 
 impl IconVertex {
@@ -32,11 +41,100 @@ impl IconVertex {
                     shader_location: 1,
                     format: VertexFormat::Float32x2,
                 },
+                VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: VertexFormat::Float32x4,
+                },
+                VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: VertexFormat::Float32x2,
+                },
+                VertexAttribute {
+                    offset: mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: VertexFormat::Float32x2,
+                },
+                VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: VertexFormat::Float32x2,
+                },
             ],
         }
     }
 }
 
+/// How the texture is clipped at the icon's edges
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IconMask {
+    /// Circle inscribed in the icon, the look of the upgrade icons
+    #[default]
+    Circle,
+    /// Whole rectangle with corners rounded by the given radius in pixels
+    RoundedRect(f32),
+}
+
+/// How a texture whose aspect ratio differs from the icon's fills the icon
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IconFit {
+    /// Fill the icon exactly, distorting the texture
+    Stretch,
+    /// Fit the whole texture inside the icon, centered, leaving empty bands
+    #[default]
+    Contain,
+    /// Fill the icon, cropping the texture's overflowing edges
+    Cover,
+}
+
+impl IconFit {
+    /// Quad `(x, y, width, height)` and texture coordinates `(u0, v0, u1, v1)`
+    /// to draw a `texture_size` texture into the `(x, y, width, height)` box.
+    /// Without a known texture size it stretches.
+    pub fn place(
+        self,
+        (x, y, width, height): (f32, f32, f32, f32),
+        texture_size: Option<(u32, u32)>,
+    ) -> ((f32, f32, f32, f32), (f32, f32, f32, f32)) {
+        let full_uv = (0.0, 0.0, 1.0, 1.0);
+        let Some((texture_width, texture_height)) = texture_size else {
+            return ((x, y, width, height), full_uv);
+        };
+        if texture_width == 0 || texture_height == 0 || width <= 0.0 || height <= 0.0 {
+            return ((x, y, width, height), full_uv);
+        }
+        let (texture_width, texture_height) = (texture_width as f32, texture_height as f32);
+
+        match self {
+            IconFit::Stretch => ((x, y, width, height), full_uv),
+            IconFit::Contain => {
+                let scale = (width / texture_width).min(height / texture_height);
+                let (fit_width, fit_height) = (texture_width * scale, texture_height * scale);
+                (
+                    (
+                        x + (width - fit_width) / 2.0,
+                        y + (height - fit_height) / 2.0,
+                        fit_width,
+                        fit_height,
+                    ),
+                    full_uv,
+                )
+            }
+            IconFit::Cover => {
+                let scale = (width / texture_width).max(height / texture_height);
+                // Share of the texture that lands inside the icon on each axis
+                let u = width / (texture_width * scale);
+                let v = height / (texture_height * scale);
+                (
+                    (x, y, width, height),
+                    (0.5 - u / 2.0, 0.5 - v / 2.0, 0.5 + u / 2.0, 0.5 + v / 2.0),
+                )
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Icon {
     pub x: f32,
@@ -44,6 +142,10 @@ pub struct Icon {
     pub width: f32,
     pub height: f32,
     pub texture_id: String,
+    /// Multiplied with the sampled texture color
+    pub tint: [f32; 4],
+    pub mask: IconMask,
+    pub fit: IconFit,
 }
 
 impl Icon {
@@ -54,8 +156,26 @@ impl Icon {
             width,
             height,
             texture_id,
+            tint: [1.0, 1.0, 1.0, 1.0],
+            mask: IconMask::Circle,
+            fit: IconFit::default(),
         }
     }
+
+    pub fn with_tint(mut self, tint: [f32; 4]) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn with_mask(mut self, mask: IconMask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    pub fn with_fit(mut self, fit: IconFit) -> Self {
+        self.fit = fit;
+        self
+    }
 }
 
 pub struct IconRenderer {
@@ -63,15 +183,26 @@ pub struct IconRenderer {
     bind_group_layout: BindGroupLayout,
     icons: Vec<Icon>,
     textures: HashMap<String, (Texture, TextureView, BindGroup)>,
+    /// Pixel `(width, height)` of each loaded texture, see `texture_size`
+    texture_sizes: HashMap<String, (u32, u32)>,
+    /// Missing texture ids already reported by `render`
+    warned_missing: HashSet<String>,
     window_width: f32,
     window_height: f32,
-    cached_vertex_buffers: HashMap<String, wgpu::Buffer>,
-    cached_index_buffers: HashMap<String, wgpu::Buffer>,
-    cached_icon_counts: HashMap<String, usize>,
+    batch_cache: BatchCache,
 }
 
 impl IconRenderer {
     pub fn new(device: &Device, surface_format: TextureFormat) -> Self {
+        Self::new_with_config(device, surface_format, &PipelineConfig::default())
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        surface_format: TextureFormat,
+        config: &PipelineConfig,
+    ) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Icon Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/icon.wgsl").into()),
@@ -142,17 +273,14 @@ impl IconRenderer {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // With Y flipped the quads wind clockwise, same as rectangles
+                cull_mode: Some(wgpu::Face::Front),
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            depth_stencil: config.depth_stencil(),
+            multisample: config.multisample(),
             multiview: None,
             cache: None,
         });
@@ -162,11 +290,11 @@ impl IconRenderer {
             bind_group_layout,
             icons: Vec::new(),
             textures: HashMap::new(),
+            texture_sizes: HashMap::new(),
+            warned_missing: HashSet::new(),
             window_width: 1360.0,
             window_height: 768.0,
-            cached_vertex_buffers: HashMap::new(),
-            cached_index_buffers: HashMap::new(),
-            cached_icon_counts: HashMap::new(),
+            batch_cache: BatchCache::default(),
         }
     }
 
@@ -242,141 +370,150 @@ impl IconRenderer {
 
         self.textures
             .insert(texture_id.to_string(), (texture, view, bind_group));
+        self.texture_sizes
+            .insert(texture_id.to_string(), dimensions);
+        self.warned_missing.remove(texture_id);
 
         Ok(())
     }
 
+    /// Whether a texture has been loaded under `texture_id`
+    pub fn has_texture(&self, texture_id: &str) -> bool {
+        self.textures.contains_key(texture_id)
+    }
+
+    /// Pixel `(width, height)` of the texture loaded under `texture_id`
+    pub fn texture_size(&self, texture_id: &str) -> Option<(u32, u32)> {
+        self.texture_sizes.get(texture_id).copied()
+    }
+
+    /// An icon at `(x, y)` drawn at the texture's own pixel size
+    pub fn natural_icon(&self, x: f32, y: f32, texture_id: &str) -> Option<Icon> {
+        let (width, height) = self.texture_size(texture_id)?;
+        Some(Icon::new(
+            x,
+            y,
+            width as f32,
+            height as f32,
+            texture_id.to_string(),
+        ))
+    }
+
     pub fn add_icon(&mut self, icon: Icon) {
         self.icons.push(icon);
     }
 
     pub fn clear_icons(&mut self) {
+        // GPU buffers stay cached by content, so re-adding the same icons is free
         self.icons.clear();
-        // Clear cached buffers when icons are cleared
-        self.cached_vertex_buffers.clear();
-        self.cached_index_buffers.clear();
-        self.cached_icon_counts.clear();
     }
 
     pub fn resize(&mut self, width: f32, height: f32) {
         self.window_width = width;
         self.window_height = height;
-        // Clear cached buffers when window is resized
-        self.cached_vertex_buffers.clear();
-        self.cached_index_buffers.clear();
-        self.cached_icon_counts.clear();
     }
 
-    pub fn render(&mut self, device: &Device, render_pass: &mut RenderPass) {
+    /// Number of GPU buffer pairs allocated so far, useful for profiling
+    pub fn buffer_allocations(&self) -> usize {
+        self.batch_cache.allocations()
+    }
+
+    pub fn render(&mut self, device: &Device, queue: &Queue, render_pass: &mut RenderPass) {
         if self.icons.is_empty() {
             return;
         }
 
         render_pass.set_pipeline(&self.render_pipeline);
 
-        // Group icons by texture to minimize bind group changes
-        let mut icons_by_texture: HashMap<String, Vec<&Icon>> = HashMap::new();
+        // Batch consecutive icons sharing a texture; keeping insertion order
+        // lets button backgrounds stay underneath the icons drawn on them
+        let mut runs: Vec<(&str, Vec<&Icon>)> = Vec::new();
         for icon in &self.icons {
-            icons_by_texture
-                .entry(icon.texture_id.clone())
-                .or_insert_with(Vec::new)
-                .push(icon);
+            match runs.last_mut() {
+                Some((texture_id, icons)) if *texture_id == icon.texture_id => icons.push(icon),
+                _ => runs.push((&icon.texture_id, vec![icon])),
+            }
         }
 
-        for (texture_id, icons) in icons_by_texture {
-            if let Some((_, _, bind_group)) = self.textures.get(&texture_id) {
+        for (texture_id, icons) in runs {
+            if let Some((_, _, bind_group)) = self.textures.get(texture_id) {
                 render_pass.set_bind_group(0, bind_group, &[]);
 
-                // Check if we can reuse cached buffers for this texture
-                let cached_count = self.cached_icon_counts.get(&texture_id).unwrap_or(&0);
-                let need_new_buffers = *cached_count != icons.len();
-
-                if need_new_buffers {
-                    // Create vertices for all icons using this texture
-                    let mut all_vertices = Vec::new();
-                    let mut all_indices = Vec::new();
-
-                    for (icon_index, icon) in icons.iter().enumerate() {
-                        // Convert screen coordinates to normalized device coordinates
-                        let x = (icon.x / self.window_width) * 2.0 - 1.0;
-                        let y = (icon.y / self.window_height) * 2.0 - 1.0; // No Y flip needed
-                        let width = (icon.width / self.window_width) * 2.0;
-                        let height = (icon.height / self.window_height) * 2.0;
-
-                        // Create vertices for this icon
-                        let vertices = [
-                            // Top-left
-                            IconVertex {
-                                position: [x, y],
-                                uv: [0.0, 0.0],
-                            },
-                            // Top-right
-                            IconVertex {
-                                position: [x + width, y],
-                                uv: [1.0, 0.0],
-                            },
-                            // Bottom-right
-                            IconVertex {
-                                position: [x + width, y + height],
-                                uv: [1.0, 1.0],
-                            },
-                            // Bottom-left
-                            IconVertex {
-                                position: [x, y + height],
-                                uv: [0.0, 1.0],
-                            },
-                        ];
-
-                        // Add vertices to the batch
-                        all_vertices.extend_from_slice(&vertices);
-
-                        // Create indices for this icon (offset by the current vertex count)
-                        let base_index = (icon_index * 4) as u16;
-                        let indices = [
-                            base_index,
-                            base_index + 1,
-                            base_index + 2,
-                            base_index,
-                            base_index + 2,
-                            base_index + 3,
-                        ];
-                        all_indices.extend_from_slice(&indices);
-                    }
-
-                    // Create new vertex buffer for all icons using this texture
-                    let vertex_buffer =
-                        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Icon Vertex Buffer"),
-                            contents: bytemuck::cast_slice(&all_vertices),
-                            usage: BufferUsages::VERTEX,
-                        });
-
-                    // Create new index buffer for all icons using this texture
-                    let index_buffer =
-                        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Icon Index Buffer"),
-                            contents: bytemuck::cast_slice(&all_indices),
-                            usage: BufferUsages::INDEX,
-                        });
-
-                    // Cache the new buffers
-                    self.cached_vertex_buffers
-                        .insert(texture_id.clone(), vertex_buffer);
-                    self.cached_index_buffers
-                        .insert(texture_id.clone(), index_buffer);
-                    self.cached_icon_counts
-                        .insert(texture_id.clone(), icons.len());
+                // Create vertices for all icons using this texture
+                let mut all_vertices = Vec::new();
+                let mut all_indices = Vec::new();
+
+                for (icon_index, icon) in icons.iter().enumerate() {
+                    // Shrink or crop to the texture's aspect ratio
+                    let ((x, y, width, height), (u0, v0, u1, v1)) = icon.fit.place(
+                        (icon.x, icon.y, icon.width, icon.height),
+                        self.texture_size(texture_id),
+                    );
+
+                    // Convert the screen corners to normalized device coordinates,
+                    // the same way rectangles are, so icons line up with buttons
+                    let [[left, top], [right, bottom]] =
+                        rect_to_ndc(x, y, width, height, self.window_width, self.window_height);
+
+                    let tint = icon.tint;
+                    let size = [width, height];
+                    let mask = match icon.mask {
+                        IconMask::Circle => [0.0, 0.0],
+                        IconMask::RoundedRect(radius) => {
+                            [1.0, radius.clamp(0.0, width.min(height) / 2.0)]
+                        }
+                    };
+                    let vertex = |position: [f32; 2], local: [f32; 2]| IconVertex {
+                        position,
+                        uv: [u0 + (u1 - u0) * local[0], v0 + (v1 - v0) * local[1]],
+                        tint,
+                        size,
+                        mask,
+                        local,
+                    };
+
+                    // Create vertices for this icon
+                    let vertices = [
+                        // Top-left
+                        vertex([left, top], [0.0, 0.0]),
+                        // Top-right
+                        vertex([right, top], [1.0, 0.0]),
+                        // Bottom-right
+                        vertex([right, bottom], [1.0, 1.0]),
+                        // Bottom-left
+                        vertex([left, bottom], [0.0, 1.0]),
+                    ];
+
+                    // Add vertices to the batch
+                    all_vertices.extend_from_slice(&vertices);
+
+                    // Create indices for this icon (offset by the current vertex count)
+                    let base_index = (icon_index * 4) as u16;
+                    let indices = [
+                        base_index,
+                        base_index + 1,
+                        base_index + 2,
+                        base_index,
+                        base_index + 2,
+                        base_index + 3,
+                    ];
+                    all_indices.extend_from_slice(&indices);
                 }
 
-                // Use cached buffers
-                if let (Some(vertex_buffer), Some(index_buffer)) = (
-                    self.cached_vertex_buffers.get(&texture_id),
-                    self.cached_index_buffers.get(&texture_id),
-                ) {
-                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                    render_pass.draw_indexed(0..(icons.len() * 6) as u32, 0, 0..1);
-                }
+                // Reuse the cached buffers unless the geometry actually changed
+                let (vertex_buffer, index_buffer) = self.batch_cache.buffers(
+                    device,
+                    queue,
+                    "Icon",
+                    bytemuck::cast_slice(&all_vertices),
+                    bytemuck::cast_slice(&all_indices),
+                );
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..all_indices.len() as u32, 0, 0..1);
+            } else if self.warned_missing.insert(texture_id.to_string()) {
+                // Logged once, the icons are skipped every frame until it loads
+                println!("Warning: icon texture '{}' is not loaded", texture_id);
             }
         }
     }
@@ -0,0 +1,207 @@
+use crate::ui::button::utils::dpi_scale;
+use crate::ui::button::{
+    Button, ButtonAnchor, ButtonManager, ButtonPosition, ButtonSpacing, ButtonStyle, TextAlign,
+};
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::text::TextStyle;
+use egui_wgpu::wgpu::{self, Device, Queue};
+use winit::dpi::PhysicalSize;
+
+/// One button of a `MenuBuilder` menu
+#[derive(Debug, Clone)]
+pub struct MenuItem<A> {
+    pub id: String,
+    pub label: String,
+    pub style: ButtonStyle,
+    pub action: A,
+}
+
+/// A centered column of buttons, described once and laid out for any window
+/// size. Keep the builder after `build` and call `apply` again on resize.
+///
+/// Widths, heights and gaps are shares of the window size, grown by the DPI
+/// scale and then clamped to their pixel bounds.
+#[derive(Debug, Clone)]
+pub struct MenuBuilder<A> {
+    pub items: Vec<MenuItem<A>>,
+    /// Button width as a share of the window width
+    pub width: f32,
+    pub min_width: f32,
+    pub max_width: f32,
+    /// Button height as a share of the window height
+    pub height: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+    /// Space between buttons as a share of the window height
+    pub gap: f32,
+    pub min_gap: f32,
+    pub max_gap: f32,
+}
+
+impl<A> Default for MenuBuilder<A> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            width: 0.38,
+            min_width: 180.0,
+            max_width: 600.0,
+            height: 0.09,
+            min_height: 32.0,
+            max_height: 140.0,
+            gap: 0.015,
+            min_gap: 2.0,
+            max_gap: 24.0,
+        }
+    }
+}
+
+impl<A: Clone> MenuBuilder<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a button below the previous ones
+    pub fn item(mut self, id: &str, label: &str, style: ButtonStyle, action: A) -> Self {
+        self.items.push(MenuItem {
+            id: id.to_string(),
+            label: label.to_string(),
+            style,
+            action,
+        });
+        self
+    }
+
+    /// A new `ButtonManager` holding the menu's buttons, labeled with `text_style`
+    pub fn build(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        text_style: &TextStyle,
+    ) -> ButtonManager<A> {
+        self.build_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            text_style,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `build`, for passes with the attachments described by `config`
+    pub fn build_with_config(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        text_style: &TextStyle,
+        config: &PipelineConfig,
+    ) -> ButtonManager<A> {
+        let mut button_manager =
+            ButtonManager::new_with_config(device, queue, surface_format, window_size, config);
+        self.apply(&mut button_manager, text_style);
+        button_manager
+    }
+
+    /// `(button height, gap, top)` of the column centered in a window of
+    /// `window_size`
+    fn column(&self, window_size: PhysicalSize<u32>) -> (f32, f32, f32) {
+        let window_height = window_size.height as f32;
+        let scale = dpi_scale(window_height);
+
+        let button_height =
+            (window_height * self.height * scale).clamp(self.min_height, self.max_height);
+        let gap = (window_height * self.gap * scale).clamp(self.min_gap, self.max_gap);
+        let count = self.items.len() as f32;
+        let total_height = button_height * count + gap * (count - 1.0).max(0.0);
+        (button_height, gap, (window_height - total_height) / 2.0)
+    }
+
+    /// Lay the menu out for `button_manager`'s current window size, adding
+    /// any buttons it doesn't have yet. Other buttons are left alone.
+    pub fn apply(&self, button_manager: &mut ButtonManager<A>, text_style: &TextStyle) {
+        let window_width = button_manager.window_size.width as f32;
+        let scale = dpi_scale(button_manager.window_size.height as f32);
+        let (button_height, gap, start_y) = self.column(button_manager.window_size);
+        let center_x = window_width / 2.0;
+
+        let mut new_buttons = Vec::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let style = ButtonStyle {
+                text_style: text_style.clone(),
+                spacing: ButtonSpacing::Hbar(self.width * scale),
+                min_width: Some(self.min_width),
+                max_width: Some(self.max_width),
+                ..item.style.clone()
+            };
+            let button_width = style.hbar_width(window_width).unwrap_or_default();
+            let y = start_y + button_height / 2.0 + i as f32 * (button_height + gap);
+            let position = ButtonPosition::new(center_x, y, button_width, button_height)
+                .with_anchor(ButtonAnchor::Center);
+
+            // Existing labels are left alone; they may have been changed with `set_text`
+            if let Some(button) = button_manager.get_button_mut(&item.id) {
+                button.style = style;
+                button.position = position;
+            } else {
+                new_buttons.push(
+                    Button::new(&item.id, &item.label)
+                        .with_id_action(item.action.clone())
+                        .with_style(style)
+                        .with_text_align(TextAlign::Center)
+                        .with_position(position),
+                );
+            }
+        }
+
+        // Lays out the existing buttons too
+        button_manager.add_buttons(new_buttons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::{self, FORMAT, WINDOW_SIZE};
+
+    fn menu() -> MenuBuilder<u32> {
+        MenuBuilder::new()
+            .item("resume", "Resume", ButtonStyle::default(), 0)
+            .item("settings", "Settings", ButtonStyle::default(), 1)
+            .item("quit", "Quit", ButtonStyle::default(), 2)
+    }
+
+    #[test]
+    fn column_is_centered_with_clamped_gaps() {
+        // 1080p: 9% of the height per button, 1.5% between them
+        let (button_height, gap, top) = menu().column(WINDOW_SIZE);
+        assert!((button_height - 97.2).abs() < 0.01);
+        assert!((gap - 16.2).abs() < 0.01);
+        assert!((top - (1080.0 - 3.0 * 97.2 - 2.0 * 16.2) / 2.0).abs() < 0.01);
+
+        // A tiny window hits the lower bounds
+        let (button_height, gap, _top) = menu().column(PhysicalSize::new(320, 150));
+        assert_eq!((button_height, gap), (32.0, 2.0));
+    }
+
+    #[test]
+    fn built_buttons_are_centered_one_row_apart() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let menu = menu();
+        let button_manager =
+            menu.build(&device, &queue, FORMAT, WINDOW_SIZE, &TextStyle::default());
+        let (button_height, gap, top) = menu.column(WINDOW_SIZE);
+
+        for (i, id) in ["resume", "settings", "quit"].iter().enumerate() {
+            let position = &button_manager.buttons[*id].position;
+            let expected_y = top + button_height / 2.0 + i as f32 * (button_height + gap);
+            assert!((position.y - expected_y).abs() < 0.01);
+            assert_eq!(position.x, WINDOW_SIZE.width as f32 / 2.0);
+        }
+    }
+}
@@ -1,8 +1,26 @@
 // UI module - contains all user interface components
+pub mod animation;
+pub mod batch;
 pub mod button;
+pub mod clip;
+pub mod confirm;
+pub mod debug_overlay;
+pub mod drag;
 pub mod icon;
+pub mod menu;
+pub mod offscreen;
+pub mod pipeline;
+pub mod prelude;
+pub mod progress_bar;
 pub mod rectangle;
+pub mod ring;
+pub mod scaling;
+pub mod tabs;
+#[cfg(test)]
+pub mod test_support;
 pub mod text;
+pub mod text_input;
+pub mod tooltip;
 
 // Re-export commonly used items for convenience
 // These are available for external use if needed
@@ -0,0 +1,212 @@
+use egui_wgpu::wgpu::{self, Device, Queue, Texture, TextureFormat, TextureView};
+use image::RgbaImage;
+
+/// A texture the UI can render into instead of the window surface, then read
+/// back as an image, e.g. for screenshots or visual regression checks
+pub struct OffscreenTarget {
+    texture: Texture,
+    view: TextureView,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl OffscreenTarget {
+    /// `format` must match the format the renderers were created with
+    pub fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen render target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            texture,
+            view,
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// View to use as the color attachment in place of the surface view
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    /// Copy the texture to the CPU, blocking until the GPU has finished.
+    /// Only 8-bit RGBA and BGRA formats are supported.
+    pub fn read_to_image(&self, device: &Device, queue: &Queue) -> Result<RgbaImage, String> {
+        let swap_red_blue = match self.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            other => return Err(format!("Unsupported offscreen format {:?}", other)),
+        };
+
+        let padded_bytes_per_row = padded_bytes_per_row(self.width);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen readback buffer"),
+            size: (padded_bytes_per_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| format!("Offscreen readback was dropped: {}", e))?
+            .map_err(|e| format!("Failed to map offscreen buffer: {}", e))?;
+
+        let mut pixels = unpad_rows(&slice.get_mapped_range(), self.width, self.height);
+        buffer.unmap();
+
+        if swap_red_blue {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(self.width, self.height, pixels)
+            .ok_or_else(|| "Offscreen pixel data has the wrong size".to_string())
+    }
+}
+
+/// Bytes per row of an RGBA copy `width` pixels wide, padded to the copy
+/// alignment wgpu requires for buffer rows
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (4 * width).div_ceil(alignment) * alignment
+}
+
+/// Tightly packed RGBA pixels from a copy buffer padded per row
+fn unpad_rows(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let unpadded_bytes_per_row = (4 * width) as usize;
+    let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+    for row in data.chunks(padded_bytes_per_row(width) as usize) {
+        pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause_menu::PauseMenu;
+    use crate::ui::test_support::{self, FORMAT};
+    use winit::dpi::PhysicalSize;
+
+    #[test]
+    fn rows_are_padded_to_the_copy_alignment() {
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+        assert_eq!(padded_bytes_per_row(1), 256);
+    }
+
+    #[test]
+    fn unpadding_drops_the_row_padding() {
+        // 3 pixels wide: 12 bytes of pixels followed by 244 bytes of padding
+        let (width, height) = (3, 2);
+        let padded = padded_bytes_per_row(width) as usize;
+        let mut data = vec![0xAA; padded * height as usize];
+        for (row, bytes) in data.chunks_mut(padded).enumerate() {
+            for (i, byte) in bytes[..12].iter_mut().enumerate() {
+                *byte = (row * 12 + i) as u8;
+            }
+        }
+
+        let pixels = unpad_rows(&data, width, height);
+        assert_eq!(pixels, (0..24).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn pause_menu_renders_offscreen() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let (width, height) = (320, 240);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: FORMAT,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+        let mut pause_menu =
+            PauseMenu::new(&device, &queue, FORMAT, PhysicalSize::new(width, height));
+        pause_menu.show(false);
+
+        let target = OffscreenTarget::new(&device, width, height, FORMAT);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen test encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                label: Some("Offscreen test render pass"),
+                occlusion_query_set: None,
+            });
+            pause_menu
+                .render_to(&device, &queue, &surface_config, &mut render_pass)
+                .unwrap();
+        }
+        queue.submit(Some(encoder.finish()));
+
+        let image = target.read_to_image(&device, &queue).unwrap();
+        assert_eq!(image.dimensions(), (width, height));
+        assert!(image.pixels().any(|pixel| pixel.0 != [255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,132 @@
+use egui_wgpu::wgpu::{
+    self, Adapter, DepthStencilState, Device, MultisampleState, TextureFormat, TextureView,
+};
+
+/// Render target settings the UI pipelines are built for, given to the
+/// renderers at construction. The default draws into a plain color target,
+/// as the game's own passes do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipelineConfig {
+    /// Format of the depth attachment of the passes the UI draws into, when
+    /// overlaying a depth-tested 3D scene. The UI ignores the depth buffer
+    /// and never writes it, so it always lands on top of the scene.
+    pub depth_format: Option<TextureFormat>,
+    /// MSAA samples per pixel of the color target: 1 (off), 2, 4 or 8.
+    /// Above 1 the passes must draw into a `MultisampleTarget` of the same count.
+    pub sample_count: u32,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            depth_format: None,
+            sample_count: 1,
+        }
+    }
+}
+
+impl PipelineConfig {
+    pub fn with_depth_format(mut self, format: TextureFormat) -> Self {
+        self.depth_format = Some(format);
+        self
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn multisample(&self) -> MultisampleState {
+        MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+
+    /// Always-pass, no-write depth state for `depth_format`, if any
+    pub fn depth_stencil(&self) -> Option<DepthStencilState> {
+        self.depth_format.map(|format| DepthStencilState {
+            format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        })
+    }
+}
+
+/// The highest sample count up to `requested` that `device` can render
+/// `format` with, falling back to 1. Counts other than 1 and 4 also need the
+/// device to have `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES` enabled.
+pub fn supported_sample_count(
+    adapter: &Adapter,
+    device: &Device,
+    format: TextureFormat,
+    requested: u32,
+) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    let adapter_specific = device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+    [8, 4, 2]
+        .into_iter()
+        .filter(|&count| count <= requested)
+        .find(|&count| flags.sample_count_supported(count) && (count == 4 || adapter_specific))
+        .unwrap_or(1)
+}
+
+/// Multisampled color texture the passes draw into when MSAA is on, resolved
+/// into the real target (surface or offscreen view) at the end of each pass.
+/// Recreate it when the surface is resized.
+pub struct MultisampleTarget {
+    view: TextureView,
+}
+
+impl MultisampleTarget {
+    pub fn new(
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multisample render target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { view }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    /// Color attachment drawing into this texture and resolving into `resolve_target`
+    pub fn attachment<'a>(
+        &'a self,
+        resolve_target: &'a TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        wgpu::RenderPassColorAttachment {
+            view: &self.view,
+            resolve_target: Some(resolve_target),
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! The types most UI code needs, for a single glob import:
+//! `use crate::ui::prelude::*;`
+
+// Public API for code building on the UI; not every re-export is used in-crate
+#![allow(unused_imports)]
+
+pub use crate::ui::button::{
+    Button, ButtonAnchor, ButtonManager, ButtonPosition, ButtonSpacing, ButtonStyle, Padding,
+    TextAlign, VerticalAlign,
+};
+pub use crate::ui::clip::ClipRegion;
+pub use crate::ui::drag::Draggable;
+pub use crate::ui::icon::{Icon, IconFit, IconRenderer};
+pub use crate::ui::pipeline::PipelineConfig;
+pub use crate::ui::rectangle::{Rectangle, RectangleRenderer};
+pub use crate::ui::text::{TextPosition, TextRenderer, TextStyle};
+pub use crate::ui::text_input::TextInput;
+pub use crate::ui::tooltip::TooltipPlacement;
@@ -0,0 +1,119 @@
+use crate::ui::button::ButtonPosition;
+use crate::ui::rectangle::Rectangle;
+use crate::ui::text::{TextPosition, TextStyle};
+
+/// A horizontal bar filled from the left by `value` (0.0..=1.0), e.g. for
+/// loading progress or the fraction of time remaining.
+/// Drawn by `ButtonManager` together with its buttons.
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    pub id: String,
+    pub value: f32,
+    pub position: ButtonPosition,
+    pub background_color: [f32; 4],
+    pub fill_color: [f32; 4],
+    pub corner_radius: f32,
+    pub label: Option<String>,
+    pub label_style: TextStyle,
+    pub label_id: String,
+    pub visible: bool,
+}
+
+impl ProgressBar {
+    pub fn new(id: &str, position: ButtonPosition) -> Self {
+        Self {
+            id: id.to_string(),
+            value: 0.0,
+            position,
+            background_color: [0.12, 0.16, 0.23, 0.9], // slate-800
+            fill_color: [0.13, 0.77, 0.37, 1.0],       // green-500
+            corner_radius: 6.0,
+            label: None,
+            label_style: TextStyle::default(),
+            label_id: format!("progress_{}", id),
+            visible: true,
+        }
+    }
+
+    pub fn with_value(mut self, value: f32) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn with_colors(mut self, background_color: [f32; 4], fill_color: [f32; 4]) -> Self {
+        self.background_color = background_color;
+        self.fill_color = fill_color;
+        self
+    }
+
+    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    /// Text drawn centered over the bar
+    pub fn with_label(mut self, label: &str, style: TextStyle) -> Self {
+        self.label = Some(label.to_string());
+        self.label_style = style;
+        self
+    }
+
+    /// Set the filled fraction, clamped to 0.0..=1.0
+    pub fn set_value(&mut self, value: f32) {
+        self.value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+    }
+
+    /// Where a label measuring `text_width` x `text_height` goes: centered
+    /// over the bar and wrapped to its size
+    pub fn label_position(&self, text_width: f32, text_height: f32) -> TextPosition {
+        let (x, y) = self.position.calculate_actual_position();
+        TextPosition {
+            x: x + (self.position.width - text_width) / 2.0,
+            y: y + (self.position.height - text_height) / 2.0,
+            max_width: Some(self.position.width),
+            max_height: Some(self.position.height),
+        }
+    }
+
+    /// Background and fill rectangles; the fill is omitted while empty
+    pub fn rectangles(&self) -> Vec<Rectangle> {
+        let (x, y) = self.position.calculate_actual_position();
+        let (width, height) = (self.position.width, self.position.height);
+
+        let mut rectangles = vec![Rectangle::new(x, y, width, height, self.background_color)
+            .with_corner_radius(self.corner_radius)];
+
+        let fill_width = width * self.value;
+        if fill_width > 0.0 {
+            rectangles.push(
+                Rectangle::new(x, y, fill_width, height, self.fill_color)
+                    // Keep the rounding from exceeding a narrow fill
+                    .with_corner_radius(self.corner_radius.min(fill_width / 2.0)),
+            );
+        }
+        rectangles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_follows_the_bar() {
+        let mut bar = ProgressBar::new("load", ButtonPosition::new(100.0, 200.0, 400.0, 20.0));
+        let position = bar.label_position(100.0, 16.0);
+        assert_eq!((position.x, position.y), (250.0, 202.0));
+        assert_eq!(position.max_width, Some(400.0));
+
+        bar.position = ButtonPosition::new(50.0, 100.0, 800.0, 40.0);
+        let position = bar.label_position(100.0, 16.0);
+        assert_eq!((position.x, position.y), (400.0, 112.0));
+        assert_eq!(position.max_width, Some(800.0));
+        assert_eq!(position.max_height, Some(40.0));
+    }
+}
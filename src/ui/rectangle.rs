@@ -1,7 +1,9 @@
+use crate::ui::batch::BatchCache;
+use crate::ui::button::utils::rect_to_ndc;
+use crate::ui::pipeline::PipelineConfig;
 use egui_wgpu::wgpu::{
-    self, util::DeviceExt, BlendState, BufferUsages, ColorTargetState, ColorWrites, Device,
-    FragmentState, MultisampleState, PrimitiveState, RenderPass, RenderPipeline, VertexAttribute,
-    VertexBufferLayout, VertexFormat, VertexState,
+    self, BlendState, ColorTargetState, ColorWrites, Device, FragmentState, PrimitiveState, Queue,
+    RenderPass, RenderPipeline, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState,
 };
 use std::mem;
 
@@ -93,6 +95,14 @@ impl Rectangle {
         self.corner_radius = radius;
         self
     }
+
+    /// Corner radius as drawn: never more than half the smaller side, so an
+    /// oversized radius gives a pill shape instead of a distorted one
+    pub fn effective_corner_radius(&self) -> f32 {
+        self.corner_radius
+            .min(self.width.min(self.height) / 2.0)
+            .max(0.0)
+    }
 }
 
 pub struct RectangleRenderer {
@@ -100,13 +110,20 @@ pub struct RectangleRenderer {
     rectangles: Vec<Rectangle>,
     window_width: f32,
     window_height: f32,
-    cached_vertex_buffer: Option<wgpu::Buffer>,
-    cached_index_buffer: Option<wgpu::Buffer>,
-    cached_rectangle_count: usize,
+    batch_cache: BatchCache,
 }
 
 impl RectangleRenderer {
     pub fn new(device: &Device, surface_format: wgpu::TextureFormat) -> Self {
+        Self::new_with_config(device, surface_format, &PipelineConfig::default())
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        config: &PipelineConfig,
+    ) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Rectangle Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/rectangle.wgsl").into()),
@@ -147,12 +164,8 @@ impl RectangleRenderer {
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            depth_stencil: config.depth_stencil(),
+            multisample: config.multisample(),
             multiview: None,
             cache: None,
         });
@@ -162,9 +175,7 @@ impl RectangleRenderer {
             rectangles: Vec::new(),
             window_width: 1360.0,
             window_height: 768.0,
-            cached_vertex_buffer: None,
-            cached_index_buffer: None,
-            cached_rectangle_count: 0,
+            batch_cache: BatchCache::default(),
         }
     }
 
@@ -173,128 +184,132 @@ impl RectangleRenderer {
     }
 
     pub fn clear_rectangles(&mut self) {
+        // GPU buffers stay cached by content, so re-adding the same rectangles is free
         self.rectangles.clear();
-        // Clear cached buffers when rectangles are cleared
-        self.cached_vertex_buffer = None;
-        self.cached_index_buffer = None;
-        self.cached_rectangle_count = 0;
     }
 
     pub fn resize(&mut self, width: f32, height: f32) {
         self.window_width = width;
         self.window_height = height;
-        // Clear cached buffers when window is resized
-        self.cached_vertex_buffer = None;
-        self.cached_index_buffer = None;
-        self.cached_rectangle_count = 0;
     }
 
-    pub fn render(&mut self, device: &Device, render_pass: &mut RenderPass) {
+    /// Number of GPU buffer pairs allocated so far, useful for profiling
+    pub fn buffer_allocations(&self) -> usize {
+        self.batch_cache.allocations()
+    }
+
+    pub fn render(&mut self, device: &Device, queue: &Queue, render_pass: &mut RenderPass) {
         if self.rectangles.is_empty() {
             return;
         }
 
         render_pass.set_pipeline(&self.render_pipeline);
 
-        // Check if we can reuse cached buffers
-        let need_new_buffers = self.cached_rectangle_count != self.rectangles.len();
-
-        if need_new_buffers {
-            // Create all vertices for all rectangles in one batch
-            let mut all_vertices = Vec::new();
-            let mut all_indices = Vec::new();
+        // Create all vertices for all rectangles in one batch
+        let mut all_vertices = Vec::new();
+        let mut all_indices = Vec::new();
 
-            for (rect_index, rectangle) in self.rectangles.iter().enumerate() {
-                // Convert screen coordinates to normalized device coordinates
-                // Note: Y-axis is flipped in screen coordinates (0,0 is top-left)
-                let x = (rectangle.x / self.window_width) * 2.0 - 1.0;
-                let y = 1.0 - (rectangle.y / self.window_height) * 2.0; // Flip Y-axis
-                let width = (rectangle.width / self.window_width) * 2.0;
-                let height = -(rectangle.height / self.window_height) * 2.0; // Negative because Y is flipped
+        for (rect_index, rectangle) in self.rectangles.iter().enumerate() {
+            // Convert the screen corners to normalized device coordinates
+            let [[left, top], [right, bottom]] = rect_to_ndc(
+                rectangle.x,
+                rectangle.y,
+                rectangle.width,
+                rectangle.height,
+                self.window_width,
+                self.window_height,
+            );
 
-                // Create vertices for this rectangle
-                let vertices = [
-                    // Top-left
-                    Vertex {
-                        position: [x, y],
-                        color: rectangle.color,
-                        uv: [0.0, 0.0],
-                        rect_size: [rectangle.width, rectangle.height],
-                        corner_radius: rectangle.corner_radius,
-                        _padding: 0.0,
-                    },
-                    // Top-right
-                    Vertex {
-                        position: [x + width, y],
-                        color: rectangle.color,
-                        uv: [rectangle.width, 0.0],
-                        rect_size: [rectangle.width, rectangle.height],
-                        corner_radius: rectangle.corner_radius,
-                        _padding: 0.0,
-                    },
-                    // Bottom-right
-                    Vertex {
-                        position: [x + width, y + height],
-                        color: rectangle.color,
-                        uv: [rectangle.width, rectangle.height],
-                        rect_size: [rectangle.width, rectangle.height],
-                        corner_radius: rectangle.corner_radius,
-                        _padding: 0.0,
-                    },
-                    // Bottom-left
-                    Vertex {
-                        position: [x, y + height],
-                        color: rectangle.color,
-                        uv: [0.0, rectangle.height],
-                        rect_size: [rectangle.width, rectangle.height],
-                        corner_radius: rectangle.corner_radius,
-                        _padding: 0.0,
-                    },
-                ];
-
-                // Add vertices to the batch
-                all_vertices.extend_from_slice(&vertices);
+            // Create vertices for this rectangle
+            let corner_radius = rectangle.effective_corner_radius();
+            let vertices = [
+                // Top-left
+                Vertex {
+                    position: [left, top],
+                    color: rectangle.color,
+                    uv: [0.0, 0.0],
+                    rect_size: [rectangle.width, rectangle.height],
+                    corner_radius,
+                    _padding: 0.0,
+                },
+                // Top-right
+                Vertex {
+                    position: [right, top],
+                    color: rectangle.color,
+                    uv: [rectangle.width, 0.0],
+                    rect_size: [rectangle.width, rectangle.height],
+                    corner_radius,
+                    _padding: 0.0,
+                },
+                // Bottom-right
+                Vertex {
+                    position: [right, bottom],
+                    color: rectangle.color,
+                    uv: [rectangle.width, rectangle.height],
+                    rect_size: [rectangle.width, rectangle.height],
+                    corner_radius,
+                    _padding: 0.0,
+                },
+                // Bottom-left
+                Vertex {
+                    position: [left, bottom],
+                    color: rectangle.color,
+                    uv: [0.0, rectangle.height],
+                    rect_size: [rectangle.width, rectangle.height],
+                    corner_radius,
+                    _padding: 0.0,
+                },
+            ];
 
-                // Create indices for this rectangle (offset by the current vertex count)
-                let base_index = (rect_index * 4) as u16;
-                let indices = [
-                    base_index,
-                    base_index + 1,
-                    base_index + 2,
-                    base_index,
-                    base_index + 2,
-                    base_index + 3,
-                ];
-                all_indices.extend_from_slice(&indices);
-            }
+            // Add vertices to the batch
+            all_vertices.extend_from_slice(&vertices);
 
-            // Create new vertex buffer for all rectangles
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Rectangle Vertex Buffer"),
-                contents: bytemuck::cast_slice(&all_vertices),
-                usage: BufferUsages::VERTEX,
-            });
+            // Create indices for this rectangle (offset by the current vertex count)
+            let base_index = (rect_index * 4) as u16;
+            let indices = [
+                base_index,
+                base_index + 1,
+                base_index + 2,
+                base_index,
+                base_index + 2,
+                base_index + 3,
+            ];
+            all_indices.extend_from_slice(&indices);
+        }
 
-            // Create new index buffer for all rectangles
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Rectangle Index Buffer"),
-                contents: bytemuck::cast_slice(&all_indices),
-                usage: BufferUsages::INDEX,
-            });
+        // Reuse the cached buffers unless the geometry actually changed
+        let (vertex_buffer, index_buffer) = self.batch_cache.buffers(
+            device,
+            queue,
+            "Rectangle",
+            bytemuck::cast_slice(&all_vertices),
+            bytemuck::cast_slice(&all_indices),
+        );
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..all_indices.len() as u32, 0, 0..1);
+    }
+}
 
-            // Cache the new buffers
-            self.cached_vertex_buffer = Some(vertex_buffer);
-            self.cached_index_buffer = Some(index_buffer);
-            self.cached_rectangle_count = self.rectangles.len();
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Use cached buffers
-        if let (Some(vertex_buffer), Some(index_buffer)) =
-            (&self.cached_vertex_buffer, &self.cached_index_buffer)
-        {
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..(self.rectangles.len() * 6) as u32, 0, 0..1);
-        }
+    #[test]
+    fn corner_radius_is_clamped_to_half_the_shorter_side() {
+        let rect = Rectangle::new(0.0, 0.0, 200.0, 40.0, [1.0; 4]);
+        assert_eq!(
+            rect.clone()
+                .with_corner_radius(100.0)
+                .effective_corner_radius(),
+            20.0
+        );
+        assert_eq!(
+            rect.clone()
+                .with_corner_radius(8.0)
+                .effective_corner_radius(),
+            8.0
+        );
+        assert_eq!(rect.with_corner_radius(-5.0).effective_corner_radius(), 0.0);
     }
 }
@@ -0,0 +1,261 @@
+use crate::ui::batch::BatchCache;
+use crate::ui::button::utils::screen_to_ndc;
+use crate::ui::pipeline::PipelineConfig;
+use egui_wgpu::wgpu::{
+    self, BlendState, ColorTargetState, ColorWrites, Device, FragmentState, PrimitiveState, Queue,
+    RenderPass, RenderPipeline, VertexAttribute, VertexBufferLayout, VertexState,
+};
+use std::f32::consts::TAU;
+use std::mem;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+    // Pixel offset from the ring center
+    local: [f32; 2],
+    // Outer radius, thickness, start angle, sweep angle
+    params: [f32; 4],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x4,
+        2 => Float32x2,
+        3 => Float32x4,
+    ];
+
+    fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// A circular arc filling clockwise from `start_angle`, e.g. a countdown indicator
+#[derive(Debug, Clone)]
+pub struct Ring {
+    pub center_x: f32,
+    pub center_y: f32,
+    /// Outer radius in pixels
+    pub radius: f32,
+    pub thickness: f32,
+    /// Radians clockwise from 12 o'clock
+    pub start_angle: f32,
+    /// Arc length in radians when `fraction` is 1.0; TAU is a full circle
+    pub sweep_angle: f32,
+    /// Filled part of the sweep, 0.0..=1.0
+    pub fraction: f32,
+    pub color: [f32; 4],
+    /// Drawn under the filled arc across the whole sweep
+    pub track_color: Option<[f32; 4]>,
+}
+
+impl Ring {
+    pub fn new(center_x: f32, center_y: f32, radius: f32, thickness: f32) -> Self {
+        Self {
+            center_x,
+            center_y,
+            radius,
+            thickness,
+            start_angle: 0.0,
+            sweep_angle: TAU,
+            fraction: 1.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            track_color: Some([1.0, 1.0, 1.0, 0.15]),
+        }
+    }
+
+    pub fn with_angles(mut self, start_angle: f32, sweep_angle: f32) -> Self {
+        self.start_angle = start_angle;
+        self.sweep_angle = sweep_angle.clamp(0.0, TAU);
+        self
+    }
+
+    pub fn with_fraction(mut self, fraction: f32) -> Self {
+        self.fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_track_color(mut self, track_color: Option<[f32; 4]>) -> Self {
+        self.track_color = track_color;
+        self
+    }
+}
+
+pub struct RingRenderer {
+    render_pipeline: RenderPipeline,
+    rings: Vec<Ring>,
+    window_width: f32,
+    window_height: f32,
+    batch_cache: BatchCache,
+}
+
+impl RingRenderer {
+    pub fn new(device: &Device, surface_format: wgpu::TextureFormat) -> Self {
+        Self::new_with_config(device, surface_format, &PipelineConfig::default())
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        config: &PipelineConfig,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ring Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/ring.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Ring Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ring Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(ColorTargetState {
+                    format: surface_format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: config.depth_stencil(),
+            multisample: config.multisample(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            render_pipeline,
+            rings: Vec::new(),
+            window_width: 1360.0,
+            window_height: 768.0,
+            batch_cache: BatchCache::default(),
+        }
+    }
+
+    pub fn add_ring(&mut self, ring: Ring) {
+        self.rings.push(ring);
+    }
+
+    pub fn clear_rings(&mut self) {
+        self.rings.clear();
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.window_width = width;
+        self.window_height = height;
+    }
+
+    pub fn render(&mut self, device: &Device, queue: &Queue, render_pass: &mut RenderPass) {
+        if self.rings.is_empty() {
+            return;
+        }
+
+        let mut all_vertices: Vec<Vertex> = Vec::new();
+        let mut all_indices: Vec<u16> = Vec::new();
+
+        for ring in &self.rings {
+            if let Some(track_color) = ring.track_color {
+                self.push_quad(&mut all_vertices, &mut all_indices, ring, track_color, 1.0);
+            }
+            if ring.fraction > 0.0 {
+                self.push_quad(
+                    &mut all_vertices,
+                    &mut all_indices,
+                    ring,
+                    ring.color,
+                    ring.fraction,
+                );
+            }
+        }
+
+        if all_indices.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        let (vertex_buffer, index_buffer) = self.batch_cache.buffers(
+            device,
+            queue,
+            "Ring",
+            bytemuck::cast_slice(&all_vertices),
+            bytemuck::cast_slice(&all_indices),
+        );
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..all_indices.len() as u32, 0, 0..1);
+    }
+
+    /// Append a quad covering the ring's bounding square, drawing `fraction` of its sweep
+    fn push_quad(
+        &self,
+        vertices: &mut Vec<Vertex>,
+        indices: &mut Vec<u16>,
+        ring: &Ring,
+        color: [f32; 4],
+        fraction: f32,
+    ) {
+        // One extra pixel so the anti-aliased outer edge isn't clipped
+        let extent = ring.radius + 1.0;
+        let params = [
+            ring.radius,
+            ring.thickness,
+            ring.start_angle,
+            ring.sweep_angle * fraction,
+        ];
+
+        let base_index = vertices.len() as u16;
+        for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let local = [dx * extent, dy * extent];
+            let screen_x = ring.center_x + local[0];
+            let screen_y = ring.center_y + local[1];
+            vertices.push(Vertex {
+                position: screen_to_ndc(screen_x, screen_y, self.window_width, self.window_height),
+                color,
+                local,
+                params,
+            });
+        }
+        indices.extend_from_slice(&[
+            base_index,
+            base_index + 1,
+            base_index + 2,
+            base_index,
+            base_index + 2,
+            base_index + 3,
+        ]);
+    }
+}
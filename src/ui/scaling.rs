@@ -0,0 +1,63 @@
+/// Limits applied to a font size and line height after scaling them to the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontBounds {
+    pub min_size: f32,
+    pub max_size: f32,
+    pub min_line_height: f32,
+    pub max_line_height: f32,
+}
+
+impl FontBounds {
+    pub const fn new(
+        min_size: f32,
+        max_size: f32,
+        min_line_height: f32,
+        max_line_height: f32,
+    ) -> Self {
+        Self {
+            min_size,
+            max_size,
+            min_line_height,
+            max_line_height,
+        }
+    }
+
+    pub fn clamp_size(&self, font_size: f32) -> f32 {
+        font_size.clamp(self.min_size, self.max_size.max(self.min_size))
+    }
+
+    pub fn clamp_line_height(&self, line_height: f32) -> f32 {
+        line_height.clamp(
+            self.min_line_height,
+            self.max_line_height.max(self.min_line_height),
+        )
+    }
+}
+
+/// Font size bounds for text that scales with the window. Widen them for very
+/// small or very large viewports; the defaults suit roughly 720p to 4K.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingConfig {
+    /// Pause menu button labels
+    pub menu: FontBounds,
+    /// Countdown timer at the top of the screen
+    pub timer: FontBounds,
+    /// Score and level labels
+    pub hud_label: FontBounds,
+    /// "Game Over!" heading
+    pub title: FontBounds,
+    /// Line under the game over heading
+    pub subtitle: FontBounds,
+}
+
+impl Default for ScalingConfig {
+    fn default() -> Self {
+        Self {
+            menu: FontBounds::new(16.0, 48.0, 24.0, 60.0),
+            timer: FontBounds::new(32.0, 180.0, 36.0, 220.0),
+            hud_label: FontBounds::new(16.0, 48.0, 20.0, 60.0),
+            title: FontBounds::new(48.0, 240.0, 60.0, 300.0),
+            subtitle: FontBounds::new(16.0, 120.0, 20.0, 156.0),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::ui::button::{
+    Button, ButtonAnchor, ButtonManager, ButtonPosition, ButtonSpacing, ButtonStyle, TextAlign,
+};
+
+/// One page of a `TabBar`: its button and the widgets shown while it is active
+#[derive(Debug, Clone)]
+pub struct Tab {
+    /// Id of the tab's button
+    pub id: String,
+    pub label: String,
+    /// Ids of the buttons shown and enabled only while this tab is active
+    pub children: Vec<String>,
+}
+
+/// A row of buttons of which exactly one is active, switching which group of
+/// child buttons is visible, e.g. Video / Audio / Controls in a settings menu.
+///
+/// The tab buttons live in a `ButtonManager` like any other; feed its clicks
+/// back through `handle_clicks` after `ButtonManager::handle_input`.
+#[derive(Debug, Clone)]
+pub struct TabBar {
+    pub tabs: Vec<Tab>,
+    /// Style of the inactive tabs
+    pub style: ButtonStyle,
+    /// Style of the active tab; by default the inactive style drawn with its
+    /// pressed color, so the text color must match `style`'s
+    pub active_style: ButtonStyle,
+    /// Space between neighbouring tabs in pixels
+    pub gap: f32,
+    active: usize,
+}
+
+impl TabBar {
+    pub fn new(style: ButtonStyle) -> Self {
+        let active_style = ButtonStyle {
+            background_color: style.pressed_color,
+            hover_color: style.pressed_color,
+            border_color: style.text_style.color,
+            ..style.clone()
+        };
+        Self {
+            tabs: Vec::new(),
+            style,
+            active_style,
+            gap: 4.0,
+            active: 0,
+        }
+    }
+
+    /// Append a tab to the right of the previous ones
+    pub fn with_tab(mut self, id: &str, label: &str, children: &[&str]) -> Self {
+        self.tabs.push(Tab {
+            id: id.to_string(),
+            label: label.to_string(),
+            children: children.iter().map(|child| child.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn with_active_style(mut self, style: ButtonStyle) -> Self {
+        self.active_style = style;
+        self
+    }
+
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Index of the active tab
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Id of the active tab, `None` if there are no tabs
+    pub fn active_id(&self) -> Option<&str> {
+        self.tabs.get(self.active).map(|tab| tab.id.as_str())
+    }
+
+    /// Add the tab buttons to `button_manager` as a row of `tab_width` by
+    /// `tab_height` buttons starting at `(x, y)`, or move the ones already there.
+    /// The children must already be in the manager; only the active tab's stay visible.
+    pub fn layout<A: Clone>(
+        &self,
+        button_manager: &mut ButtonManager<A>,
+        x: f32,
+        y: f32,
+        tab_width: f32,
+        tab_height: f32,
+    ) {
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let position = ButtonPosition::new(
+                x + i as f32 * (tab_width + self.gap),
+                y,
+                tab_width,
+                tab_height,
+            )
+            .with_anchor(ButtonAnchor::TopLeft);
+
+            if let Some(button) = button_manager.get_button_mut(&tab.id) {
+                button.position = position;
+                button.style.spacing = ButtonSpacing::Fixed(tab_width, tab_height);
+            } else {
+                button_manager.add_button(
+                    Button::new(&tab.id, &tab.label)
+                        .with_style(ButtonStyle {
+                            spacing: ButtonSpacing::Fixed(tab_width, tab_height),
+                            ..self.tab_style(i).clone()
+                        })
+                        .with_text_align(TextAlign::Center)
+                        .with_position(position),
+                );
+            }
+        }
+
+        button_manager.update_button_positions();
+        self.apply(button_manager);
+    }
+
+    /// Make tab `index` active. Returns an error for an index past the last tab.
+    pub fn select<A: Clone>(
+        &mut self,
+        button_manager: &mut ButtonManager<A>,
+        index: usize,
+    ) -> Result<(), String> {
+        if index >= self.tabs.len() {
+            return Err(format!("Tab {} not found", index));
+        }
+        self.active = index;
+        self.apply(button_manager);
+        Ok(())
+    }
+
+    /// Switch to a tab whose button was just clicked, returning its index
+    pub fn handle_clicks<A: Clone>(
+        &mut self,
+        button_manager: &mut ButtonManager<A>,
+    ) -> Option<usize> {
+        let index = self
+            .tabs
+            .iter()
+            .position(|tab| button_manager.is_button_clicked(&tab.id))?;
+        self.active = index;
+        self.apply(button_manager);
+        Some(index)
+    }
+
+    fn tab_style(&self, index: usize) -> &ButtonStyle {
+        if index == self.active {
+            &self.active_style
+        } else {
+            &self.style
+        }
+    }
+
+    /// Restyle the tab buttons and show only the active tab's children
+    fn apply<A: Clone>(&self, button_manager: &mut ButtonManager<A>) {
+        for (i, tab) in self.tabs.iter().enumerate() {
+            if let Some(button) = button_manager.get_button_mut(&tab.id) {
+                let style = ButtonStyle {
+                    spacing: button.style.spacing.clone(),
+                    ..self.tab_style(i).clone()
+                };
+                if let Err(e) = button_manager.set_style(&tab.id, style) {
+                    println!("Failed to restyle tab: {}", e);
+                }
+            }
+
+            let active = i == self.active;
+            for child in &tab.children {
+                let result = button_manager
+                    .set_visible(child, active)
+                    .and_then(|_| button_manager.set_enabled(child, active));
+                if let Err(e) = result {
+                    println!("Failed to switch tab child: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::{self, FORMAT, WINDOW_SIZE};
+
+    fn tab_bar() -> TabBar {
+        TabBar::new(ButtonStyle::default())
+            .with_tab("video", "Video", &["vsync", "fullscreen"])
+            .with_tab("audio", "Audio", &["volume"])
+    }
+
+    /// Whether every one of `ids` is visible and enabled, or every one hidden and disabled
+    fn all_shown(button_manager: &ButtonManager, ids: &[&str], shown: bool) -> bool {
+        ids.iter().all(|id| {
+            let button = &button_manager.buttons[*id];
+            button.visible == shown && button.enabled == shown
+        })
+    }
+
+    #[test]
+    fn first_tab_is_active() {
+        let tab_bar = tab_bar();
+        assert_eq!(tab_bar.active_index(), 0);
+        assert_eq!(tab_bar.active_id(), Some("video"));
+        assert_eq!(TabBar::new(ButtonStyle::default()).active_id(), None);
+    }
+
+    #[test]
+    fn only_the_active_tabs_children_are_shown() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut button_manager: ButtonManager =
+            ButtonManager::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        for id in ["vsync", "fullscreen", "volume"] {
+            button_manager.add_button(Button::new(id, id));
+        }
+        let mut tab_bar = tab_bar();
+        tab_bar.layout(&mut button_manager, 100.0, 100.0, 120.0, 40.0);
+        assert!(all_shown(&button_manager, &["vsync", "fullscreen"], true));
+        assert!(all_shown(&button_manager, &["volume"], false));
+
+        // No tab clicked, nothing changes
+        assert_eq!(tab_bar.handle_clicks(&mut button_manager), None);
+
+        button_manager.click("audio").unwrap();
+        assert_eq!(tab_bar.handle_clicks(&mut button_manager), Some(1));
+        assert_eq!(tab_bar.active_index(), 1);
+        assert!(all_shown(&button_manager, &["vsync", "fullscreen"], false));
+        assert!(all_shown(&button_manager, &["volume"], true));
+
+        tab_bar.select(&mut button_manager, 0).unwrap();
+        assert!(all_shown(&button_manager, &["vsync", "fullscreen"], true));
+        assert!(all_shown(&button_manager, &["volume"], false));
+        assert!(tab_bar.select(&mut button_manager, 2).is_err());
+        assert_eq!(tab_bar.active_index(), 0);
+    }
+}
@@ -0,0 +1,43 @@
+//! Shared setup for unit tests that need a GPU device or fonts
+
+use crate::ui::text::{load_font_into, HANKEN_GROTESK_FACES};
+use egui_wgpu::wgpu::{self, Device, Queue};
+use glyphon::cosmic_text::fontdb;
+use glyphon::FontSystem;
+use winit::dpi::PhysicalSize;
+
+/// Format the tests' renderers are built for
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Window size the tests lay out in, the reference height of `dpi_scale`
+pub const WINDOW_SIZE: PhysicalSize<u32> = PhysicalSize::new(1920, 1080);
+
+/// A device on any adapter, or `None` on machines without one (e.g. headless
+/// CI), in which case the calling test returns early
+pub fn gpu() -> Option<(Device, Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let Some(adapter) =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+    else {
+        println!("No GPU adapter available, skipping");
+        return None;
+    };
+    match pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)) {
+        Ok(device) => Some(device),
+        Err(e) => {
+            println!("Failed to create a device, skipping: {}", e);
+            None
+        }
+    }
+}
+
+/// A font system with only the bundled HankenGrotesk faces, registered like
+/// `TextRenderer` does, so measurements don't depend on the machine's fonts
+pub fn font_system() -> FontSystem {
+    let mut db = fontdb::Database::new();
+    for (path, weight, style) in HANKEN_GROTESK_FACES {
+        load_font_into(&mut db, path, "HankenGrotesk", Some((*weight, *style)))
+            .expect("bundled font is missing");
+    }
+    FontSystem::new_with_locale_and_db("en-US".to_string(), db)
+}
@@ -1,14 +1,144 @@
+use crate::ui::button::utils::dpi_scale;
+use crate::ui::pipeline::PipelineConfig;
+use crate::ui::scaling::ScalingConfig;
 use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
+use glyphon::cosmic_text::fontdb;
+use glyphon::cosmic_text::Align;
 use glyphon::{
     Attrs, Buffer, Cache, Color, Family, FontSystem, Metrics, Resolution, Shaping, Style,
     SwashCache, TextArea, TextAtlas, TextBounds, TextRenderer as GlyphonTextRenderer, Viewport,
-    Weight,
+    Weight, Wrap,
 };
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
+/// How a line is broken when it is wider than the buffer's max width.
+///
+/// Explicit line breaks (`\n` or `\r\n`) in the text always start a new line,
+/// regardless of the wrap mode, so multi-line labels like " Show\nDebug\n  Info"
+/// can be written directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextWrap {
+    /// Never wrap, lines extend past the max width and get clipped
+    None,
+    /// Wrap at word boundaries, breaking a word only if it can't fit on a line by itself
+    #[default]
+    Word,
+    /// Wrap at any glyph
+    Glyph,
+}
+
+impl From<TextWrap> for Wrap {
+    fn from(wrap: TextWrap) -> Self {
+        match wrap {
+            TextWrap::None => Wrap::None,
+            TextWrap::Word => Wrap::WordOrGlyph,
+            TextWrap::Glyph => Wrap::Glyph,
+        }
+    }
+}
+
+/// Base direction used to align text.
+///
+/// Glyph order within a run is always handled by bidi shaping. The direction
+/// only decides which edge lines align to and, for buttons, which side
+/// `TextAlign::Left`/`Right` refer to (start/end of the line).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextDirection {
+    /// Each paragraph aligns by its first strong character (Arabic/Hebrew align right)
+    #[default]
+    Auto,
+    /// Left-to-right, lines align to the left edge
+    Ltr,
+    /// Right-to-left, lines align to the right edge
+    Rtl,
+}
+
+impl TextDirection {
+    fn align(self) -> Option<Align> {
+        match self {
+            TextDirection::Auto => None,
+            TextDirection::Ltr => Some(Align::Left),
+            TextDirection::Rtl => Some(Align::Right),
+        }
+    }
+
+    /// Apply this direction's alignment to every line of `buffer`.
+    /// Must be called after `set_text`, which replaces the lines.
+    fn apply(self, buffer: &mut Buffer) {
+        for line in buffer.lines.iter_mut() {
+            line.set_align(self.align());
+        }
+    }
+}
+
+/// Face files registered under the "HankenGrotesk" family.
+///
+/// | File                           | Weight        | Style  |
+/// |--------------------------------|---------------|--------|
+/// | `HankenGrotesk-Medium.ttf`     | `MEDIUM` 500  | Normal |
+///
+/// Only the Medium face ships in `fonts/HankenGrotesk/`, so other weights
+/// and styles, like the Bold hover labels, are drawn with it. Add a file
+/// there and a row here to give one its own face.
+pub const HANKEN_GROTESK_FACES: &[(&str, Weight, Style)] = &[(
+    "fonts/HankenGrotesk/HankenGrotesk-Medium.ttf",
+    Weight::MEDIUM,
+    Style::Normal,
+)];
+
+/// Ids of the game UI's text buffers: the HUD created by
+/// `game::initialize_game_ui`, the game over text and the debug readout
+pub mod game_ui_ids {
+    /// Countdown at the top center
+    pub const MAIN_TIMER: &str = "main_timer";
+    /// "PAUSED" under the timer while it is paused
+    pub const PAUSED_INDICATOR: &str = "paused_indicator";
+    pub const LEVEL: &str = "level";
+    pub const SCORE: &str = "score";
+    pub const GAME_OVER_TITLE: &str = "game_over_title";
+    /// "Click anywhere to play again." under the title
+    pub const GAME_OVER_RESTART: &str = "game_over_restart";
+    /// FPS and input readout shown with the pause menu's debug panel
+    pub const DEBUG_INFO: &str = "debug_info";
+}
+
+/// Families tried in order when a requested one is missing
+pub const DEFAULT_FONT_FALLBACKS: &[&str] = &[
+    "HankenGrotesk",
+    "DejaVu Sans",
+    "Noto Sans",
+    "Liberation Sans",
+    "Arial",
+];
+
+/// Color emoji font loaded if present, e.g. for platforms without one. It is
+/// registered as "Noto Color Emoji", a family cosmic-text's per-glyph fallback
+/// already tries, so emoji in any label find it without naming it.
+pub const EMOJI_FONT_PATH: &str = "fonts/NotoColorEmoji/NotoColorEmoji.ttf";
+
+/// Color emoji families of the common platforms. Glyphs missing from a
+/// label's font fall back to these; their COLR/CBDT glyphs are rasterized in
+/// color into glyphon's color atlas and ignore the text color.
+pub const EMOJI_FONT_FAMILIES: &[&str] = &[
+    "Noto Color Emoji",
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "Twemoji Mozilla",
+];
+
+/// Tracking of the "Game Over!" title at 1080p, in pixels
+const GAME_OVER_TRACKING: f32 = 3.0;
+
+/// Shaped by `has_emoji_glyphs` to check that emoji render
+const EMOJI_PROBE: &str = "🎉";
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TextStyle {
     pub font_family: String,
@@ -17,6 +147,11 @@ pub struct TextStyle {
     pub color: Color,
     pub weight: Weight,
     pub style: Style,
+    pub wrap: TextWrap,
+    pub direction: TextDirection,
+    /// Extra space after every character but a line's last, in pixels, e.g.
+    /// for headings. Included in `measure_text`; negative values act as 0.
+    pub letter_spacing: f32,
 }
 
 impl Default for TextStyle {
@@ -28,10 +163,200 @@ impl Default for TextStyle {
             color: Color::rgb(255, 255, 255),
             weight: Weight::NORMAL,
             style: Style::Normal,
+            wrap: TextWrap::Word,
+            direction: TextDirection::Auto,
+            letter_spacing: 0.0,
+        }
+    }
+}
+
+impl TextStyle {
+    /// Attributes the text is shaped with, for a `font_family` already resolved
+    fn attrs(&self) -> Attrs<'_> {
+        Attrs::new()
+            .family(Family::Name(&self.font_family))
+            .weight(self.weight)
+            .style(self.style)
+    }
+
+    /// Shape `text` into `buffer` with this style's attributes and letter
+    /// spacing. cosmic-text has no tracking of its own, so a narrow no-break
+    /// space sized to `letter_spacing` is shaped between the characters; it
+    /// adds no line break opportunities.
+    fn set_buffer_text(&self, font_system: &mut FontSystem, buffer: &mut Buffer, text: &str) {
+        let attrs = self.attrs();
+        if self.letter_spacing <= 0.0 {
+            buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+            return;
+        }
+
+        let spacer_size = self.letter_spacing / spacer_advance(font_system, attrs);
+        let spacer_attrs = attrs.metrics(Metrics::new(spacer_size, self.line_height));
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let mut characters = text.char_indices().peekable();
+        while let Some((index, character)) = characters.next() {
+            let end = index + character.len_utf8();
+            let Some(&(_, next)) = characters.peek() else {
+                break;
+            };
+            if takes_letter_spacing(character, next) {
+                spans.push((&text[start..end], attrs));
+                spans.push((LETTER_SPACER, spacer_attrs));
+                start = end;
+            }
         }
+        spans.push((&text[start..], attrs));
+        buffer.set_rich_text(font_system, spans, attrs, Shaping::Advanced);
+    }
+}
+
+/// Shaped between characters for `TextStyle::letter_spacing`
+const LETTER_SPACER: &str = "\u{202F}";
+
+/// Width of `LETTER_SPACER` per pixel of font size in the font of `attrs`
+fn spacer_advance(font_system: &mut FontSystem, attrs: Attrs) -> f32 {
+    let mut buffer = Buffer::new(font_system, Metrics::new(100.0, 100.0));
+    buffer.set_text(font_system, LETTER_SPACER, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_system, false);
+    let width: f32 = buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter())
+        .map(|glyph| glyph.w)
+        .sum();
+    // Typical for a narrow space, should the font report none
+    if width > 0.0 {
+        width / 100.0
+    } else {
+        0.2
     }
 }
 
+/// Whether letter spacing goes between `character` and the `next` one. Not
+/// at line ends, and not inside a sequence that draws as one glyph, like an
+/// accented letter or a joined emoji.
+fn takes_letter_spacing(character: char, next: char) -> bool {
+    // Zero-width joiner, variation selectors, combining accents, skin tones
+    let joins = |c: char| {
+        matches!(
+            c,
+            '\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{0300}'..='\u{036F}' | '\u{1F3FB}'..='\u{1F3FF}'
+        )
+    };
+    let line_break = |c: char| matches!(c, '\n' | '\r');
+    !line_break(character) && !line_break(next) && character != '\u{200D}' && !joins(next)
+}
+
+/// Add the faces in `font_path` to `db` under `font_name`, see
+/// `TextRenderer::register_font`
+pub(crate) fn load_font_into(
+    db: &mut fontdb::Database,
+    font_path: &str,
+    font_name: &str,
+    face: Option<(Weight, Style)>,
+) -> Result<(), std::io::Error> {
+    let font_data = fs::read(Path::new(font_path))?;
+    let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(font_data)));
+    for id in ids {
+        let Some(mut info) = db.face(id).cloned() else {
+            continue;
+        };
+        db.remove_face(id);
+        info.families = vec![(
+            font_name.to_string(),
+            fontdb::Language::English_UnitedStates,
+        )];
+        if let Some((weight, style)) = face {
+            info.weight = weight;
+            info.style = style;
+        }
+        db.push_face_info(info);
+    }
+    Ok(())
+}
+
+/// Whether `font_system` has a font from `EMOJI_FONT_FAMILIES` that shapes
+/// emoji to real glyphs rather than the missing-glyph box
+pub fn has_emoji_glyphs(font_system: &mut FontSystem) -> bool {
+    let has_emoji_family = font_system.db().faces().any(|face| {
+        face.families.iter().any(|(name, _)| {
+            EMOJI_FONT_FAMILIES
+                .iter()
+                .any(|family| name.eq_ignore_ascii_case(family))
+        })
+    });
+    if !has_emoji_family {
+        return false;
+    }
+
+    let mut buffer = Buffer::new(font_system, Metrics::new(32.0, 40.0));
+    buffer.set_text(font_system, EMOJI_PROBE, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(font_system, false);
+    let mut glyphs = buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter())
+        .peekable();
+    // Glyph 0 is the font's "missing glyph" box
+    glyphs.peek().is_some() && glyphs.all(|glyph| glyph.glyph_id != 0)
+}
+
+/// Shape `text` in a scratch buffer `max_width` wide and measure it as
+/// `(min_x, width, height)`. `style.font_family` must already be resolved.
+fn shape_and_measure(
+    font_system: &mut FontSystem,
+    text: &str,
+    style: &TextStyle,
+    max_width: Option<f32>,
+) -> (f32, f32, f32) {
+    let metrics = Metrics::new(style.font_size, style.line_height);
+    let mut buffer = Buffer::new(font_system, metrics);
+    if max_width.is_some() {
+        buffer.set_size(font_system, max_width, None);
+        buffer.set_wrap(font_system, style.wrap.into());
+    }
+
+    style.set_buffer_text(font_system, &mut buffer, text);
+    style.direction.apply(&mut buffer);
+    buffer.shape_until_scroll(font_system, false);
+
+    // Calculate text dimensions from layout runs
+    let mut min_x = f32::MAX;
+    let mut max_x: f32 = 0.0;
+    let mut height: f32 = 0.0;
+
+    for run in buffer.layout_runs() {
+        if let Some(first_glyph) = run.glyphs.first() {
+            min_x = min_x.min(first_glyph.x);
+        }
+        if let Some(last_glyph) = run.glyphs.last() {
+            max_x = max_x.max(last_glyph.x + last_glyph.w);
+        }
+        height += run.line_height;
+    }
+
+    // If no runs, estimate based on text length and font size
+    if min_x == f32::MAX && !text.is_empty() {
+        min_x = 0.0;
+        max_x = text.len() as f32 * style.font_size * 0.6;
+        height = style.line_height;
+    }
+
+    let width = max_x - min_x;
+    (min_x, width, height)
+}
+
+/// Visible buffers in the order they are drawn, later ones over earlier
+/// ones: by `z_index`, then by creation order
+fn draw_order(text_buffers: &HashMap<String, TextBuffer>) -> Vec<(&str, &TextBuffer)> {
+    let mut ordered: Vec<(&str, &TextBuffer)> = text_buffers
+        .iter()
+        .filter(|(_, text_buffer)| text_buffer.visible)
+        .map(|(id, text_buffer)| (id.as_str(), text_buffer))
+        .collect();
+    ordered.sort_by_key(|(_, text_buffer)| (text_buffer.z_index, text_buffer.order));
+    ordered
+}
+
 #[derive(Debug, Clone)]
 pub struct TextPosition {
     pub x: f32,
@@ -51,6 +376,82 @@ impl Default for TextPosition {
     }
 }
 
+/// Maximum number of measurements kept by `TextRenderer::measure_text`
+const MEASURE_CACHE_CAPACITY: usize = 256;
+
+/// Everything that affects the result of `measure_text`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MeasureKey {
+    text: String,
+    font_family: String,
+    font_size: u32,
+    line_height: u32,
+    weight: Weight,
+    style: Style,
+    letter_spacing: u32,
+    wrap: TextWrap,
+    direction: TextDirection,
+    max_width: Option<u32>,
+}
+
+impl MeasureKey {
+    fn new(text: &str, style: &TextStyle, max_width: Option<f32>) -> Self {
+        Self {
+            text: text.to_string(),
+            font_family: style.font_family.clone(),
+            font_size: style.font_size.to_bits(),
+            line_height: style.line_height.to_bits(),
+            weight: style.weight,
+            style: style.style,
+            letter_spacing: style.letter_spacing.to_bits(),
+            wrap: style.wrap,
+            direction: style.direction,
+            max_width: max_width.map(f32::to_bits),
+        }
+    }
+}
+
+/// Least-recently-used cache of `(min_x, width, height)` measurements
+#[derive(Debug, Default)]
+struct MeasureCache {
+    entries: HashMap<MeasureKey, ((f32, f32, f32), u64)>,
+    tick: u64,
+}
+
+impl MeasureCache {
+    fn get(&mut self, key: &MeasureKey) -> Option<(f32, f32, f32)> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(value, last_used)| {
+            *last_used = tick;
+            *value
+        })
+    }
+
+    fn insert(&mut self, key: MeasureKey, value: (f32, f32, f32)) {
+        if self.entries.len() >= MEASURE_CACHE_CAPACITY {
+            // Evict the least recently used entry
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Re-sizes and re-positions a buffer for a new window size, see `register_reflow`
+pub type ReflowHandler = Box<dyn FnMut(&mut TextRenderer, u32, u32)>;
+
 #[derive(Debug)]
 pub struct TextBuffer {
     pub buffer: Buffer,
@@ -59,74 +460,269 @@ pub struct TextBuffer {
     pub scale: f32,
     pub visible: bool,
     pub text_content: String,
+    /// Buffers with a higher z_index draw on top; equal values draw in creation order
+    pub z_index: i32,
+    /// Drawn displaced from `position` by this many pixels, e.g. while animating
+    pub offset: (f32, f32),
+    /// Multiplied into the text color's alpha
+    pub opacity: f32,
+    /// Creation sequence number, kept when a buffer is recreated under the same id
+    order: u64,
 }
 
+/// A `TextRenderer` several components draw through, so they share one font
+/// system, glyph atlas and `prepare` per frame. Each keeps its buffers in a
+/// namespace of its own, see `TextRenderer::namespaced`.
+pub type SharedTextRenderer = Rc<RefCell<TextRenderer>>;
+
+/// Owns the glyph pipeline and every named text buffer.
+///
+/// Buffer ids may carry a namespace, as in `"pause/button_resume"`. All
+/// namespaces share the atlas and are prepared together, but each is drawn
+/// by its own `render_namespace` call, so a menu's text can go on top of the
+/// menu while the rest stays below it.
+///
+/// Font state uses `RefCell` so measuring works through `&self`. That makes
+/// the renderer `!Sync`: keep it on the render thread, and don't call
+/// `measure_text` from code that holds a borrow of `font_system`, which would
+/// panic.
 pub struct TextRenderer {
-    pub font_system: FontSystem,
+    /// Shared with `measure_text`, which only has `&self`; borrow it mutably
+    /// with `get_mut` when holding `&mut TextRenderer`
+    pub font_system: RefCell<FontSystem>,
     pub swash_cache: SwashCache,
     pub viewport: Viewport,
     pub atlas: TextAtlas,
-    pub glyph_renderer: GlyphonTextRenderer,
+    /// One per buffer namespace, all drawing from `atlas`
+    glyph_renderers: HashMap<String, GlyphonTextRenderer>,
+    /// What glyph renderers for new namespaces are built for
+    pipeline_config: PipelineConfig,
     pub text_buffers: HashMap<String, TextBuffer>,
-    pub window_size: winit::dpi::PhysicalSize<u32>,
+    pub window_size: PhysicalSize<u32>,
     pub loaded_fonts: Vec<String>,
+    /// Sequence number handed to the next newly created buffer
+    next_order: u64,
+    /// Families tried in order when a requested family isn't available
+    font_fallbacks: Vec<String>,
+    /// Requested family → family actually used, filled lazily
+    resolved_families: RefCell<HashMap<String, String>>,
+    measure_cache: RefCell<MeasureCache>,
+    /// Set whenever buffers change so `prepare` can skip unchanged frames
+    dirty: bool,
+    /// Font size bounds for the game over and HUD text
+    pub scaling: ScalingConfig,
+    /// Per-buffer auto-size handlers run by `reflow`, in registration order
+    reflow_handlers: Vec<(String, ReflowHandler)>,
 }
 
 impl TextRenderer {
-    pub fn new(
+    /// Sized to the window's current inner size
+    pub fn from_window(
         device: &Device,
         queue: &Queue,
         surface_format: wgpu::TextureFormat,
         window: &Window,
+    ) -> Self {
+        Self::new(device, queue, surface_format, window.inner_size())
+    }
+
+    /// Build without a window, e.g. for offscreen rendering
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        config: &PipelineConfig,
     ) -> Self {
         let font_system = FontSystem::new();
         let swash_cache = SwashCache::new();
         let cache = Cache::new(device);
         let viewport = Viewport::new(device, &cache);
         let mut atlas = TextAtlas::new(device, queue, &cache, surface_format);
-        let glyph_renderer =
-            GlyphonTextRenderer::new(&mut atlas, device, wgpu::MultisampleState::default(), None);
-
-        let size = window.inner_size();
+        let glyph_renderer = GlyphonTextRenderer::new(
+            &mut atlas,
+            device,
+            config.multisample(),
+            config.depth_stencil(),
+        );
 
         let mut renderer = Self {
-            font_system,
+            font_system: RefCell::new(font_system),
             swash_cache,
             viewport,
             atlas,
-            glyph_renderer,
+            glyph_renderers: HashMap::from([(String::new(), glyph_renderer)]),
+            pipeline_config: *config,
             text_buffers: HashMap::new(),
             window_size: size,
             loaded_fonts: Vec::new(),
+            next_order: 0,
+            font_fallbacks: DEFAULT_FONT_FALLBACKS
+                .iter()
+                .map(|family| family.to_string())
+                .collect(),
+            resolved_families: RefCell::new(HashMap::new()),
+            measure_cache: RefCell::new(MeasureCache::default()),
+            dirty: true,
+            scaling: ScalingConfig::default(),
+            reflow_handlers: Vec::new(),
         };
 
-        // Try to load the custom font, but don't fail if it doesn't exist
-        match renderer.load_font(
-            "fonts/HankenGrotesk/HankenGrotesk-Medium.ttf",
-            "HankenGrotesk",
-        ) {
-            Ok(_) => println!("Successfully loaded HankenGrotesk font"),
-            Err(e) => {
-                println!(
-                    "Failed to load HankenGrotesk font: {}. Using system fonts.",
-                    e
-                );
+        // Try to load the custom font faces, but don't fail if they don't exist
+        match renderer.load_font_family("HankenGrotesk", HANKEN_GROTESK_FACES) {
+            0 => println!("Failed to load HankenGrotesk font. Using system fonts."),
+            count => println!("Successfully loaded {} HankenGrotesk face(s)", count),
+        }
+
+        // A bundled emoji font is optional, system ones work as well
+        if Path::new(EMOJI_FONT_PATH).exists() {
+            if let Err(e) = renderer.load_font(EMOJI_FONT_PATH, EMOJI_FONT_FAMILIES[0]) {
+                println!("Failed to load emoji font: {}", e);
             }
         }
+        if !renderer.supports_emoji() {
+            println!("Warning: no color emoji font found, emoji will render as missing glyphs");
+        }
 
         renderer
     }
 
-    /// Load a font from a file path and register it with a name
+    /// Load a font from a file path and register it with a name.
+    /// Weight and style are taken from the font file itself.
     pub fn load_font(&mut self, font_path: &str, font_name: &str) -> Result<(), std::io::Error> {
-        let font_data = fs::read(Path::new(font_path))?;
-        self.font_system.db_mut().load_font_data(font_data);
-        self.loaded_fonts.push(font_name.to_string());
+        self.register_font(font_path, font_name, None)
+    }
+
+    /// Load one face of a family, e.g. the Bold file, under `font_name`.
+    /// Text asking for this family picks the loaded face closest to its weight and style.
+    pub fn load_font_face(
+        &mut self,
+        font_path: &str,
+        font_name: &str,
+        weight: Weight,
+        style: Style,
+    ) -> Result<(), std::io::Error> {
+        self.register_font(font_path, font_name, Some((weight, style)))
+    }
+
+    /// Load every `(path, weight, style)` face under `font_name`, skipping files
+    /// that fail to load. Returns how many faces were loaded.
+    pub fn load_font_family(&mut self, font_name: &str, faces: &[(&str, Weight, Style)]) -> usize {
+        let mut loaded = 0;
+        for (font_path, weight, style) in faces {
+            match self.load_font_face(font_path, font_name, *weight, *style) {
+                Ok(_) => loaded += 1,
+                Err(e) => println!("Failed to load font face {}: {}", font_path, e),
+            }
+        }
+        loaded
+    }
+
+    /// Replace the fallback families tried when a requested family is missing
+    pub fn set_font_fallbacks(&mut self, fallbacks: Vec<String>) {
+        self.font_fallbacks = fallbacks;
+        self.resolved_families.get_mut().clear();
+        self.measure_cache.get_mut().clear();
+    }
+
+    /// Whether `family` was loaded by name or exists in the system font database
+    pub fn is_font_available(&self, family: &str) -> bool {
+        self.loaded_fonts.iter().any(|name| name == family)
+            || self.font_system.borrow().db().faces().any(|face| {
+                face.families
+                    .iter()
+                    .any(|(name, _)| name.eq_ignore_ascii_case(family))
+            })
+    }
+
+    /// Whether emoji shape to real glyphs rather than the missing-glyph box,
+    /// i.e. a font from `EMOJI_FONT_FAMILIES` is available to fall back on
+    pub fn supports_emoji(&self) -> bool {
+        has_emoji_glyphs(&mut self.font_system.borrow_mut())
+    }
+
+    /// Family to shape `family` with: itself if available, otherwise the first
+    /// available entry of `font_fallbacks`. Substitutions are logged once.
+    fn resolve_font_family(&self, family: &str) -> String {
+        if let Some(resolved) = self.resolved_families.borrow().get(family) {
+            return resolved.clone();
+        }
+
+        let resolved = if self.is_font_available(family) {
+            family.to_string()
+        } else {
+            match self
+                .font_fallbacks
+                .iter()
+                .find(|fallback| self.is_font_available(fallback))
+            {
+                Some(fallback) => {
+                    println!(
+                        "Warning: font '{}' not available, using '{}' instead",
+                        family, fallback
+                    );
+                    fallback.clone()
+                }
+                None => {
+                    println!(
+                        "Warning: font '{}' and all fallbacks are unavailable",
+                        family
+                    );
+                    family.to_string()
+                }
+            }
+        };
+
+        self.resolved_families
+            .borrow_mut()
+            .insert(family.to_string(), resolved.clone());
+        resolved
+    }
+
+    /// Add the faces in `font_path` to the font database under `font_name`,
+    /// overriding the family name stored in the file so every face of a family
+    /// is found by the same name
+    fn register_font(
+        &mut self,
+        font_path: &str,
+        font_name: &str,
+        face: Option<(Weight, Style)>,
+    ) -> Result<(), std::io::Error> {
+        load_font_into(
+            self.font_system.get_mut().db_mut(),
+            font_path,
+            font_name,
+            face,
+        )?;
+
+        if !self.loaded_fonts.iter().any(|name| name == font_name) {
+            self.loaded_fonts.push(font_name.to_string());
+        }
+        // A new font can change how previously measured text shapes
+        self.measure_cache.get_mut().clear();
+        self.resolved_families.get_mut().clear();
         println!("Loaded font: {} from {}", font_name, font_path);
         Ok(())
     }
 
-    /// Create a new text buffer with the given ID, text, style, and position
+    /// Create a new text buffer with the given ID, text, style, and position.
+    /// Newlines in `text` are hard line breaks; wrapping within a line follows `style.wrap`.
     pub fn create_text_buffer(
         &mut self,
         id: &str,
@@ -137,13 +733,11 @@ impl TextRenderer {
         let mut style = style.unwrap_or_default();
         let position = position.unwrap_or_default();
 
-        // If the requested font isn't loaded, fall back to a system font
-        if !self.loaded_fonts.contains(&style.font_family) && style.font_family == "HankenGrotesk" {
-            style.font_family = "DejaVu Sans".to_string();
-        }
+        // If the requested font isn't available, fall back to one that is
+        style.font_family = self.resolve_font_family(&style.font_family);
 
         let metrics = Metrics::new(style.font_size, style.line_height);
-        let mut buffer = Buffer::new(&mut self.font_system, metrics);
+        let mut buffer = Buffer::new(self.font_system.get_mut(), metrics);
 
         // Set buffer size based on position constraints or window size
         let width = position.max_width.unwrap_or(self.window_size.width as f32);
@@ -151,15 +745,26 @@ impl TextRenderer {
             .max_height
             .unwrap_or(self.window_size.height as f32);
 
-        buffer.set_size(&mut self.font_system, Some(width), Some(height));
+        buffer.set_size(self.font_system.get_mut(), Some(width), Some(height));
+        buffer.set_wrap(self.font_system.get_mut(), style.wrap.into());
 
-        let attrs = Attrs::new()
-            .family(Family::Name(&style.font_family))
-            .weight(style.weight)
-            .style(style.style);
+        style.set_buffer_text(self.font_system.get_mut(), &mut buffer, text);
+        style.direction.apply(&mut buffer);
+        buffer.shape_until_scroll(self.font_system.get_mut(), false);
 
-        buffer.set_text(&mut self.font_system, text, attrs, Shaping::Advanced);
-        buffer.shape_until_scroll(&mut self.font_system, false);
+        // Recreating an existing id keeps its place in the draw order
+        let (z_index, order, offset, opacity) = match self.text_buffers.get(id) {
+            Some(existing) => (
+                existing.z_index,
+                existing.order,
+                existing.offset,
+                existing.opacity,
+            ),
+            None => {
+                self.next_order += 1;
+                (0, self.next_order, (0.0, 0.0), 1.0)
+            }
+        };
 
         let text_buffer = TextBuffer {
             buffer,
@@ -168,23 +773,26 @@ impl TextRenderer {
             scale: 1.0,
             visible: true,
             text_content: text.to_string(),
+            z_index,
+            offset,
+            opacity,
+            order,
         };
 
         self.text_buffers.insert(id.to_string(), text_buffer);
+        self.dirty = true;
     }
 
     /// Update the style of an existing buffer
     pub fn update_style(&mut self, id: &str, mut style: TextStyle) -> Result<(), String> {
+        // If the requested font isn't available, fall back to one that is
+        style.font_family = self.resolve_font_family(&style.font_family);
+
         let text_buffer = self
             .text_buffers
             .get_mut(id)
             .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
 
-        // If the requested font isn't loaded, fall back to a system font
-        if !self.loaded_fonts.contains(&style.font_family) && style.font_family == "HankenGrotesk" {
-            style.font_family = "DejaVu Sans".to_string();
-        }
-
         // Update metrics if font size or line height changed
         if text_buffer.style.font_size != style.font_size
             || text_buffer.style.line_height != style.line_height
@@ -192,26 +800,52 @@ impl TextRenderer {
             let metrics = Metrics::new(style.font_size, style.line_height);
             text_buffer
                 .buffer
-                .set_metrics(&mut self.font_system, metrics);
+                .set_metrics(self.font_system.get_mut(), metrics);
+        }
+
+        if text_buffer.style.wrap != style.wrap {
+            text_buffer
+                .buffer
+                .set_wrap(self.font_system.get_mut(), style.wrap.into());
         }
 
         text_buffer.style = style;
 
         // Re-apply text with new attributes using stored content
-        let attrs = Attrs::new()
-            .family(Family::Name(&text_buffer.style.font_family))
-            .weight(text_buffer.style.weight)
-            .style(text_buffer.style.style);
-
-        text_buffer.buffer.set_text(
-            &mut self.font_system,
+        text_buffer.style.set_buffer_text(
+            self.font_system.get_mut(),
+            &mut text_buffer.buffer,
             &text_buffer.text_content,
-            attrs,
-            Shaping::Advanced,
         );
+        text_buffer.style.direction.apply(&mut text_buffer.buffer);
         text_buffer
             .buffer
-            .shape_until_scroll(&mut self.font_system, false);
+            .shape_until_scroll(self.font_system.get_mut(), false);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Replace the text of an existing buffer, keeping its style and position
+    pub fn set_text(&mut self, id: &str, text: &str) -> Result<(), String> {
+        let text_buffer = self
+            .text_buffers
+            .get_mut(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        if text_buffer.text_content == text {
+            return Ok(());
+        }
+        text_buffer.text_content = text.to_string();
+        let style = text_buffer.style.clone();
+        self.update_style(id, style)
+    }
+
+    /// Delete a buffer and its reflow handler, e.g. the label of a removed button
+    pub fn remove_text_buffer(&mut self, id: &str) -> Result<(), String> {
+        self.text_buffers
+            .remove(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        self.unregister_reflow(id);
+        self.dirty = true;
         Ok(())
     }
 
@@ -232,15 +866,125 @@ impl TextRenderer {
                 .unwrap_or(self.window_size.height as f32);
             text_buffer
                 .buffer
-                .set_size(&mut self.font_system, Some(width), Some(height));
+                .set_size(self.font_system.get_mut(), Some(width), Some(height));
         }
 
         text_buffer.position = position;
+        self.dirty = true;
         Ok(())
     }
 
+    /// Show or hide a text buffer. Only marks the renderer dirty when visibility changes.
+    pub fn set_visible(&mut self, id: &str, visible: bool) -> Result<(), String> {
+        let text_buffer = self
+            .text_buffers
+            .get_mut(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        if text_buffer.visible != visible {
+            text_buffer.visible = visible;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Set the draw layer of a text buffer; higher values draw on top
+    pub fn set_z_index(&mut self, id: &str, z_index: i32) -> Result<(), String> {
+        let text_buffer = self
+            .text_buffers
+            .get_mut(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        if text_buffer.z_index != z_index {
+            text_buffer.z_index = z_index;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Draw a buffer displaced from its position without reshaping it
+    pub fn set_offset(&mut self, id: &str, dx: f32, dy: f32) -> Result<(), String> {
+        let text_buffer = self
+            .text_buffers
+            .get_mut(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        if text_buffer.offset != (dx, dy) {
+            text_buffer.offset = (dx, dy);
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Fade a buffer without changing its style color, clamped to 0.0..=1.0
+    pub fn set_opacity(&mut self, id: &str, opacity: f32) -> Result<(), String> {
+        let text_buffer = self
+            .text_buffers
+            .get_mut(id)
+            .ok_or_else(|| format!("Text buffer '{}' not found", id))?;
+        let opacity = opacity.clamp(0.0, 1.0);
+        if text_buffer.opacity != opacity {
+            text_buffer.opacity = opacity;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Whether a text buffer exists and is visible
+    pub fn is_visible(&self, id: &str) -> bool {
+        self.text_buffers
+            .get(id)
+            .map(|buffer| buffer.visible)
+            .unwrap_or(false)
+    }
+
+    /// Force the next `prepare` to rebuild glyphs, for callers that mutate
+    /// `text_buffers` directly
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether any buffer changed since the last successful `prepare`
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
     pub fn resize(&mut self, queue: &Queue, resolution: Resolution) {
         self.viewport.update(queue, resolution);
+        self.dirty = true;
+    }
+
+    /// Run `handler` from `reflow` while a buffer named `id` exists. Replaces
+    /// any handler already registered for `id`.
+    pub fn register_reflow(
+        &mut self,
+        id: &str,
+        handler: impl FnMut(&mut TextRenderer, u32, u32) + 'static,
+    ) {
+        let handler: ReflowHandler = Box::new(handler);
+        if let Some(entry) = self.reflow_handlers.iter_mut().find(|(key, _)| key == id) {
+            entry.1 = handler;
+        } else {
+            self.reflow_handlers.push((id.to_string(), handler));
+        }
+    }
+
+    pub fn unregister_reflow(&mut self, id: &str) {
+        self.reflow_handlers.retain(|(key, _)| key != id);
+    }
+
+    /// Re-size and re-position every buffer that has a reflow handler, e.g.
+    /// after a window resize. Handlers of missing buffers are skipped.
+    pub fn reflow(&mut self, width: u32, height: u32) {
+        // Handlers borrow the renderer mutably, so they are taken out while they run
+        let mut handlers = std::mem::take(&mut self.reflow_handlers);
+        for (id, handler) in handlers.iter_mut() {
+            if self.text_buffers.contains_key(id.as_str()) {
+                handler(self, width, height);
+            }
+        }
+        // Keep handlers registered by the handlers themselves
+        let added = std::mem::replace(&mut self.reflow_handlers, handlers);
+        for (id, handler) in added {
+            self.register_reflow(&id, handler);
+        }
     }
 
     pub fn prepare(
@@ -249,107 +993,218 @@ impl TextRenderer {
         queue: &Queue,
         _surface_config: &SurfaceConfiguration,
     ) -> Result<(), glyphon::PrepareError> {
-        let mut text_areas = Vec::new();
+        // Nothing changed, the glyphs prepared last time are still valid for render
+        if !self.dirty {
+            return Ok(());
+        }
 
-        for text_buffer in self.text_buffers.values() {
-            if !text_buffer.visible {
-                continue;
+        // Every namespace with buffers gets a glyph renderer of its own
+        let namespaces: Vec<String> = self
+            .text_buffers
+            .keys()
+            .map(|id| Self::namespace_of(id).to_string())
+            .collect();
+        for namespace in namespaces {
+            if !self.glyph_renderers.contains_key(&namespace) {
+                let glyph_renderer = GlyphonTextRenderer::new(
+                    &mut self.atlas,
+                    device,
+                    self.pipeline_config.multisample(),
+                    self.pipeline_config.depth_stencil(),
+                );
+                self.glyph_renderers.insert(namespace, glyph_renderer);
             }
+        }
 
-            let bounds = TextBounds {
-                left: text_buffer.position.x as i32,
-                top: text_buffer.position.y as i32,
-                right: (text_buffer.position.x
-                    + text_buffer
-                        .position
-                        .max_width
-                        .unwrap_or(self.window_size.width as f32)) as i32,
-                bottom: (text_buffer.position.y
-                    + text_buffer
-                        .position
-                        .max_height
-                        .unwrap_or(self.window_size.height as f32)) as i32,
-            };
+        let ordered: Vec<(&str, &TextBuffer)> = draw_order(&self.text_buffers)
+            .into_iter()
+            .map(|(id, text_buffer)| (Self::namespace_of(id), text_buffer))
+            .collect();
 
-            let text_area = TextArea {
-                buffer: &text_buffer.buffer,
-                left: text_buffer.position.x,
-                top: text_buffer.position.y,
-                scale: text_buffer.scale,
-                bounds,
-                default_color: text_buffer.style.color,
-                custom_glyphs: &[],
-            };
+        // Namespaces without visible buffers are prepared empty, so they draw nothing
+        let window_size = self.window_size;
+        for (namespace, glyph_renderer) in self.glyph_renderers.iter_mut() {
+            let text_areas = ordered
+                .iter()
+                .filter(|(buffer_namespace, _)| *buffer_namespace == namespace.as_str())
+                .map(|(_, text_buffer)| Self::text_area(text_buffer, window_size));
+            glyph_renderer.prepare(
+                device,
+                queue,
+                self.font_system.get_mut(),
+                &mut self.atlas,
+                &self.viewport,
+                text_areas,
+                &mut self.swash_cache,
+            )?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
 
-            text_areas.push(text_area);
+    /// Where and how to draw a buffer, with its offset and opacity applied
+    fn text_area(text_buffer: &TextBuffer, window_size: PhysicalSize<u32>) -> TextArea<'_> {
+        let left = text_buffer.position.x + text_buffer.offset.0;
+        let top = text_buffer.position.y + text_buffer.offset.1;
+        let bounds = TextBounds {
+            left: left as i32,
+            top: top as i32,
+            right: (left
+                + text_buffer
+                    .position
+                    .max_width
+                    .unwrap_or(window_size.width as f32)) as i32,
+            bottom: (top
+                + text_buffer
+                    .position
+                    .max_height
+                    .unwrap_or(window_size.height as f32)) as i32,
+        };
+
+        let color = text_buffer.style.color;
+        let default_color = if text_buffer.opacity < 1.0 {
+            Color::rgba(
+                color.r(),
+                color.g(),
+                color.b(),
+                (color.a() as f32 * text_buffer.opacity).round() as u8,
+            )
+        } else {
+            color
+        };
+
+        TextArea {
+            buffer: &text_buffer.buffer,
+            left,
+            top,
+            scale: text_buffer.scale,
+            bounds,
+            default_color,
+            custom_glyphs: &[],
         }
+    }
 
-        self.glyph_renderer.prepare(
-            device,
-            queue,
-            &mut self.font_system,
-            &mut self.atlas,
-            &self.viewport,
-            text_areas,
-            &mut self.swash_cache,
-        )
+    /// Draw the buffers outside any namespace, e.g. the game HUD
+    pub fn render(&self, render_pass: &mut RenderPass) -> Result<(), glyphon::RenderError> {
+        self.render_namespace("", render_pass)
     }
 
-    pub fn render(&mut self, render_pass: &mut RenderPass) -> Result<(), glyphon::RenderError> {
-        self.glyph_renderer
-            .render(&self.atlas, &self.viewport, render_pass)
+    /// Draw the buffers of `namespace` as of the last `prepare`
+    pub fn render_namespace(
+        &self,
+        namespace: &str,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), glyphon::RenderError> {
+        match self.glyph_renderers.get(namespace) {
+            Some(glyph_renderer) => glyph_renderer.render(&self.atlas, &self.viewport, render_pass),
+            // Nothing was ever created in it
+            None => Ok(()),
+        }
     }
 
-    pub fn measure_text(&mut self, text: &str, style: &TextStyle) -> (f32, f32, f32) {
-        let metrics = Metrics::new(style.font_size, style.line_height);
-        let mut buffer = Buffer::new(&mut self.font_system, metrics);
+    /// Id of buffer `id` inside `namespace`, e.g. `"pause/button_resume"`.
+    /// The empty namespace leaves `id` as it is.
+    pub fn namespaced(namespace: &str, id: &str) -> String {
+        if namespace.is_empty() {
+            id.to_string()
+        } else {
+            format!("{}/{}", namespace, id)
+        }
+    }
 
-        let attrs = Attrs::new()
-            .family(Family::Name(&style.font_family))
-            .weight(style.weight)
-            .style(style.style);
+    /// Namespace of a buffer id: the part before the first `/`, empty if none
+    pub fn namespace_of(id: &str) -> &str {
+        id.split_once('/').map_or("", |(namespace, _)| namespace)
+    }
 
-        buffer.set_text(&mut self.font_system, text, attrs, Shaping::Advanced);
-        buffer.shape_until_scroll(&mut self.font_system, false);
+    /// On-screen rectangle `(x, y, width, height)` covered by the already shaped
+    /// glyphs of buffer `id`. Unlike `measure_text` this doesn't re-shape anything.
+    pub fn get_buffer_bounds(&self, id: &str) -> Option<(f32, f32, f32, f32)> {
+        let text_buffer = self.text_buffers.get(id)?;
+        let scale = text_buffer.scale;
 
-        // Calculate text dimensions from layout runs
         let mut min_x = f32::MAX;
-        let mut max_x: f32 = 0.0;
-        let mut height: f32 = 0.0;
-
-        for run in buffer.layout_runs() {
-            if let Some(first_glyph) = run.glyphs.first() {
-                min_x = min_x.min(first_glyph.x);
+        let mut max_x = f32::MIN;
+        let mut top = f32::MAX;
+        let mut bottom = f32::MIN;
+        for run in text_buffer.buffer.layout_runs() {
+            for glyph in run.glyphs.iter() {
+                min_x = min_x.min(glyph.x);
+                max_x = max_x.max(glyph.x + glyph.w);
             }
-            if let Some(last_glyph) = run.glyphs.last() {
-                max_x = max_x.max(last_glyph.x + last_glyph.w);
-            }
-            height += run.line_height;
+            top = top.min(run.line_top);
+            bottom = bottom.max(run.line_top + run.line_height);
+        }
+
+        // Empty text has no glyphs, report a zero-sized rectangle at the origin
+        if min_x > max_x || top > bottom {
+            return Some((text_buffer.position.x, text_buffer.position.y, 0.0, 0.0));
         }
 
-        // If no runs, estimate based on text length and font size
-        if min_x == f32::MAX && !text.is_empty() {
-            min_x = 0.0;
-            max_x = text.len() as f32 * style.font_size * 0.6;
-            height = style.line_height;
+        Some((
+            text_buffer.position.x + min_x * scale,
+            text_buffer.position.y + top * scale,
+            (max_x - min_x) * scale,
+            (bottom - top) * scale,
+        ))
+    }
+
+    /// Screen-space y coordinate of the first line's baseline in buffer `id`
+    pub fn get_first_baseline(&self, id: &str) -> Option<f32> {
+        let text_buffer = self.text_buffers.get(id)?;
+        text_buffer
+            .buffer
+            .layout_runs()
+            .next()
+            .map(|run| text_buffer.position.y + run.line_y * text_buffer.scale)
+    }
+
+    /// Measure `text` as `(min_x, width, height)`. Results are cached, so calling
+    /// this every frame for unchanged labels doesn't re-shape them.
+    ///
+    /// Only needs `&self`: the font system and cache sit behind `RefCell`s, so
+    /// measuring can be interleaved with borrows of other renderer state.
+    pub fn measure_text(&self, text: &str, style: &TextStyle) -> (f32, f32, f32) {
+        self.measure_text_in(text, style, None)
+    }
+
+    /// Like `measure_text`, wrapping lines longer than `max_width` as
+    /// `style.wrap` says. `None` only breaks lines at newlines.
+    pub fn measure_text_in(
+        &self,
+        text: &str,
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> (f32, f32, f32) {
+        let key = MeasureKey::new(text, style, max_width);
+        if let Some(measurement) = self.measure_cache.borrow_mut().get(&key) {
+            return measurement;
         }
 
-        let width = max_x - min_x;
-        (min_x, width, height)
+        let style = TextStyle {
+            font_family: self.resolve_font_family(&style.font_family),
+            ..style.clone()
+        };
+        let measurement =
+            shape_and_measure(&mut self.font_system.borrow_mut(), text, &style, max_width);
+        self.measure_cache.borrow_mut().insert(key, measurement);
+        measurement
     }
 
     pub fn create_game_over_display(&mut self, width: u32, height: u32) {
         // Virtual DPI scaling based on reference height
-        let reference_height = 1080.0;
-        let scale = (height as f32 / reference_height).clamp(0.7, 2.0);
+        let scale = dpi_scale(height as f32);
         // Main "Game Over!" text - large and centered
         let game_over_style = TextStyle {
             font_family: "HankenGrotesk".to_string(),
-            font_size: (72.0 * scale).clamp(32.0, 180.0),
-            line_height: (90.0 * scale).clamp(36.0, 220.0),
+            font_size: self.scaling.title.clamp_size(72.0 * scale),
+            line_height: self.scaling.title.clamp_line_height(90.0 * scale),
             color: Color::rgb(255, 255, 255), // White color
             weight: Weight::BOLD,
             style: Style::Normal,
+            wrap: TextWrap::Word,
+            direction: TextDirection::Auto,
+            letter_spacing: GAME_OVER_TRACKING * scale,
         };
         // Calculate center position for "Game Over!" text
         let text_width = 450.0 * scale; // Approximate width for "Game Over!" at scaled size
@@ -361,7 +1216,7 @@ impl TextRenderer {
             max_height: Some(text_height),
         };
         self.create_text_buffer(
-            "game_over_title",
+            game_ui_ids::GAME_OVER_TITLE,
             "Game Over!",
             Some(game_over_style),
             Some(game_over_position),
@@ -369,11 +1224,14 @@ impl TextRenderer {
         // Restart instruction text - smaller and below the main text
         let restart_style = TextStyle {
             font_family: "HankenGrotesk".to_string(),
-            font_size: (24.0 * scale).clamp(12.0, 60.0),
-            line_height: (30.0 * scale).clamp(16.0, 80.0),
+            font_size: self.scaling.subtitle.clamp_size(24.0 * scale),
+            line_height: self.scaling.subtitle.clamp_line_height(30.0 * scale),
             color: Color::rgb(255, 255, 255), // White color
             weight: Weight::NORMAL,
             style: Style::Normal,
+            wrap: TextWrap::Word,
+            direction: TextDirection::Auto,
+            letter_spacing: 0.0,
         };
         let restart_text_width = 350.0 * scale; // Approximate width for restart message
         let restart_text_height = 30.0 * scale;
@@ -384,71 +1242,68 @@ impl TextRenderer {
             max_height: Some(restart_text_height),
         };
         self.create_text_buffer(
-            "game_over_restart",
+            game_ui_ids::GAME_OVER_RESTART,
             "Click anywhere to play again.",
             Some(restart_style),
             Some(restart_position),
         );
         // Initially hide the game over display
         self.hide_game_over_display();
+        self.register_reflow(game_ui_ids::GAME_OVER_TITLE, |renderer, width, height| {
+            renderer.handle_game_over_text(width, height)
+        });
     }
 
     /// Show the game over display
     pub fn show_game_over_display(&mut self) {
-        if let Some(title_buffer) = self.text_buffers.get_mut("game_over_title") {
-            title_buffer.visible = true;
-        }
-        if let Some(restart_buffer) = self.text_buffers.get_mut("game_over_restart") {
-            restart_buffer.visible = true;
-        }
+        let _ = self.set_visible(game_ui_ids::GAME_OVER_TITLE, true);
+        let _ = self.set_visible(game_ui_ids::GAME_OVER_RESTART, true);
     }
 
     /// Hide the game over display
     pub fn hide_game_over_display(&mut self) {
-        if let Some(title_buffer) = self.text_buffers.get_mut("game_over_title") {
-            title_buffer.visible = false;
-        }
-        if let Some(restart_buffer) = self.text_buffers.get_mut("game_over_restart") {
-            restart_buffer.visible = false;
-        }
+        let _ = self.set_visible(game_ui_ids::GAME_OVER_TITLE, false);
+        let _ = self.set_visible(game_ui_ids::GAME_OVER_RESTART, false);
     }
 
     /// Check if game over display is currently visible
     pub fn is_game_over_visible(&self) -> bool {
-        self.text_buffers
-            .get("game_over_title")
-            .map(|buffer| buffer.visible)
-            .unwrap_or(false)
+        self.is_visible(game_ui_ids::GAME_OVER_TITLE)
     }
 
     /// Update game over display for different screen sizes (call on window resize)
     pub fn update_game_over_position(&mut self, width: u32, height: u32) -> Result<(), String> {
-        let reference_height = 1080.0;
-        let scale = (height as f32 / reference_height).clamp(0.7, 2.0);
+        let scale = dpi_scale(height as f32);
         // Get the styles from existing buffers to measure text
         let game_over_style = self
             .text_buffers
-            .get("game_over_title")
+            .get(game_ui_ids::GAME_OVER_TITLE)
             .map(|buffer| buffer.style.clone())
             .unwrap_or_else(|| TextStyle {
                 font_family: "HankenGrotesk".to_string(),
-                font_size: (72.0 * scale).clamp(32.0, 180.0),
-                line_height: (90.0 * scale).clamp(36.0, 220.0),
+                font_size: self.scaling.title.clamp_size(72.0 * scale),
+                line_height: self.scaling.title.clamp_line_height(90.0 * scale),
                 color: Color::rgb(255, 255, 255),
                 weight: Weight::BOLD,
                 style: Style::Normal,
+                wrap: TextWrap::Word,
+                direction: TextDirection::Auto,
+                letter_spacing: GAME_OVER_TRACKING * scale,
             });
         let restart_style = self
             .text_buffers
-            .get("game_over_restart")
+            .get(game_ui_ids::GAME_OVER_RESTART)
             .map(|buffer| buffer.style.clone())
             .unwrap_or_else(|| TextStyle {
                 font_family: "HankenGrotesk".to_string(),
-                font_size: (24.0 * scale).clamp(12.0, 60.0),
-                line_height: (30.0 * scale).clamp(16.0, 80.0),
+                font_size: self.scaling.subtitle.clamp_size(24.0 * scale),
+                line_height: self.scaling.subtitle.clamp_line_height(30.0 * scale),
                 color: Color::rgb(255, 255, 255),
                 weight: Weight::NORMAL,
                 style: Style::Normal,
+                wrap: TextWrap::Word,
+                direction: TextDirection::Auto,
+                letter_spacing: 0.0,
             });
         // Measure the actual text dimensions
         let (_, text_width, text_height) = self.measure_text("Game Over!", &game_over_style);
@@ -461,7 +1316,7 @@ impl TextRenderer {
             max_width: Some(text_width + 20.0 * scale), // Add some padding
             max_height: Some(text_height + 10.0 * scale), // Add some padding
         };
-        self.update_position("game_over_title", game_over_position)?;
+        self.update_position(game_ui_ids::GAME_OVER_TITLE, game_over_position)?;
         // Update restart text position
         let restart_position = TextPosition {
             x: (width as f32 / 2.0) - (restart_text_width / 2.0),
@@ -469,7 +1324,7 @@ impl TextRenderer {
             max_width: Some(restart_text_width + 20.0 * scale), // Add some padding
             max_height: Some(restart_text_height + 10.0 * scale), // Add some padding
         };
-        self.update_position("game_over_restart", restart_position)?;
+        self.update_position(game_ui_ids::GAME_OVER_RESTART, restart_position)?;
         Ok(())
     }
 
@@ -480,23 +1335,25 @@ impl TextRenderer {
         let height = height as f32;
 
         // Apply DPI scaling based on height (consistent with other UI elements)
-        let reference_height = 1080.0;
-        let scale = (height / reference_height).clamp(0.7, 2.0);
+        let scale = dpi_scale(height);
 
         // Dynamically scale font sizes with DPI scaling
-        let title_font_size = (width * 0.12 * scale).clamp(48.0, 240.0); // 12% of width, min 48, max 240
-        let title_line_height = (title_font_size * 1.25).clamp(60.0, 300.0);
-        let subtitle_font_size = (width * 0.025 * scale).clamp(16.0, 120.0); // 2.5% of width, min 16, max 120
-        let subtitle_line_height = (subtitle_font_size * 1.3).clamp(20.0, 156.0);
+        let title_font_size = self.scaling.title.clamp_size(width * 0.12 * scale); // 12% of width
+        let title_line_height = self.scaling.title.clamp_line_height(title_font_size * 1.25);
+        let subtitle_font_size = self.scaling.subtitle.clamp_size(width * 0.025 * scale); // 2.5% of width
+        let subtitle_line_height = self
+            .scaling
+            .subtitle
+            .clamp_line_height(subtitle_font_size * 1.3);
 
         // Update game over title
-        if let Some(title_buffer) = self.text_buffers.get_mut("game_over_title") {
+        if let Some(title_buffer) = self.text_buffers.get(game_ui_ids::GAME_OVER_TITLE) {
             let mut style = title_buffer.style.clone();
             style.font_size = title_font_size;
             style.line_height = title_line_height;
             let text = title_buffer.text_content.clone();
 
-            let _ = self.update_style("game_over_title", style.clone());
+            let _ = self.update_style(game_ui_ids::GAME_OVER_TITLE, style.clone());
             let (_min_x, text_width, text_height) = self.measure_text(&text, &style);
 
             let pos = TextPosition {
@@ -505,17 +1362,17 @@ impl TextRenderer {
                 max_width: Some(text_width + 40.0 * scale), // Add padding to prevent clipping
                 max_height: Some(text_height + 20.0 * scale),
             };
-            let _ = self.update_position("game_over_title", pos);
+            let _ = self.update_position(game_ui_ids::GAME_OVER_TITLE, pos);
         }
 
         // Update restart text
-        if let Some(restart_buffer) = self.text_buffers.get_mut("game_over_restart") {
+        if let Some(restart_buffer) = self.text_buffers.get(game_ui_ids::GAME_OVER_RESTART) {
             let mut style = restart_buffer.style.clone();
             style.font_size = subtitle_font_size;
             style.line_height = subtitle_line_height;
             let text = restart_buffer.text_content.clone();
 
-            let _ = self.update_style("game_over_restart", style.clone());
+            let _ = self.update_style(game_ui_ids::GAME_OVER_RESTART, style.clone());
             let (_min_x, text_width, text_height) = self.measure_text(&text, &style);
 
             let pos = TextPosition {
@@ -524,7 +1381,7 @@ impl TextRenderer {
                 max_width: Some(text_width + 60.0 * scale), // Add more padding for subtitle to prevent clipping
                 max_height: Some(text_height + 30.0 * scale),
             };
-            let _ = self.update_position("game_over_restart", pos);
+            let _ = self.update_position(game_ui_ids::GAME_OVER_RESTART, pos);
         }
     }
 
@@ -533,20 +1390,19 @@ impl TextRenderer {
     pub fn handle_score_and_level_text(&mut self, width: u32, height: u32) {
         let width = width as f32;
         let height = height as f32;
-        let reference_height = 1080.0;
-        let scale = (height / reference_height).clamp(0.7, 2.0);
+        let scale = dpi_scale(height);
         // Make this text smaller than subtitles, but more legible on high-DPI
-        let font_size = (width * 0.022 * scale).clamp(16.0, 48.0); // 2.2% of width, min 16, max 48
-        let line_height = (font_size * 1.25).clamp(20.0, 60.0);
+        let font_size = self.scaling.hud_label.clamp_size(width * 0.022 * scale); // 2.2% of width
+        let line_height = self.scaling.hud_label.clamp_line_height(font_size * 1.25);
         let padding_x = 32.0 * scale;
         let padding_y = 24.0 * scale;
         // Score text
-        if let Some(score_buffer) = self.text_buffers.get_mut("score") {
+        if let Some(score_buffer) = self.text_buffers.get(game_ui_ids::SCORE) {
             let mut style = score_buffer.style.clone();
             style.font_size = font_size;
             style.line_height = line_height;
             let text = score_buffer.text_content.clone();
-            let _ = self.update_style("score", style.clone());
+            let _ = self.update_style(game_ui_ids::SCORE, style.clone());
             let (_min_x, text_width, text_height) = self.measure_text(&text, &style);
             let pos = TextPosition {
                 x: padding_x,
@@ -554,15 +1410,15 @@ impl TextRenderer {
                 max_width: Some(text_width + 20.0 * scale),
                 max_height: Some(text_height + 10.0 * scale),
             };
-            let _ = self.update_position("score", pos);
+            let _ = self.update_position(game_ui_ids::SCORE, pos);
         }
         // Level text (place below score)
-        if let Some(level_buffer) = self.text_buffers.get_mut("level") {
+        if let Some(level_buffer) = self.text_buffers.get(game_ui_ids::LEVEL) {
             let mut style = level_buffer.style.clone();
             style.font_size = font_size;
             style.line_height = line_height;
             let text = level_buffer.text_content.clone();
-            let _ = self.update_style("level", style.clone());
+            let _ = self.update_style(game_ui_ids::LEVEL, style.clone());
             let (_min_x, text_width, text_height) = self.measure_text(&text, &style);
             let pos = TextPosition {
                 x: padding_x,
@@ -570,7 +1426,143 @@ impl TextRenderer {
                 max_width: Some(text_width + 20.0 * scale),
                 max_height: Some(text_height + 10.0 * scale),
             };
-            let _ = self.update_position("level", pos);
+            let _ = self.update_position(game_ui_ids::LEVEL, pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support;
+
+    #[test]
+    fn every_bundled_face_exists() {
+        for (path, _weight, _style) in HANKEN_GROTESK_FACES {
+            assert!(Path::new(path).is_file(), "{} is missing", path);
+        }
+    }
+
+    fn style(wrap: TextWrap) -> TextStyle {
+        TextStyle {
+            font_family: "HankenGrotesk".to_string(),
+            font_size: 20.0,
+            line_height: 25.0,
+            wrap,
+            ..TextStyle::default()
+        }
+    }
+
+    fn line_count(
+        font_system: &mut FontSystem,
+        text: &str,
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> usize {
+        let (_min_x, _width, height) = shape_and_measure(font_system, text, style, max_width);
+        (height / style.line_height).round() as usize
+    }
+
+    #[test]
+    fn wrap_modes_break_lines_differently() {
+        let mut font_system = test_support::font_system();
+        // Room for "xx xx" and a bit: glyph wrapping fills every line, word
+        // wrapping moves "xxxx" to a line of its own
+        let (_min_x, width, _height) =
+            shape_and_measure(&mut font_system, "xx xx", &style(TextWrap::Word), None);
+        let max_width = Some(width + 1.0);
+        let text = "xx xxxx xx";
+
+        let none = line_count(&mut font_system, text, &style(TextWrap::None), max_width);
+        let glyph = line_count(&mut font_system, text, &style(TextWrap::Glyph), max_width);
+        let word = line_count(&mut font_system, text, &style(TextWrap::Word), max_width);
+        assert_eq!((none, glyph, word), (1, 2, 3));
+    }
+
+    #[test]
+    fn measure_key_tells_directions_apart() {
+        let ltr = TextStyle {
+            direction: TextDirection::Ltr,
+            ..TextStyle::default()
+        };
+        let rtl = TextStyle {
+            direction: TextDirection::Rtl,
+            ..TextStyle::default()
+        };
+        assert_ne!(
+            MeasureKey::new("שלום", &ltr, None),
+            MeasureKey::new("שלום", &rtl, None)
+        );
+    }
+
+    fn text_buffer(font_system: &mut FontSystem, z_index: i32, order: u64) -> TextBuffer {
+        TextBuffer {
+            buffer: Buffer::new(font_system, Metrics::new(20.0, 25.0)),
+            style: TextStyle::default(),
+            position: TextPosition::default(),
+            scale: 1.0,
+            visible: true,
+            text_content: String::new(),
+            z_index,
+            offset: (0.0, 0.0),
+            opacity: 1.0,
+            order,
+        }
+    }
+
+    #[test]
+    fn higher_z_draws_later_and_ties_keep_creation_order() {
+        let mut font_system = test_support::font_system();
+        let mut text_buffers = HashMap::new();
+        for (id, z_index, order) in [
+            ("debug", 100, 1),
+            ("timer", 0, 2),
+            ("score", 0, 3),
+            ("hidden", 50, 4),
+            ("level", -1, 5),
+        ] {
+            text_buffers.insert(
+                id.to_string(),
+                text_buffer(&mut font_system, z_index, order),
+            );
+        }
+        text_buffers.get_mut("hidden").unwrap().visible = false;
+
+        let ids: Vec<&str> = draw_order(&text_buffers)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, ["level", "timer", "score", "debug"]);
+    }
+
+    #[test]
+    fn newlines_break_lines_in_every_wrap_mode() {
+        let mut font_system = test_support::font_system();
+        for wrap in [TextWrap::None, TextWrap::Word, TextWrap::Glyph] {
+            let lines = line_count(&mut font_system, "one\ntwo\r\nthree", &style(wrap), None);
+            assert_eq!(lines, 3, "{:?}", wrap);
         }
     }
+
+    #[test]
+    fn no_emoji_glyphs_without_an_emoji_font() {
+        assert!(!has_emoji_glyphs(&mut test_support::font_system()));
+    }
+
+    #[test]
+    fn emoji_glyphs_with_the_emoji_font() {
+        if !Path::new(EMOJI_FONT_PATH).exists() {
+            println!("{} is missing, skipping", EMOJI_FONT_PATH);
+            return;
+        }
+        let mut font_system = test_support::font_system();
+        load_font_into(
+            font_system.db_mut(),
+            EMOJI_FONT_PATH,
+            EMOJI_FONT_FAMILIES[0],
+            None,
+        )
+        .unwrap();
+        assert!(has_emoji_glyphs(&mut font_system));
+    }
 }
@@ -0,0 +1,245 @@
+use crate::ui::button::ButtonPosition;
+use crate::ui::rectangle::Rectangle;
+use crate::ui::text::TextStyle;
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{Key, NamedKey};
+
+/// Seconds the caret stays on, then off
+const CARET_BLINK_INTERVAL: f32 = 0.5;
+
+/// A single-line box the player types into, e.g. a name or a seed.
+/// Drawn by `ButtonManager` together with its buttons; clicking it takes
+/// keyboard focus and clicking anywhere else drops it.
+pub struct TextInput {
+    pub id: String,
+    pub value: String,
+    pub position: ButtonPosition,
+    pub style: TextStyle,
+    pub background_color: [f32; 4],
+    /// Background while focused
+    pub focused_color: [f32; 4],
+    pub caret_color: [f32; 4],
+    pub corner_radius: f32,
+    /// Space between the box edge and the text, in pixels
+    pub padding: f32,
+    /// Longest value accepted, in characters
+    pub max_length: Option<usize>,
+    pub visible: bool,
+    pub focused: bool,
+    /// Id of the text buffer showing the value
+    pub text_id: String,
+    /// Caret position as a character index into `value`
+    caret: usize,
+    /// Caret x offset from the start of the text, measured by the manager
+    pub(crate) caret_offset: f32,
+    /// Time since the caret last (re)appeared
+    blink_elapsed: f32,
+    on_submit: Option<Box<dyn FnMut(&str)>>,
+}
+
+impl TextInput {
+    pub fn new(id: &str, position: ButtonPosition) -> Self {
+        Self {
+            id: id.to_string(),
+            value: String::new(),
+            position,
+            style: TextStyle::default(),
+            background_color: [0.12, 0.16, 0.23, 0.9], // slate-800
+            focused_color: [0.2, 0.25, 0.33, 0.95],    // slate-700
+            caret_color: [0.97, 0.98, 0.99, 1.0],      // slate-50
+            corner_radius: 6.0,
+            padding: 10.0,
+            max_length: None,
+            visible: true,
+            focused: false,
+            text_id: format!("text_input_{}", id),
+            caret: 0,
+            caret_offset: 0.0,
+            blink_elapsed: 0.0,
+            on_submit: None,
+        }
+    }
+
+    /// Start with `value`, caret at the end
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = value.to_string();
+        self.caret = self.value.chars().count();
+        self
+    }
+
+    pub fn with_style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Called with the value when Enter is pressed while focused
+    pub fn with_on_submit(mut self, on_submit: impl FnMut(&str) + 'static) -> Self {
+        self.on_submit = Some(Box::new(on_submit));
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the value, caret at the end
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.caret = self.value.chars().count();
+        self.blink_elapsed = 0.0;
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.blink_elapsed = 0.0;
+    }
+
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (box_x, box_y) = self.position.calculate_actual_position();
+        x >= box_x
+            && x <= box_x + self.position.width
+            && y >= box_y
+            && y <= box_y + self.position.height
+    }
+
+    /// The value up to the caret, for measuring where to draw it
+    pub fn text_before_caret(&self) -> &str {
+        let end = self
+            .value
+            .char_indices()
+            .nth(self.caret)
+            .map_or(self.value.len(), |(index, _)| index);
+        &self.value[..end]
+    }
+
+    /// Top-left corner of the text inside the box
+    pub fn text_origin(&self) -> (f32, f32) {
+        let (box_x, box_y) = self.position.calculate_actual_position();
+        let text_y = box_y + (self.position.height - self.style.line_height) / 2.0;
+        (box_x + self.padding, text_y)
+    }
+
+    /// Advance the caret blink by `delta_time` seconds
+    pub fn update(&mut self, delta_time: f32) {
+        if self.focused {
+            self.blink_elapsed += delta_time.max(0.0);
+        }
+    }
+
+    pub fn caret_visible(&self) -> bool {
+        self.focused && ((self.blink_elapsed / CARET_BLINK_INTERVAL) as u32).is_multiple_of(2)
+    }
+
+    /// Edit the value for a key press while focused. Returns whether the
+    /// value or caret changed, so the text needs laying out again.
+    pub fn handle_key(&mut self, event: &KeyEvent) -> bool {
+        if !self.focused || event.state != ElementState::Pressed {
+            return false;
+        }
+
+        let length = self.value.chars().count();
+        let changed = match &event.logical_key {
+            Key::Named(NamedKey::Backspace) if self.caret > 0 => {
+                self.caret -= 1;
+                self.remove_at_caret();
+                true
+            }
+            Key::Named(NamedKey::Delete) if self.caret < length => {
+                self.remove_at_caret();
+                true
+            }
+            Key::Named(NamedKey::ArrowLeft) if self.caret > 0 => {
+                self.caret -= 1;
+                true
+            }
+            Key::Named(NamedKey::ArrowRight) if self.caret < length => {
+                self.caret += 1;
+                true
+            }
+            Key::Named(NamedKey::Home) => {
+                self.caret = 0;
+                true
+            }
+            Key::Named(NamedKey::End) => {
+                self.caret = length;
+                true
+            }
+            Key::Named(NamedKey::Enter) => {
+                if let Some(on_submit) = &mut self.on_submit {
+                    on_submit(&self.value);
+                }
+                false
+            }
+            Key::Named(NamedKey::Escape) => {
+                self.focused = false;
+                false
+            }
+            _ => match &event.text {
+                Some(text) => self.insert(text),
+                None => false,
+            },
+        };
+        if changed {
+            self.blink_elapsed = 0.0;
+        }
+        changed
+    }
+
+    /// Insert typed text at the caret, skipping control characters and
+    /// anything past `max_length`
+    fn insert(&mut self, text: &str) -> bool {
+        let mut inserted = false;
+        for character in text.chars().filter(|character| !character.is_control()) {
+            if self
+                .max_length
+                .is_some_and(|max_length| self.value.chars().count() >= max_length)
+            {
+                break;
+            }
+            let index = self.text_before_caret().len();
+            self.value.insert(index, character);
+            self.caret += 1;
+            inserted = true;
+        }
+        inserted
+    }
+
+    fn remove_at_caret(&mut self) {
+        let index = self.text_before_caret().len();
+        if index < self.value.len() {
+            self.value.remove(index);
+        }
+    }
+
+    /// Background and, while it is on, the caret
+    pub fn rectangles(&self) -> Vec<Rectangle> {
+        let (x, y) = self.position.calculate_actual_position();
+        let color = if self.focused {
+            self.focused_color
+        } else {
+            self.background_color
+        };
+        let mut rectangles =
+            vec![
+                Rectangle::new(x, y, self.position.width, self.position.height, color)
+                    .with_corner_radius(self.corner_radius),
+            ];
+
+        if self.caret_visible() {
+            let (text_x, text_y) = self.text_origin();
+            rectangles.push(Rectangle::new(
+                text_x + self.caret_offset,
+                text_y,
+                2.0,
+                self.style.line_height,
+                self.caret_color,
+            ));
+        }
+        rectangles
+    }
+}
@@ -0,0 +1,148 @@
+/// Which side of its button a floating tooltip (see `Button::with_tooltip`)
+/// appears on. A side without room flips to the opposite one, and the box is
+/// always kept inside the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TooltipPlacement {
+    Above,
+    Below,
+    Right,
+    /// Below, else above, else beside the button, whichever fits first
+    #[default]
+    Auto,
+}
+
+impl TooltipPlacement {
+    /// Top-left corner for a tooltip of `size` next to `anchor`
+    /// `(x, y, width, height)`, `gap` pixels away, in a window of
+    /// `window_size`
+    pub fn place(
+        self,
+        anchor: (f32, f32, f32, f32),
+        size: (f32, f32),
+        window_size: (f32, f32),
+        gap: f32,
+    ) -> (f32, f32) {
+        let (anchor_x, anchor_y, anchor_width, anchor_height) = anchor;
+        let (width, height) = size;
+        let (window_width, window_height) = window_size;
+
+        let centered_x = anchor_x + (anchor_width - width) / 2.0;
+        let centered_y = anchor_y + (anchor_height - height) / 2.0;
+        let above = (centered_x, anchor_y - gap - height);
+        let below = (centered_x, anchor_y + anchor_height + gap);
+        let right = (anchor_x + anchor_width + gap, centered_y);
+        let left = (anchor_x - gap - width, centered_y);
+
+        let fits_vertically = |(_, y): (f32, f32)| y >= 0.0 && y + height <= window_height;
+        let fits_horizontally = |(x, _): (f32, f32)| x >= 0.0 && x + width <= window_width;
+        let flip = |preferred, opposite, fits: &dyn Fn((f32, f32)) -> bool| {
+            if !fits(preferred) && fits(opposite) {
+                opposite
+            } else {
+                preferred
+            }
+        };
+
+        let (x, y) = match self {
+            TooltipPlacement::Above => flip(above, below, &fits_vertically),
+            TooltipPlacement::Below => flip(below, above, &fits_vertically),
+            TooltipPlacement::Right => flip(right, left, &fits_horizontally),
+            TooltipPlacement::Auto => [below, above, right, left]
+                .into_iter()
+                .find(|&position| fits_vertically(position) && fits_horizontally(position))
+                .unwrap_or(below),
+        };
+
+        // Slide along the edge rather than leave the window; a tooltip larger
+        // than the window keeps its top-left corner on screen
+        (
+            x.min(window_width - width).max(0.0),
+            y.min(window_height - height).max(0.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: (f32, f32) = (800.0, 600.0);
+    const SIZE: (f32, f32) = (100.0, 40.0);
+    const GAP: f32 = 10.0;
+
+    #[test]
+    fn placements_with_room_stay_on_their_side() {
+        let anchor = (300.0, 200.0, 200.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Above.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 150.0)
+        );
+        assert_eq!(
+            TooltipPlacement::Below.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 260.0)
+        );
+        assert_eq!(
+            TooltipPlacement::Right.place(anchor, SIZE, WINDOW, GAP),
+            (510.0, 205.0)
+        );
+        assert_eq!(
+            TooltipPlacement::Auto.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 260.0)
+        );
+    }
+
+    #[test]
+    fn above_flips_below_at_the_top_edge() {
+        let anchor = (300.0, 20.0, 200.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Above.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 80.0)
+        );
+    }
+
+    #[test]
+    fn below_flips_above_at_the_bottom_edge() {
+        let anchor = (300.0, 540.0, 200.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Below.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 490.0)
+        );
+        assert_eq!(
+            TooltipPlacement::Auto.place(anchor, SIZE, WINDOW, GAP),
+            (350.0, 490.0)
+        );
+    }
+
+    #[test]
+    fn right_flips_left_at_the_right_edge() {
+        let anchor = (650.0, 200.0, 100.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Right.place(anchor, SIZE, WINDOW, GAP),
+            (540.0, 205.0)
+        );
+    }
+
+    #[test]
+    fn tooltips_slide_inside_the_left_and_right_edges() {
+        let at_left = (0.0, 200.0, 40.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Below.place(at_left, SIZE, WINDOW, GAP),
+            (0.0, 260.0)
+        );
+        let at_right = (780.0, 200.0, 20.0, 50.0);
+        assert_eq!(
+            TooltipPlacement::Below.place(at_right, SIZE, WINDOW, GAP),
+            (700.0, 260.0)
+        );
+    }
+
+    #[test]
+    fn tooltip_larger_than_the_window_keeps_its_corner_on_screen() {
+        let anchor = (300.0, 200.0, 200.0, 50.0);
+        let size = (1000.0, 700.0);
+        assert_eq!(
+            TooltipPlacement::Auto.place(anchor, size, WINDOW, GAP),
+            (0.0, 0.0)
+        );
+    }
+}
@@ -1,46 +1,230 @@
-use crate::ui::button::{
-    create_primary_button_style, Button, ButtonAnchor, ButtonManager, ButtonPosition, TextAlign,
-};
+use crate::ui::animation::{EntranceAnimation, EntranceDirection};
+use crate::ui::button::{create_primary_button_style, DEFAULT_OVERLAY_COLOR};
+use crate::ui::prelude::*;
+use crate::ui::text::SharedTextRenderer;
 use egui_wgpu::wgpu::{self, Device, Queue, RenderPass, SurfaceConfiguration};
 use glyphon::{Color, Resolution};
+use std::sync::mpsc::Sender;
 use winit::dpi::PhysicalSize;
 use winit::event::WindowEvent;
-use winit::window::Window;
+use winit::window::{CursorIcon, Window};
+
+/// Icons the upgrade slots can show, as `(texture id, path)`. Loaded into the
+/// menu's icon renderer behind the loading screen at startup.
+pub const UPGRADE_ICON_TEXTURES: [(&str, &str); 7] = [
+    ("dash_icon", "assets/icons/dash-icon.png"),
+    ("head_start_icon", "assets/icons/head-start-icon.png"),
+    ("silent_step_icon", "assets/icons/silent-step-icon.png"),
+    (
+        "slower_seconds_icon",
+        "assets/icons/slower-seconds-icon.png",
+    ),
+    ("speed_up_icon", "assets/icons/speed-up-icon.png"),
+    ("tall_boots_icon", "assets/icons/tall-boots-icon.png"),
+    ("unknown_icon", "assets/icons/unknown-icon.png"),
+];
+
+/// Most slots the container fits side by side; `set_upgrades` drops offers
+/// beyond it
+pub const MAX_UPGRADE_SLOTS: usize = 8;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum UpgradeMenuAction {
-    SelectUpgrade1,
-    SelectUpgrade2,
-    SelectUpgrade3,
+    /// The slot at this index was picked; see `UpgradeMenu::upgrade`
+    SelectUpgrade(usize),
     None,
 }
 
+/// An upgrade offered in one slot of the `UpgradeMenu`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradeOffer {
+    pub name: String,
+    /// Level the upgrade reaches when picked, shown as "Level N"
+    pub level: u32,
+    /// Shown under the level; `None` keeps the placeholder text
+    pub description: Option<String>,
+    /// Texture id from `UPGRADE_ICON_TEXTURES`; `None` shows the blank icon
+    pub icon: Option<String>,
+}
+
+impl UpgradeOffer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            level: 1,
+            description: None,
+            icon: None,
+        }
+    }
+
+    pub fn with_level(mut self, level: u32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_icon(mut self, texture_id: &str) -> Self {
+        self.icon = Some(texture_id.to_string());
+        self
+    }
+}
+
 pub struct UpgradeMenu {
-    pub button_manager: ButtonManager,
+    pub button_manager: ButtonManager<UpgradeMenuAction>,
     pub visible: bool,
     pub last_action: UpgradeMenuAction,
+    /// Color of the full-window rectangle drawn behind the menu
+    pub overlay_color: [f32; 4],
+    /// Clear color while the menu is open; `None` keeps the game background
+    pub background_color: Option<[f32; 4]>,
+    /// Played by `show`; `None` makes the buttons appear at once
+    pub entrance_animation: Option<EntranceAnimation>,
+    /// Receives every action as soon as it happens, see `set_action_sender`
+    action_sender: Option<Sender<UpgradeMenuAction>>,
+    /// Whether the container and its slots can be moved by dragging
+    container_draggable: bool,
+    /// One per slot, in order; replace with `set_upgrades`
+    offers: Vec<UpgradeOffer>,
 }
 
 impl UpgradeMenu {
-    pub fn new(
+    /// Laid out for the window's current inner size
+    pub fn from_window(
         device: &Device,
         queue: &Queue,
         surface_format: wgpu::TextureFormat,
         window: &Window,
     ) -> Self {
-        let mut button_manager = ButtonManager::new(device, queue, surface_format, window);
+        Self::new(device, queue, surface_format, window.inner_size())
+    }
+
+    /// Build without a window, e.g. for offscreen rendering
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+    ) -> Self {
+        Self::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            &PipelineConfig::default(),
+        )
+    }
+
+    /// Like `new`, for passes with the attachments described by `config`
+    pub fn new_with_config(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::new_with_config(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+        ))
+    }
+
+    /// Like `new_with_config`, drawing text through `text_renderer` under
+    /// the "upgrade" namespace
+    pub fn with_text_renderer(
+        device: &Device,
+        queue: &Queue,
+        surface_format: wgpu::TextureFormat,
+        window_size: PhysicalSize<u32>,
+        config: &PipelineConfig,
+        text_renderer: &SharedTextRenderer,
+    ) -> Self {
+        Self::with_button_manager(ButtonManager::with_text_renderer(
+            device,
+            queue,
+            surface_format,
+            window_size,
+            config,
+            text_renderer,
+            "upgrade",
+        ))
+    }
+
+    fn with_button_manager(mut button_manager: ButtonManager<UpgradeMenuAction>) -> Self {
+        // Placeholders until the game offers real upgrades
+        let offers: Vec<UpgradeOffer> = (1..=3)
+            .map(|i| UpgradeOffer::new(&format!("Upgrade {}", i)))
+            .collect();
 
         // Create upgrade menu layout
-        Self::create_upgrade_layout(&mut button_manager, window.inner_size());
+        let window_size = button_manager.window_size;
+        Self::create_upgrade_layout(&mut button_manager, window_size, &offers);
 
         Self {
             button_manager,
             visible: false,
             last_action: UpgradeMenuAction::None,
+            overlay_color: DEFAULT_OVERLAY_COLOR,
+            background_color: None,
+            entrance_animation: Some(EntranceAnimation::new(0.08, EntranceDirection::FromBottom)),
+            action_sender: None,
+            container_draggable: false,
+            offers,
         }
     }
 
-    fn create_upgrade_layout(button_manager: &mut ButtonManager, window_size: PhysicalSize<u32>) {
+    /// Let the container be dragged around with its slots, for layout
+    /// experiments. A resize puts it back in the center.
+    pub fn set_container_draggable(&mut self, draggable: bool) {
+        self.container_draggable = draggable;
+        self.sync_container_drag();
+    }
+
+    fn sync_container_drag(&mut self) {
+        self.button_manager.remove_draggable("upgrade_container");
+        if !self.container_draggable {
+            return;
+        }
+        if let Some(container) = &self.button_manager.container_rect {
+            let draggable = (0..self.offers.len()).fold(
+                Draggable::new(
+                    "upgrade_container",
+                    container.x,
+                    container.y,
+                    container.width,
+                    container.height,
+                )
+                .with_container(),
+                |draggable, i| draggable.with_button(&Self::slot_id(i)),
+            );
+            self.button_manager.add_draggable(draggable);
+        }
+    }
+
+    /// Width of each of `count` slots side by side in the container, and the
+    /// space between them
+    fn slot_size(container_width: f32, count: usize) -> (f32, f32) {
+        let slot_spacing = container_width * 0.05; // 5% spacing between slots
+        let fitting = (container_width - slot_spacing * (count as f32 + 1.0)) / count.max(1) as f32;
+        (fitting.min(container_width * 0.25).max(0.0), slot_spacing)
+    }
+
+    /// Id of the button of slot `index`, counting from 0
+    fn slot_id(index: usize) -> String {
+        format!("upgrade_{}", index + 1)
+    }
+
+    fn create_upgrade_layout(
+        button_manager: &mut ButtonManager<UpgradeMenuAction>,
+        window_size: PhysicalSize<u32>,
+        offers: &[UpgradeOffer],
+    ) {
         let window_width = window_size.width as f32;
         let window_height = window_size.height as f32;
 
@@ -61,15 +245,17 @@ impl UpgradeMenu {
             )
             .with_corner_radius(20.0),
         );
+        // Slots growing on hover must stay inside the container
+        button_manager.set_clip(Some(ClipRegion::Container));
 
-        // Three upgrade slots (tall rounded rectangles)
-        let slot_width = container_width * 0.25; // 25% of container width
-        let slot_spacing = container_width * 0.05; // 5% spacing between slots
-        let total_slots_width = slot_width * 3.0 + slot_spacing * 2.0;
+        // One upgrade slot (tall rounded rectangle) per offer
+        let (slot_width, slot_spacing) = Self::slot_size(container_width, offers.len());
+        let total_slots_width =
+            slot_width * offers.len() as f32 + slot_spacing * offers.len().saturating_sub(1) as f32;
         let slots_start_x = container_x + (container_width - total_slots_width) / 2.0;
 
-        // Create three upgrade slot buttons
-        for i in 0..3 {
+        let mut slots = Vec::with_capacity(offers.len());
+        for (i, offer) in offers.iter().enumerate() {
             let slot_x = slots_start_x + i as f32 * (slot_width + slot_spacing);
 
             // Create a custom style for the upgrade slots (lighter grey)
@@ -78,24 +264,19 @@ impl UpgradeMenu {
             slot_style.hover_color = Color::rgb(180, 180, 180); // Slightly darker on hover
             slot_style.pressed_color = Color::rgb(160, 160, 160); // Even darker when pressed
             slot_style.corner_radius = 12.0; // Rounded corners
-            slot_style.padding = (8.0, 8.0); // Minimal padding
+            slot_style.padding = Padding::Pixels(8.0, 8.0); // Minimal padding
             slot_style.text_style.font_size = 32.0; // Doubled from 16.0
             slot_style.text_style.line_height = 48.0; // Doubled from 18.0 (approximate)
             slot_style.text_style.color = Color::rgb(50, 50, 50); // Dark text for contrast
-
-            let upgrade_text = match i {
-                0 => "Upgrade 1",
-                1 => "Upgrade 2",
-                2 => "Upgrade 3",
-                _ => "Unknown",
-            };
+            slot_style.vertical_align = VerticalAlign::Top; // Title above the icon
 
             // Calculate height proportion for tall buttons
             let margin = 0.1; // 10% margin
             let height_proportion = (container_height * (1.0 - 2.0 * margin)) / window_height;
             slot_style.spacing = crate::ui::button::ButtonSpacing::Tall(height_proportion);
 
-            let button = Button::new(&format!("upgrade_{}", i + 1), upgrade_text)
+            let mut button = Button::new(&Self::slot_id(i), &offer.name)
+                .with_id_action(UpgradeMenuAction::SelectUpgrade(i))
                 .with_style(slot_style)
                 .with_text_align(TextAlign::Center)
                 .with_level_text()
@@ -104,60 +285,159 @@ impl UpgradeMenu {
                     ButtonPosition::new(slot_x, 0.0, slot_width, 0.0) // Width set, height will be calculated by ButtonManager
                         .with_anchor(ButtonAnchor::TopLeft),
                 );
+            if let Some(icon) = &offer.icon {
+                button = button.with_icon(icon);
+            }
 
-            button_manager.add_button(button);
+            slots.push(button);
         }
 
-        // Update button positions to ensure proper layout
-        button_manager.update_button_positions();
+        // Adds them in order and lays them out
+        button_manager.add_buttons(slots);
+
+        // The slots are created with placeholder level and description text
+        for (i, offer) in offers.iter().enumerate() {
+            let Some(button) = button_manager.get_button_mut(&Self::slot_id(i)) else {
+                continue;
+            };
+            let level_id = button.level_text_id.clone();
+            let tooltip_id = button.tooltip_text_id.clone();
+            let mut text_renderer = button_manager.text_renderer.borrow_mut();
+            let mut result = match &level_id {
+                Some(level_id) => {
+                    text_renderer.set_text(level_id, &format!("Level {}", offer.level))
+                }
+                None => Ok(()),
+            };
+            if let (Some(tooltip_id), Some(description)) = (&tooltip_id, &offer.description) {
+                result = result.and_then(|_| text_renderer.set_text(tooltip_id, description));
+            }
+            if let Err(e) = result {
+                println!("Failed to fill upgrade slot: {}", e);
+            }
+        }
+    }
+
+    /// Replace the upgrades on offer, e.g. with the picks for a level-up.
+    /// Slots of the previous offers are removed along with their text. Only
+    /// the first `MAX_UPGRADE_SLOTS` offers are shown.
+    pub fn set_upgrades(&mut self, mut offers: Vec<UpgradeOffer>) {
+        if offers.len() > MAX_UPGRADE_SLOTS {
+            println!(
+                "Warning: {} upgrades offered, only the first {} fit",
+                offers.len(),
+                MAX_UPGRADE_SLOTS
+            );
+            offers.truncate(MAX_UPGRADE_SLOTS);
+        }
+        for i in 0..self.offers.len() {
+            if let Err(e) = self.button_manager.remove_button(&Self::slot_id(i)) {
+                println!("Failed to remove upgrade slot: {}", e);
+            }
+        }
+        self.offers = offers;
+
+        let window_size = self.button_manager.window_size;
+        Self::create_upgrade_layout(&mut self.button_manager, window_size, &self.offers);
+        self.recreate_layout_for_new_size();
+        self.button_manager.set_all_visible(self.visible);
+        self.button_manager.update_button_states();
+    }
+
+    /// The upgrades currently on offer, in slot order
+    pub fn upgrades(&self) -> &[UpgradeOffer] {
+        &self.offers
+    }
+
+    /// The upgrade behind a `UpgradeMenuAction::SelectUpgrade` index
+    pub fn upgrade(&self, index: usize) -> Option<&UpgradeOffer> {
+        self.offers.get(index)
     }
 
     pub fn show(&mut self) {
+        // show is called every frame while the menu is up; only animate on opening
+        let opening = !self.visible;
         self.visible = true;
         self.last_action = UpgradeMenuAction::None;
 
         // Show all buttons
-        for button in self.button_manager.buttons.values_mut() {
-            button.set_visible(true);
-        }
+        self.button_manager.set_all_visible(true);
 
         // Ensure button text is made visible and styled immediately
         self.button_manager.update_button_states();
+        if opening {
+            if let Some(entrance) = &self.entrance_animation {
+                self.button_manager.start_entrance(entrance.clone());
+            }
+        }
     }
 
     pub fn hide(&mut self) {
         self.visible = false;
         self.last_action = UpgradeMenuAction::None;
+        self.button_manager.stop_entrance();
 
         // Hide all buttons
-        for button in self.button_manager.buttons.values_mut() {
-            button.set_visible(false);
-        }
+        self.button_manager.set_all_visible(false);
+        // Nothing of the menu may be drawn by a host that renders it anyway
+        self.button_manager.rectangle_renderer.clear_rectangles();
     }
 
     pub fn is_visible(&self) -> bool {
         self.visible
     }
 
-    pub fn handle_input(&mut self, event: &WindowEvent) {
+    /// Cursor for the current mouse position while the menu is open
+    pub fn desired_cursor(&self) -> Option<CursorIcon> {
         if !self.visible {
-            return;
+            return None;
         }
+        self.button_manager.desired_cursor()
+    }
+
+    /// Advance the entrance animation and held repeat buttons by `delta_time` seconds
+    pub fn update(&mut self, delta_time: f32) {
+        for action in self.button_manager.update(delta_time) {
+            self.emit_action(action);
+        }
+    }
+
+    /// Whether buttons are still animating in, so frames must keep coming
+    pub fn is_animating(&self) -> bool {
+        self.button_manager.is_animating()
+    }
 
-        self.button_manager.handle_input(event);
+    /// Like `PauseMenu::handle_input`, returns whether the event was consumed
+    pub fn handle_input(&mut self, event: &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
 
-        // Check for button clicks
-        if self.button_manager.is_button_clicked("upgrade_1") {
-            self.last_action = UpgradeMenuAction::SelectUpgrade1;
+        for action in self.button_manager.handle_input(event) {
+            self.emit_action(action);
         }
 
-        if self.button_manager.is_button_clicked("upgrade_2") {
-            self.last_action = UpgradeMenuAction::SelectUpgrade2;
+        // A hovered slot grows, so draw it above its neighbors
+        if let Some(id) = self.button_manager.hovered_button().map(str::to_string) {
+            let _ = self.button_manager.bring_to_front(&id);
         }
+        self.button_manager.input_consumed()
+    }
 
-        if self.button_manager.is_button_clicked("upgrade_3") {
-            self.last_action = UpgradeMenuAction::SelectUpgrade3;
+    /// Send every future action to `sender` the moment it happens, so none are
+    /// lost between `get_last_action` polls. Polling keeps working alongside it.
+    pub fn set_action_sender(&mut self, sender: Sender<UpgradeMenuAction>) {
+        self.action_sender = Some(sender);
+    }
+
+    fn emit_action(&mut self, action: UpgradeMenuAction) {
+        if let Some(sender) = &self.action_sender {
+            // The receiver went away, stop sending
+            if sender.send(action.clone()).is_err() {
+                self.action_sender = None;
+            }
         }
+        self.last_action = action;
     }
 
     pub fn get_last_action(&mut self) -> UpgradeMenuAction {
@@ -202,24 +482,22 @@ impl UpgradeMenu {
             .with_corner_radius(20.0),
         );
 
-        // Three upgrade slots
-        let slot_width = container_width * 0.25;
-        let slot_spacing = container_width * 0.05;
-        let total_slots_width = slot_width * 3.0 + slot_spacing * 2.0;
+        // One slot per offer
+        let count = self.offers.len();
+        let (slot_width, slot_spacing) = Self::slot_size(container_width, count);
+        let total_slots_width =
+            slot_width * count as f32 + slot_spacing * count.saturating_sub(1) as f32;
         let slots_start_x = container_x + (container_width - total_slots_width) / 2.0;
 
-        // Update positions for all three upgrade buttons
-        for i in 0..3 {
+        // Update positions for all upgrade buttons
+        for i in 0..count {
             let slot_x = slots_start_x + i as f32 * (slot_width + slot_spacing);
 
             // Calculate height proportion for tall buttons
             let margin = 0.1; // 10% margin
             let height_proportion = (container_height * (1.0 - 2.0 * margin)) / window_height;
 
-            if let Some(button) = self
-                .button_manager
-                .get_button_mut(&format!("upgrade_{}", i + 1))
-            {
+            if let Some(button) = self.button_manager.get_button_mut(&Self::slot_id(i)) {
                 // Update the spacing to use the new height proportion
                 button.style.spacing = crate::ui::button::ButtonSpacing::Tall(height_proportion);
                 button.position.x = slot_x;
@@ -232,6 +510,28 @@ impl UpgradeMenu {
 
         // Update text positions
         self.button_manager.update_button_positions();
+        self.sync_container_drag();
+    }
+
+    /// Draw the menu, dimming what's behind it, into a render pass the host
+    /// owns; see `ButtonManager::render_to`. Draws nothing while hidden.
+    pub fn render_to(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        surface_config: &SurfaceConfiguration,
+        render_pass: &mut RenderPass,
+    ) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        self.button_manager.render_to(
+            device,
+            queue,
+            surface_config,
+            render_pass,
+            Some(self.overlay_color),
+        )
     }
 
     pub fn prepare(
@@ -246,8 +546,101 @@ impl UpgradeMenu {
     pub fn render(
         &mut self,
         device: &Device,
+        queue: &Queue,
         render_pass: &mut RenderPass,
     ) -> Result<(), glyphon::RenderError> {
-        self.button_manager.render(device, render_pass)
+        // Dim the scene behind the menu first
+        self.button_manager
+            .render_overlay(device, queue, render_pass, self.overlay_color);
+        self.button_manager.render(device, queue, render_pass)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::test_support::{self, FORMAT, WINDOW_SIZE};
+    use std::collections::HashSet;
+
+    fn offers(count: usize) -> Vec<UpgradeOffer> {
+        (1..=count)
+            .map(|i| UpgradeOffer::new(&format!("Upgrade {}", i)).with_description("Faster"))
+            .collect()
+    }
+
+    #[test]
+    fn slots_have_room_up_to_the_limit() {
+        let container_width = 1536.0;
+        for count in 1..=MAX_UPGRADE_SLOTS {
+            let (slot_width, slot_spacing) = UpgradeMenu::slot_size(container_width, count);
+            assert!(slot_width > 0.0);
+            let total = slot_width * count as f32 + slot_spacing * (count as f32 + 1.0);
+            assert!(total <= container_width + 0.01);
+        }
+        // Too many slots give no width rather than a negative one
+        assert_eq!(UpgradeMenu::slot_size(container_width, 40).0, 0.0);
+    }
+
+    #[test]
+    fn set_upgrades_then_resize_leaves_one_slot_per_offer() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut menu = UpgradeMenu::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        menu.show();
+        menu.set_upgrades(Vec::new());
+        let text_ids = |menu: &UpgradeMenu| -> HashSet<String> {
+            let namespace = &menu.button_manager.text_namespace;
+            menu.button_manager
+                .text_renderer
+                .borrow()
+                .text_buffers
+                .keys()
+                .filter(|id| TextRenderer::namespace_of(id) == namespace)
+                .cloned()
+                .collect()
+        };
+        let base_text_ids = text_ids(&menu);
+
+        menu.set_upgrades(offers(5));
+        menu.set_upgrades(offers(2));
+        menu.resize(
+            &queue,
+            Resolution {
+                width: 1280,
+                height: 720,
+            },
+        );
+
+        let slots: Vec<&Button<UpgradeMenuAction>> = menu
+            .button_manager
+            .buttons
+            .values()
+            .filter(|button| button.id.starts_with("upgrade_"))
+            .collect();
+        assert_eq!(slots.len(), 2);
+        let slot_text_ids: HashSet<String> = slots
+            .iter()
+            .flat_map(|button| {
+                std::iter::once(button.text_id.clone())
+                    .chain(button.level_text_id.clone())
+                    .chain(button.tooltip_text_id.clone())
+            })
+            .collect();
+        let added_text_ids: HashSet<String> = text_ids(&menu)
+            .difference(&base_text_ids)
+            .cloned()
+            .collect();
+        assert_eq!(added_text_ids, slot_text_ids);
+    }
+
+    #[test]
+    fn too_many_offers_are_dropped() {
+        let Some((device, queue)) = test_support::gpu() else {
+            return;
+        };
+        let mut menu = UpgradeMenu::new(&device, &queue, FORMAT, WINDOW_SIZE);
+        menu.set_upgrades(offers(MAX_UPGRADE_SLOTS + 3));
+        assert_eq!(menu.upgrades().len(), MAX_UPGRADE_SLOTS);
     }
 }